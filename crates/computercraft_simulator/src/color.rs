@@ -0,0 +1,164 @@
+/// One of the 16 colors available to ComputerCraft terminals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    White,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    Black,
+}
+
+impl Color {
+    /// All of the colors, ordered by their index.
+    pub const ALL: [Color; 16] = [
+        Color::White,
+        Color::Orange,
+        Color::Magenta,
+        Color::LightBlue,
+        Color::Yellow,
+        Color::Lime,
+        Color::Pink,
+        Color::Gray,
+        Color::LightGray,
+        Color::Cyan,
+        Color::Purple,
+        Color::Blue,
+        Color::Brown,
+        Color::Green,
+        Color::Red,
+        Color::Black,
+    ];
+
+    /// Returns the index of the color, from `0` (white) to `15` (black).
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// Returns the value of the color in the `colors` API (e.g., `colors.red` is `16384`).
+    pub fn value(self) -> u16 {
+        1 << self.index()
+    }
+
+    /// Returns the color for the given `colors` API value.
+    ///
+    /// Like ComputerCraft, only the highest set bit is considered.
+    pub fn from_value(value: u32) -> Option<Self> {
+        if value == 0 {
+            return None;
+        }
+
+        Self::from_index(31 - value.leading_zeros() as usize)
+    }
+
+    /// Returns the name of the color in the `colors` API.
+    pub fn name(self) -> &'static str {
+        match self {
+            Color::White => "white",
+            Color::Orange => "orange",
+            Color::Magenta => "magenta",
+            Color::LightBlue => "lightBlue",
+            Color::Yellow => "yellow",
+            Color::Lime => "lime",
+            Color::Pink => "pink",
+            Color::Gray => "gray",
+            Color::LightGray => "lightGray",
+            Color::Cyan => "cyan",
+            Color::Purple => "purple",
+            Color::Blue => "blue",
+            Color::Brown => "brown",
+            Color::Green => "green",
+            Color::Red => "red",
+            Color::Black => "black",
+        }
+    }
+
+    /// Returns the hex character used to represent the color in `term.blit`.
+    pub fn to_blit(self) -> u8 {
+        b"0123456789abcdef"[self.index()]
+    }
+
+    pub fn from_blit(blit: u8) -> Option<Self> {
+        let index = (blit as char).to_digit(16)?;
+        Self::from_index(index as usize)
+    }
+
+    /// Returns the default RGB value of the color, as a 24-bit integer.
+    pub fn default_rgb(self) -> u32 {
+        match self {
+            Color::White => 0xF0F0F0,
+            Color::Orange => 0xF2B233,
+            Color::Magenta => 0xE57FD8,
+            Color::LightBlue => 0x99B2F2,
+            Color::Yellow => 0xDEDE6C,
+            Color::Lime => 0x7FCC19,
+            Color::Pink => 0xF2B2CC,
+            Color::Gray => 0x4C4C4C,
+            Color::LightGray => 0x999999,
+            Color::Cyan => 0x4C99B2,
+            Color::Purple => 0xB266E5,
+            Color::Blue => 0x3366CC,
+            Color::Brown => 0x7F664C,
+            Color::Green => 0x57A64E,
+            Color::Red => 0xCC4C4C,
+            Color::Black => 0x111111,
+        }
+    }
+}
+
+/// Splits a 24-bit RGB value into its channels, each in the range `0.0..=1.0`.
+pub fn unpack_rgb(rgb: u32) -> [f64; 3] {
+    [
+        ((rgb >> 16) & 0xFF) as f64 / 255.0,
+        ((rgb >> 8) & 0xFF) as f64 / 255.0,
+        (rgb & 0xFF) as f64 / 255.0,
+    ]
+}
+
+/// Packs the channels, each in the range `0.0..=1.0`, into a 24-bit RGB value.
+pub fn pack_rgb([r, g, b]: [f64; 3]) -> u32 {
+    let channel = |value: f64| ((value.clamp(0.0, 1.0) * 255.0) as u32) & 0xFF;
+
+    (channel(r) << 16) | (channel(g) << 8) | channel(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_color_values() {
+        assert_eq!(Color::White.value(), 1);
+        assert_eq!(Color::Black.value(), 32768);
+        assert_eq!(Color::from_value(16384), Some(Color::Red));
+        assert_eq!(Color::from_value(16384 | 2), Some(Color::Red));
+        assert_eq!(Color::from_value(0), None);
+        assert_eq!(Color::from_value(65536), None);
+
+        assert_eq!(Color::Magenta.to_blit(), b'2');
+        assert_eq!(Color::from_blit(b'f'), Some(Color::Black));
+        assert_eq!(Color::from_blit(b'g'), None);
+    }
+
+    #[test]
+    fn test_pack_rgb() {
+        assert_eq!(pack_rgb([0.7, 0.2, 0.6]), 0xB23399);
+        assert_eq!(pack_rgb(unpack_rgb(0xB23399)), 0xB23399);
+    }
+}
@@ -0,0 +1,94 @@
+use minecraft::world::{Direction, Position};
+
+use crate::TurtleKind;
+
+/// The maximum length of a computer's label, in characters.
+pub const MAX_LABEL_LENGTH: usize = 32;
+
+/// The kind of device the computer is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputerKind {
+    Computer,
+    Turtle,
+    Pocket,
+}
+
+/// Whether the computer is a normal or an advanced one. Advanced computers have
+/// a colour terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputerFamily {
+    Normal,
+    Advanced,
+}
+
+impl From<ComputerFamily> for TurtleKind {
+    fn from(family: ComputerFamily) -> Self {
+        match family {
+            ComputerFamily::Normal => TurtleKind::Normal,
+            ComputerFamily::Advanced => TurtleKind::Advanced,
+        }
+    }
+}
+
+/// The identity of a simulated computer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Computer {
+    /// The ID of the computer, which is unique within its world.
+    pub id: u32,
+    /// The label set with `os.setComputerLabel`.
+    pub label: Option<String>,
+    pub kind: ComputerKind,
+    pub family: ComputerFamily,
+    /// The position of the computer in the world. Turtles use their own
+    /// position instead, as they move.
+    pub position: Position,
+    /// The direction the front of the computer faces. Turtles use their own
+    /// facing instead.
+    pub facing: Direction,
+}
+
+impl Computer {
+    pub fn new(id: u32, kind: ComputerKind, family: ComputerFamily) -> Self {
+        Self {
+            id,
+            label: None,
+            kind,
+            family,
+            position: Position::new(0, 0, 0),
+            facing: Direction::North,
+        }
+    }
+
+    /// Sets the computer's label, dropping unprintable characters and
+    /// truncating it to [`MAX_LABEL_LENGTH`] characters like ComputerCraft.
+    pub fn set_label(&mut self, label: Option<&str>) {
+        self.label = label.map(|label| {
+            label
+                .chars()
+                .filter(|&char| matches!(char as u32, 32..=126 | 160..=255))
+                .take(MAX_LABEL_LENGTH)
+                .collect()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_set_label() {
+        let mut computer = Computer::new(0, ComputerKind::Turtle, ComputerFamily::Advanced);
+
+        computer.set_label(Some("Miner\n#1 \u{1F600}"));
+        assert_eq!(computer.label.as_deref(), Some("Miner#1 "));
+
+        computer.set_label(Some(&"a".repeat(40)));
+        assert_eq!(computer.label, Some("a".repeat(MAX_LABEL_LENGTH)));
+
+        computer.set_label(None);
+        assert_eq!(computer.label, None);
+    }
+}
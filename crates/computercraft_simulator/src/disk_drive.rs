@@ -0,0 +1,80 @@
+use minecraft::{ItemId, ItemStack, Nbt, NbtValue};
+
+/// The item of a floppy disk.
+pub const FLOPPY_DISK: ItemId = ItemId::new_static("computercraft:disk");
+
+/// The space on a floppy disk, in bytes.
+pub const FLOPPY_DISK_CAPACITY: u64 = 125_000;
+
+/// Creates the item of the floppy disk with the ID, which is how
+/// ComputerCraft tells which files belong to it.
+pub fn floppy_disk(id: u32, label: Option<&str>) -> ItemStack {
+    let mut nbt = Nbt::from([("DiskId".to_string(), NbtValue::Int(id.into()))]);
+    if let Some(label) = label {
+        nbt.insert("Label".to_string(), NbtValue::String(label.to_string()));
+    }
+
+    ItemStack::new(FLOPPY_DISK, 1).with_nbt(nbt)
+}
+
+/// Returns the ID of the floppy disk, if the item is one.
+pub fn disk_id(stack: &ItemStack) -> Option<u32> {
+    if stack.name != FLOPPY_DISK {
+        return None;
+    }
+
+    stack.nbt.as_ref()?.get("DiskId")?.as_int()?.try_into().ok()
+}
+
+/// Returns the label of the floppy disk, if the item is one and has a label.
+pub fn disk_label(stack: &ItemStack) -> Option<&str> {
+    disk_id(stack)?;
+    stack.nbt.as_ref()?.get("Label")?.as_str()
+}
+
+/// A disk drive, which holds a single item such as a floppy disk.
+#[derive(Debug, Default)]
+pub struct DiskDrive {
+    disk: Option<ItemStack>,
+    /// Where the disk's files are mounted on the computer the drive is
+    /// attached to, if they are.
+    pub(crate) mount_path: Option<String>,
+}
+
+impl DiskDrive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a disk drive with the item already in it.
+    pub fn with_disk(disk: ItemStack) -> Self {
+        Self {
+            disk: Some(disk),
+            mount_path: None,
+        }
+    }
+
+    /// Returns the item in the drive.
+    pub fn disk(&self) -> Option<&ItemStack> {
+        self.disk.as_ref()
+    }
+
+    pub(crate) fn disk_mut(&mut self) -> Option<&mut ItemStack> {
+        self.disk.as_mut()
+    }
+
+    /// Puts one of the items in the drive if it is empty, returning the items
+    /// which didn't fit.
+    pub fn insert(&mut self, mut stack: ItemStack) -> Option<ItemStack> {
+        if self.disk.is_none() && !stack.is_empty() {
+            self.disk = Some(stack.split(1));
+        }
+
+        (!stack.is_empty()).then_some(stack)
+    }
+
+    /// Takes the item out of the drive.
+    pub fn eject(&mut self) -> Option<ItemStack> {
+        self.disk.take()
+    }
+}
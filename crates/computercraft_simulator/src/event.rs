@@ -0,0 +1,86 @@
+use std::rc::Rc;
+
+use crate::{HttpResponse, WebsocketConnection};
+
+/// A value that can be passed between Lua and Rust as part of an event.
+///
+/// Like in ComputerCraft, tables are copied when they are queued, and values
+/// that can't be copied (such as functions) become `nil`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventValue {
+    Nil,
+    Boolean(bool),
+    Integer(i64),
+    Number(f64),
+    String(Vec<u8>),
+    Table(Vec<(EventValue, EventValue)>),
+    /// A response to an HTTP request, which becomes a handle to read it.
+    HttpResponse {
+        response: Rc<HttpResponse>,
+        binary: bool,
+    },
+    /// A connection to a websocket server, which becomes a handle to it.
+    Websocket(WebsocketConnection),
+}
+
+impl EventValue {
+    /// Returns the value as a string, if it is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(bytes) => std::str::from_utf8(bytes).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for EventValue {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl From<i64> for EventValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<u32> for EventValue {
+    fn from(value: u32) -> Self {
+        Self::Integer(value as i64)
+    }
+}
+
+impl From<f64> for EventValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<&str> for EventValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for EventValue {
+    fn from(value: String) -> Self {
+        Self::String(value.into_bytes())
+    }
+}
+
+/// An event in a computer's event queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub args: Vec<EventValue>,
+}
+
+impl Event {
+    pub fn new(name: impl Into<String>, args: Vec<EventValue>) -> Self {
+        Self {
+            name: name.into(),
+            args,
+        }
+    }
+}
@@ -0,0 +1,979 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use thiserror::Error;
+
+/// The default capacity of a computer's filesystem, in bytes.
+pub const COMPUTER_SPACE_LIMIT: u64 = 1_000_000;
+
+/// The default capacity of a floppy disk, in bytes.
+pub const FLOPPY_SPACE_LIMIT: u64 = 125_000;
+
+/// The number of milliseconds in a Minecraft tick.
+const MILLIS_PER_TICK: u64 = 50;
+
+/// The space taken up by a directory, and the minimum space taken up by a file.
+const MINIMUM_FILE_SIZE: u64 = 500;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FileSystemError {
+    #[error("/{0}: No such file")]
+    NoSuchFile(String),
+    #[error("/{0}: Not a directory")]
+    NotADirectory(String),
+    #[error("/{0}: Access denied")]
+    AccessDenied(String),
+    #[error("/{0}: File exists")]
+    FileExists(String),
+    #[error("/{0}: Cannot write to directory")]
+    CannotWriteToDirectory(String),
+    #[error("/{0}: Can't copy a directory inside itself")]
+    CopyIntoItself(String),
+    #[error("Can't move a directory inside itself")]
+    MoveIntoItself,
+    #[error("Out of space")]
+    OutOfSpace,
+    #[error("Invalid Path")]
+    InvalidPath,
+}
+
+pub type FileSystemResult<T> = Result<T, FileSystemError>;
+
+/// The attributes of a file or directory, as returned by `fs.attributes`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileAttributes {
+    pub size: u64,
+    pub is_dir: bool,
+    pub is_read_only: bool,
+    /// When the file was created, in milliseconds since the world was
+    /// created. Files on the host are dated from the UNIX epoch instead.
+    pub created: u64,
+    /// When the file was last modified, in the same units as `created`.
+    pub modified: u64,
+    /// An alias of `modified`, kept for compatibility with older programs.
+    pub modification: u64,
+}
+
+#[derive(Debug, Clone)]
+enum EntryKind {
+    File(Vec<u8>),
+    /// A file on the host, which is only read once it is opened.
+    HostFile {
+        path: PathBuf,
+        size: u64,
+    },
+    Directory,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    kind: EntryKind,
+    created: u64,
+    modified: u64,
+}
+
+impl Entry {
+    /// Looks up the file or directory at the path on the host.
+    fn from_host(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let kind = if metadata.is_dir() {
+            EntryKind::Directory
+        } else {
+            EntryKind::HostFile {
+                path: path.to_path_buf(),
+                size: metadata.len(),
+            }
+        };
+        let millis = |time: std::io::Result<SystemTime>| {
+            time.ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_millis() as u64)
+        };
+        let modified = millis(metadata.modified());
+
+        Some(Self {
+            kind,
+            created: millis(metadata.created()).min(modified),
+            modified,
+        })
+    }
+
+    /// Creates an entry made at the time, in milliseconds.
+    fn new(kind: EntryKind, now: u64) -> Self {
+        Self {
+            kind,
+            created: now,
+            modified: now,
+        }
+    }
+
+    fn is_dir(&self) -> bool {
+        matches!(self.kind, EntryKind::Directory)
+    }
+
+    fn size(&self) -> u64 {
+        match &self.kind {
+            EntryKind::File(contents) => contents.len() as u64,
+            EntryKind::HostFile { size, .. } => *size,
+            EntryKind::Directory => 0,
+        }
+    }
+
+    /// Returns the space the entry takes up on its mount.
+    fn cost(&self) -> u64 {
+        self.size().max(MINIMUM_FILE_SIZE)
+    }
+}
+
+/// A single drive, such as a computer's hard drive, its ROM, or a floppy disk.
+///
+/// Paths within a mount are relative to its root and already sanitized.
+///
+/// A mount can be backed by a directory on the host, whose files show through
+/// underneath the mount's own. Changes are never written back to the host:
+/// files written to the mount are kept in memory, and deleted files are
+/// hidden.
+#[derive(Debug, Clone)]
+pub struct Mount {
+    drive: String,
+    capacity: u64,
+    read_only: bool,
+    entries: BTreeMap<String, Entry>,
+    host_dir: Option<PathBuf>,
+    /// The paths deleted from the host directory.
+    removed: BTreeSet<String>,
+}
+
+impl Mount {
+    /// Creates an empty, writable mount with the given capacity in bytes.
+    pub fn new(drive: impl Into<String>, capacity: u64) -> Self {
+        let mut entries = BTreeMap::new();
+        entries.insert(String::new(), Entry::new(EntryKind::Directory, 0));
+
+        Self {
+            drive: drive.into(),
+            capacity,
+            read_only: false,
+            entries,
+            host_dir: None,
+            removed: BTreeSet::new(),
+        }
+    }
+
+    /// Creates an empty, read-only mount.
+    pub fn read_only(drive: impl Into<String>) -> Self {
+        Self {
+            read_only: true,
+            ..Self::new(drive, 0)
+        }
+    }
+
+    /// Returns the name of the drive, as returned by `fs.getDrive`.
+    pub fn drive(&self) -> &str {
+        &self.drive
+    }
+
+    pub fn set_drive(&mut self, drive: impl Into<String>) {
+        self.drive = drive.into();
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Returns the directory on the host backing the mount, if there is one.
+    pub fn host_dir(&self) -> Option<&Path> {
+        self.host_dir.as_deref()
+    }
+
+    /// Backs the mount with a directory on the host.
+    ///
+    /// Files on the host don't count towards the mount's used space.
+    pub fn set_host_dir(&mut self, host_dir: impl Into<PathBuf>) {
+        self.host_dir = Some(host_dir.into());
+        self.removed.clear();
+    }
+
+    /// Hides the file or directory in the host directory from the computer,
+    /// as if it had been deleted.
+    pub fn hide_host_path(&mut self, path: &str) {
+        self.removed.insert(sanitize_path(path, false));
+    }
+
+    /// Returns the space used by the files and directories on the mount.
+    pub fn used_space(&self) -> u64 {
+        self.entries
+            .iter()
+            .filter(|(path, _)| !path.is_empty())
+            .map(|(_, entry)| entry.cost())
+            .sum()
+    }
+
+    pub fn free_space(&self) -> u64 {
+        if self.read_only {
+            return 0;
+        }
+
+        self.capacity.saturating_sub(self.used_space())
+    }
+
+    /// Adds a file to the mount, creating its parent directories.
+    ///
+    /// Unlike writing through a [`FileSystem`], this ignores the mount being
+    /// read-only and its capacity, so it can be used to set up a ROM. Files
+    /// added this way are dated to when the world was created.
+    pub fn insert_file(&mut self, path: &str, contents: impl Into<Vec<u8>>) {
+        let path = sanitize_path(path, false);
+        self.insert_parents(&path, 0);
+        self.entries
+            .insert(path, Entry::new(EntryKind::File(contents.into()), 0));
+    }
+
+    /// Copies the files in a directory on the host into the mount.
+    pub fn insert_dir(&mut self, path: &str, host_dir: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = sanitize_path(path, false);
+        self.insert_parents(&path, 0);
+        self.entries
+            .entry(path.clone())
+            .or_insert_with(|| Entry::new(EntryKind::Directory, 0));
+
+        for entry in std::fs::read_dir(host_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let child = join(&path, &name);
+
+            if entry.file_type()?.is_dir() {
+                self.insert_dir(&child, entry.path())?;
+            } else {
+                self.insert_file(&child, std::fs::read(entry.path())?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn insert_parents(&mut self, path: &str, now: u64) {
+        let mut parent = parent_of(path);
+        while !parent.is_empty() {
+            if self.get(parent).is_none() {
+                self.entries
+                    .insert(parent.to_string(), Entry::new(EntryKind::Directory, now));
+            }
+            parent = parent_of(parent);
+        }
+    }
+
+    fn get(&self, path: &str) -> Option<Cow<'_, Entry>> {
+        if let Some(entry) = self.entries.get(path) {
+            return Some(Cow::Borrowed(entry));
+        }
+
+        Entry::from_host(&self.host_path(path)?).map(Cow::Owned)
+    }
+
+    /// Returns where the path is on the host, unless it has been deleted.
+    fn host_path(&self, path: &str) -> Option<PathBuf> {
+        let host_dir = self.host_dir.as_ref()?;
+
+        let mut current = path;
+        while !current.is_empty() {
+            if self.removed.contains(current) {
+                return None;
+            }
+            current = parent_of(current);
+        }
+
+        Some(host_dir.join(path))
+    }
+
+    fn list(&self, path: &str) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|child| !child.is_empty() && parent_of(child) == path)
+            .map(|child| name_of(child).to_string())
+            .collect();
+
+        if let Some(host_path) = self.host_path(path)
+            && let Ok(host_entries) = std::fs::read_dir(host_path)
+        {
+            for entry in host_entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if !self.removed.contains(&join(path, &name)) {
+                    names.push(name);
+                }
+            }
+        }
+
+        names
+    }
+
+    /// Returns the paths of the entry and everything inside it.
+    fn descendants(&self, path: &str) -> Vec<String> {
+        self.entries
+            .keys()
+            .filter(|child| path.is_empty() || is_same_or_inside(child, path))
+            .cloned()
+            .collect()
+    }
+
+    fn ensure_space(&self, freed: u64, needed: u64) -> FileSystemResult<()> {
+        if self.used_space() - freed + needed > self.capacity {
+            return Err(FileSystemError::OutOfSpace);
+        }
+
+        Ok(())
+    }
+
+    /// Creates the directory and any missing parents.
+    fn make_dir(&mut self, path: &str, now: u64) -> FileSystemResult<()> {
+        let mut missing = Vec::new();
+        let mut current = path;
+        while !current.is_empty() && self.get(current).is_none() {
+            missing.push(current.to_string());
+            current = parent_of(current);
+        }
+
+        self.ensure_space(0, missing.len() as u64 * MINIMUM_FILE_SIZE)?;
+        for path in missing {
+            self.entries
+                .insert(path, Entry::new(EntryKind::Directory, now));
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, path: &str, contents: Vec<u8>, now: u64) -> FileSystemResult<()> {
+        let freed = self.entries.get(path).map_or(0, Entry::cost);
+        let mut needed = (contents.len() as u64).max(MINIMUM_FILE_SIZE);
+        let mut current = parent_of(path);
+        while !current.is_empty() && self.get(current).is_none() {
+            needed += MINIMUM_FILE_SIZE;
+            current = parent_of(current);
+        }
+        self.ensure_space(freed, needed)?;
+
+        self.insert_parents(path, now);
+        match self.entries.get_mut(path) {
+            Some(entry) => {
+                entry.kind = EntryKind::File(contents);
+                entry.modified = now;
+            }
+            None => {
+                self.entries
+                    .insert(path.to_string(), Entry::new(EntryKind::File(contents), now));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn delete(&mut self, path: &str) {
+        for path in self.descendants(path) {
+            if !path.is_empty() {
+                self.entries.remove(&path);
+            }
+        }
+
+        if self.host_dir.is_some() && !path.is_empty() {
+            self.removed.insert(path.to_string());
+        }
+    }
+}
+
+/// The filesystem of a single computer, made up of the mounts attached to it.
+///
+/// Paths are given the same way as to the `fs` API, and are sanitized the same
+/// way ComputerCraft does.
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    mounts: BTreeMap<String, Mount>,
+    /// The world's time in ticks, which files are dated with.
+    clock: Rc<Cell<u64>>,
+}
+
+impl FileSystem {
+    /// Creates a filesystem with an empty hard drive and an empty, read-only `/rom`.
+    pub fn new() -> Self {
+        Self::with_capacity(COMPUTER_SPACE_LIMIT)
+    }
+
+    /// Creates a filesystem whose hard drive has the given capacity in bytes.
+    pub fn with_capacity(capacity: u64) -> Self {
+        let mut mounts = BTreeMap::new();
+        mounts.insert(String::new(), Mount::new("hdd", capacity));
+        mounts.insert("rom".to_string(), Mount::read_only("rom"));
+
+        Self {
+            mounts,
+            clock: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// Dates the files written from now on with the time from the clock,
+    /// in ticks, rather than with the start of the world.
+    pub(crate) fn set_clock(&mut self, clock: Rc<Cell<u64>>) {
+        self.clock = clock;
+    }
+
+    /// Returns the current time, in milliseconds.
+    fn now(&self) -> u64 {
+        self.clock.get() * MILLIS_PER_TICK
+    }
+
+    /// Attaches the mount at the given path, replacing any existing mount there.
+    pub fn mount(&mut self, path: &str, mount: Mount) -> Option<Mount> {
+        self.mounts.insert(sanitize_path(path, false), mount)
+    }
+
+    /// Detaches the mount at the given path, returning it.
+    pub fn unmount(&mut self, path: &str) -> Option<Mount> {
+        let path = sanitize_path(path, false);
+        if path.is_empty() {
+            return None;
+        }
+
+        self.mounts.remove(&path)
+    }
+
+    /// Returns the mount attached at the given path.
+    pub fn mount_at(&self, path: &str) -> Option<&Mount> {
+        self.mounts.get(&sanitize_path(path, false))
+    }
+
+    /// Returns the mount attached at the given path.
+    pub fn mount_at_mut(&mut self, path: &str) -> Option<&mut Mount> {
+        self.mounts.get_mut(&sanitize_path(path, false))
+    }
+
+    /// Sanitizes the path, and finds the mount it belongs to and the path
+    /// within that mount.
+    fn resolve(&self, path: &str) -> FileSystemResult<(String, &str, String)> {
+        let path = sanitize_path(path, false);
+        if path == ".." || path.starts_with("../") {
+            return Err(FileSystemError::InvalidPath);
+        }
+
+        let (mount_path, _) = self
+            .mounts
+            .iter()
+            .filter(|(mount_path, _)| mount_path.is_empty() || is_same_or_inside(&path, mount_path))
+            .max_by_key(|(mount_path, _)| mount_path.len())
+            .expect("the root mount should always exist");
+        let local = match mount_path.as_str() {
+            "" => path.clone(),
+            mount_path => path[mount_path.len()..].trim_start_matches('/').to_string(),
+        };
+        let mount_path = mount_path.as_str();
+
+        Ok((path, mount_path, local))
+    }
+
+    fn resolve_mount(&self, path: &str) -> FileSystemResult<(String, &Mount, String)> {
+        let (path, mount_path, local) = self.resolve(path)?;
+
+        Ok((path, &self.mounts[mount_path], local))
+    }
+
+    fn resolve_writable(&mut self, path: &str) -> FileSystemResult<(String, &mut Mount, String)> {
+        let (path, mount_path, local) = self.resolve(path)?;
+        let mount_path = mount_path.to_string();
+        let mount = self
+            .mounts
+            .get_mut(&mount_path)
+            .expect("the mount should exist");
+        if mount.read_only {
+            return Err(FileSystemError::AccessDenied(path));
+        }
+
+        Ok((path, mount, local))
+    }
+
+    fn entry(&self, path: &str) -> FileSystemResult<Option<Cow<'_, Entry>>> {
+        let (_, mount, local) = self.resolve_mount(path)?;
+
+        Ok(mount.get(&local))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        matches!(self.entry(path), Ok(Some(_)))
+    }
+
+    pub fn is_dir(&self, path: &str) -> bool {
+        matches!(self.entry(path), Ok(Some(entry)) if entry.is_dir())
+    }
+
+    pub fn is_read_only(&self, path: &str) -> bool {
+        self.resolve_mount(path)
+            .is_ok_and(|(_, mount, _)| mount.read_only)
+    }
+
+    /// Returns the name of the drive the path is on, if it exists.
+    pub fn drive(&self, path: &str) -> Option<&str> {
+        let (_, mount, local) = self.resolve_mount(path).ok()?;
+        mount.get(&local)?;
+
+        Some(&mount.drive)
+    }
+
+    /// Returns the sorted names of the files and directories in the directory.
+    pub fn list(&self, path: &str) -> FileSystemResult<Vec<String>> {
+        let (path, mount, local) = self.resolve_mount(path)?;
+        if !mount.get(&local).is_some_and(|entry| entry.is_dir()) {
+            return Err(FileSystemError::NotADirectory(path));
+        }
+
+        let mut names = mount.list(&local);
+        for mount_path in self.mounts.keys() {
+            if !mount_path.is_empty() && parent_of(mount_path) == path {
+                names.push(name_of(mount_path).to_string());
+            }
+        }
+        names.sort();
+        names.dedup();
+
+        Ok(names)
+    }
+
+    /// Returns the size of the file in bytes, or `0` for a directory.
+    pub fn size(&self, path: &str) -> FileSystemResult<u64> {
+        let (path, mount, local) = self.resolve_mount(path)?;
+
+        mount
+            .get(&local)
+            .map(|entry| entry.size())
+            .ok_or(FileSystemError::NoSuchFile(path))
+    }
+
+    pub fn attributes(&self, path: &str) -> FileSystemResult<FileAttributes> {
+        let (path, mount, local) = self.resolve_mount(path)?;
+        let entry = mount.get(&local).ok_or(FileSystemError::NoSuchFile(path))?;
+
+        Ok(FileAttributes {
+            size: entry.size(),
+            is_dir: entry.is_dir(),
+            is_read_only: mount.read_only,
+            created: entry.created,
+            modified: entry.modified,
+            modification: entry.modified,
+        })
+    }
+
+    /// Returns the free space on the mount the path is on.
+    pub fn free_space(&self, path: &str) -> FileSystemResult<u64> {
+        let (_, mount, _) = self.resolve_mount(path)?;
+
+        Ok(mount.free_space())
+    }
+
+    /// Returns the capacity of the mount the path is on, or `None` if it is read-only.
+    pub fn capacity(&self, path: &str) -> FileSystemResult<Option<u64>> {
+        let (_, mount, _) = self.resolve_mount(path)?;
+
+        Ok((!mount.read_only).then_some(mount.capacity))
+    }
+
+    pub fn read_file(&self, path: &str) -> FileSystemResult<Vec<u8>> {
+        let (path, mount, local) = self.resolve_mount(path)?;
+
+        match mount.get(&local).as_deref().map(|entry| &entry.kind) {
+            Some(EntryKind::File(contents)) => Ok(contents.clone()),
+            Some(EntryKind::HostFile {
+                path: host_path, ..
+            }) => std::fs::read(host_path).map_err(|_| FileSystemError::NoSuchFile(path)),
+            _ => Err(FileSystemError::NoSuchFile(path)),
+        }
+    }
+
+    /// Replaces the contents of the file, creating it and its parent directories.
+    pub fn write_file(&mut self, path: &str, contents: impl Into<Vec<u8>>) -> FileSystemResult<()> {
+        self.check_writable_file(path)?;
+
+        let now = self.now();
+        let (_, mount, local) = self.resolve_writable(path)?;
+        mount.write(&local, contents.into(), now)
+    }
+
+    fn check_writable_file(&self, path: &str) -> FileSystemResult<()> {
+        let (path, mount, local) = self.resolve_mount(path)?;
+        if mount.read_only {
+            return Err(FileSystemError::AccessDenied(path));
+        }
+        if local.is_empty() || mount.get(&local).is_some_and(|entry| entry.is_dir()) {
+            return Err(FileSystemError::CannotWriteToDirectory(path));
+        }
+
+        let mut parent = parent_of(&local);
+        while !parent.is_empty() {
+            if mount.get(parent).is_some_and(|entry| !entry.is_dir()) {
+                return Err(FileSystemError::AccessDenied(path));
+            }
+            parent = parent_of(parent);
+        }
+
+        Ok(())
+    }
+
+    /// Creates the directory and any missing parents.
+    pub fn make_dir(&mut self, path: &str) -> FileSystemResult<()> {
+        let now = self.now();
+        let (path, mount, local) = self.resolve_writable(path)?;
+
+        let mut current = local.as_str();
+        while !current.is_empty() {
+            if mount.get(current).is_some_and(|entry| !entry.is_dir()) {
+                return Err(FileSystemError::FileExists(path));
+            }
+            current = parent_of(current);
+        }
+
+        mount.make_dir(&local, now)
+    }
+
+    /// Deletes the file or directory, along with everything inside it.
+    ///
+    /// Deleting a path that doesn't exist does nothing.
+    pub fn delete(&mut self, path: &str) -> FileSystemResult<()> {
+        let (path, mount, local) = self.resolve_writable(path)?;
+        if local.is_empty() {
+            return Err(FileSystemError::AccessDenied(path));
+        }
+
+        mount.delete(&local);
+
+        Ok(())
+    }
+
+    /// Copies the file or directory to a path that doesn't exist yet.
+    pub fn copy(&mut self, from: &str, to: &str) -> FileSystemResult<()> {
+        let from = sanitize_path(from, false);
+        let to = sanitize_path(to, false);
+
+        if self.is_read_only(&to) {
+            return Err(FileSystemError::AccessDenied(to));
+        }
+        if !self.exists(&from) {
+            return Err(FileSystemError::NoSuchFile(from));
+        }
+        if self.exists(&to) {
+            return Err(FileSystemError::FileExists(to));
+        }
+        if is_same_or_inside(&to, &from) {
+            return Err(FileSystemError::CopyIntoItself(from));
+        }
+
+        self.copy_recursive(&from, &to)
+    }
+
+    fn copy_recursive(&mut self, from: &str, to: &str) -> FileSystemResult<()> {
+        if self.is_dir(from) {
+            self.make_dir(to)?;
+            for name in self.list(from)? {
+                self.copy_recursive(&join(from, &name), &join(to, &name))?;
+            }
+
+            Ok(())
+        } else {
+            let contents = self.read_file(from)?;
+            self.write_file(to, contents)
+        }
+    }
+
+    /// Moves the file or directory to a path that doesn't exist yet.
+    pub fn rename(&mut self, from: &str, to: &str) -> FileSystemResult<()> {
+        let from = sanitize_path(from, false);
+        let to = sanitize_path(to, false);
+
+        if self.is_read_only(&from) {
+            return Err(FileSystemError::AccessDenied(from));
+        }
+        if self.is_read_only(&to) {
+            return Err(FileSystemError::AccessDenied(to));
+        }
+        if !self.exists(&from) {
+            return Err(FileSystemError::NoSuchFile(from));
+        }
+        if self.exists(&to) {
+            return Err(FileSystemError::FileExists(to));
+        }
+        if is_same_or_inside(&to, &from) {
+            return Err(FileSystemError::MoveIntoItself);
+        }
+
+        self.copy_recursive(&from, &to)?;
+        self.delete(&from)
+    }
+
+    /// Returns the sorted paths matching the pattern, where `*` matches any
+    /// part of a single path component.
+    pub fn find(&self, pattern: &str) -> FileSystemResult<Vec<String>> {
+        let pattern = sanitize_path(pattern, true);
+        let mut matches = Vec::new();
+        self.find_in("", &pattern, &mut matches)?;
+        matches.sort();
+
+        Ok(matches)
+    }
+
+    fn find_in(&self, dir: &str, pattern: &str, matches: &mut Vec<String>) -> FileSystemResult<()> {
+        for name in self.list(dir)? {
+            let path = join(dir, &name);
+            if matches_wildcard(pattern.as_bytes(), path.as_bytes()) {
+                matches.push(path.clone());
+            }
+            if self.is_dir(&path) {
+                self.find_in(&path, pattern, matches)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Normalizes a path the way ComputerCraft does: backslashes become slashes,
+/// special characters are removed, and `.` and `..` components are resolved.
+pub fn sanitize_path(path: &str, allow_wildcards: bool) -> String {
+    let path: String = path
+        .replace('\\', "/")
+        .chars()
+        .filter(|&char| {
+            char >= ' ' && !"\":<>?|".contains(char) && (allow_wildcards || char != '*')
+        })
+        .collect();
+
+    let mut parts: Vec<String> = Vec::new();
+    for part in path.split('/') {
+        let part = part.trim();
+        if part.is_empty() || part.chars().all(|char| char == '.') && part != ".." {
+            continue;
+        }
+
+        if part == ".." {
+            match parts.last() {
+                Some(last) if last != ".." => {
+                    parts.pop();
+                }
+                _ => parts.push("..".to_string()),
+            }
+        } else {
+            parts.push(part.chars().take(255).collect());
+        }
+    }
+
+    parts.join("/")
+}
+
+/// Joins the paths together, like `fs.combine`.
+pub fn combine<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    let path = parts
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    sanitize_path(&path, true)
+}
+
+/// Returns the final component of the path, like `fs.getName`.
+pub fn get_name(path: &str) -> String {
+    let path = sanitize_path(path, true);
+    if path.is_empty() {
+        return "root".to_string();
+    }
+
+    name_of(&path).to_string()
+}
+
+/// Returns the parent directory of the path, like `fs.getDir`.
+pub fn get_dir(path: &str) -> String {
+    let path = sanitize_path(path, true);
+    if path.is_empty() {
+        return "..".to_string();
+    }
+
+    parent_of(&path).to_string()
+}
+
+fn parent_of(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn name_of(path: &str) -> &str {
+    path.rsplit_once('/').map_or(path, |(_, name)| name)
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+/// Returns whether the path is the directory, or somewhere inside it.
+fn is_same_or_inside(path: &str, dir: &str) -> bool {
+    path == dir
+        || path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Matches the path against a pattern where `*` matches anything but `/`.
+fn matches_wildcard(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((b'*', rest)) => {
+            for len in 0..=path.len() {
+                if matches_wildcard(rest, &path[len..]) {
+                    return true;
+                }
+                if path.get(len) == Some(&b'/') {
+                    break;
+                }
+            }
+
+            false
+        }
+        Some((char, rest)) => path
+            .split_first()
+            .is_some_and(|(first, path)| first == char && matches_wildcard(rest, path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_sanitize_path() {
+        assert_eq!(sanitize_path("/foo//bar/", false), "foo/bar");
+        assert_eq!(sanitize_path("foo\\.\\bar/../baz", false), "foo/baz");
+        assert_eq!(sanitize_path("../../x", false), "../../x");
+        assert_eq!(sanitize_path("a/.../b?*", false), "a/b");
+        assert_eq!(combine(["a/b", "../c", "*.lua"]), "a/c/*.lua");
+        assert_eq!(get_name("/"), "root");
+        assert_eq!(get_name("a/b.lua"), "b.lua");
+        assert_eq!(get_dir("a/b.lua"), "a");
+        assert_eq!(get_dir(""), "..");
+    }
+
+    #[test]
+    fn test_read_write() {
+        let mut fs = FileSystem::new();
+
+        fs.write_file("logs/today/debug.txt", "hello").unwrap();
+        assert_eq!(fs.read_file("/logs/today/debug.txt").unwrap(), b"hello");
+        assert!(fs.is_dir("logs/today"));
+        assert_eq!(fs.list("").unwrap(), vec!["logs", "rom"]);
+        assert_eq!(fs.size("logs/today/debug.txt").unwrap(), 5);
+        assert_eq!(
+            fs.read_file("logs/missing.txt"),
+            Err(FileSystemError::NoSuchFile("logs/missing.txt".to_string()))
+        );
+        assert_eq!(
+            fs.write_file("logs", "oops").unwrap_err().to_string(),
+            "/logs: Cannot write to directory"
+        );
+        assert_eq!(
+            fs.write_file("rom/x", "oops").unwrap_err().to_string(),
+            "/rom/x: Access denied"
+        );
+    }
+
+    #[test]
+    fn test_copy_move_delete() {
+        let mut fs = FileSystem::new();
+        fs.write_file("a/one.lua", "1").unwrap();
+        fs.write_file("a/b/two.lua", "2").unwrap();
+
+        fs.copy("a", "c").unwrap();
+        assert_eq!(fs.read_file("c/b/two.lua").unwrap(), b"2");
+        assert_eq!(
+            fs.copy("a", "a/d").unwrap_err().to_string(),
+            "/a: Can't copy a directory inside itself"
+        );
+
+        fs.rename("c", "d").unwrap();
+        assert!(!fs.exists("c"));
+        assert_eq!(fs.find("*/*.lua").unwrap(), vec!["a/one.lua", "d/one.lua"]);
+
+        fs.delete("a").unwrap();
+        assert!(!fs.exists("a/b/two.lua"));
+        assert_eq!(
+            fs.delete("rom").unwrap_err().to_string(),
+            "/rom: Access denied"
+        );
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut fs = FileSystem::with_capacity(2_000);
+
+        fs.write_file("small.txt", "x").unwrap();
+        assert_eq!(fs.free_space("").unwrap(), 1_500);
+        assert_eq!(
+            fs.write_file("big.txt", vec![0; 1_600]),
+            Err(FileSystemError::OutOfSpace)
+        );
+        fs.write_file("small.txt", vec![0; 1_900]).unwrap();
+        assert_eq!(fs.free_space("").unwrap(), 100);
+        assert_eq!(fs.free_space("rom").unwrap(), 0);
+        assert_eq!(fs.capacity("rom").unwrap(), None);
+    }
+
+    #[test]
+    fn test_host_dir() {
+        let host_dir = std::env::temp_dir().join(format!("cc-host-dir-{}", std::process::id()));
+        std::fs::create_dir_all(host_dir.join("lib")).unwrap();
+        std::fs::write(host_dir.join("lib/move.lua"), "return {}").unwrap();
+
+        let mut fs = FileSystem::with_capacity(2_000);
+        fs.mount_at_mut("").unwrap().set_host_dir(&host_dir);
+
+        assert_eq!(fs.read_file("lib/move.lua").unwrap(), b"return {}");
+        assert_eq!(fs.size("lib/move.lua").unwrap(), 9);
+        assert_eq!(fs.list("").unwrap(), vec!["lib", "rom"]);
+        assert_eq!(fs.free_space("").unwrap(), 2_000);
+
+        fs.mount_at_mut("").unwrap().hide_host_path("lib/move.lua");
+        assert!(!fs.exists("lib/move.lua"));
+        assert_eq!(fs.list("lib").unwrap(), Vec::<String>::new());
+
+        fs.write_file("lib/move.lua", "changed").unwrap();
+        fs.write_file("lib/new.lua", "new").unwrap();
+        assert_eq!(
+            fs.find("*/*.lua").unwrap(),
+            vec!["lib/move.lua", "lib/new.lua"]
+        );
+        assert_eq!(
+            std::fs::read(host_dir.join("lib/move.lua")).unwrap(),
+            b"return {}"
+        );
+
+        fs.delete("lib").unwrap();
+        assert!(!fs.exists("lib/move.lua"));
+        assert!(host_dir.join("lib/move.lua").exists());
+        fs.make_dir("lib").unwrap();
+        assert_eq!(fs.list("lib").unwrap(), Vec::<String>::new());
+
+        std::fs::remove_dir_all(host_dir).unwrap();
+    }
+}
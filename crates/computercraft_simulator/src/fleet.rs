@@ -0,0 +1,226 @@
+use thiserror::Error;
+
+use crate::simulator::{Program, ProgramStatus};
+use crate::{Simulator, SimulatorError, SimulatorResult};
+
+/// A program in a [`Fleet`] which failed.
+#[derive(Error, Debug)]
+#[error("Computer {id} failed: {source}")]
+pub struct FleetError {
+    /// The ID of the computer the program ran on.
+    pub id: u32,
+    pub source: SimulatorError,
+}
+
+/// Several computers sharing a world, running their programs side by side.
+///
+/// Like in Minecraft, each turtle action takes a tick. Every tick, each
+/// running program runs until its next turtle action completes, or until it
+/// waits for an event, in order of computer ID, so runs are reproducible.
+/// Timers go off as the ticks pass.
+pub struct Fleet {
+    /// The computers, in order of ID.
+    computers: Vec<Simulator>,
+    /// The program running on each computer.
+    programs: Vec<Option<Program>>,
+    ticks: u64,
+}
+
+impl Fleet {
+    /// Creates a fleet from computers in the same world, made with
+    /// [`Simulator::new_computer`].
+    pub fn new(mut computers: Vec<Simulator>) -> Self {
+        computers.sort_by_key(|computer| computer.computer().id);
+        let programs = computers.iter().map(|_| None).collect();
+
+        Self {
+            computers,
+            programs,
+            ticks: 0,
+        }
+    }
+
+    /// Returns the computer with the given ID.
+    pub fn computer(&self, id: u32) -> Option<&Simulator> {
+        self.computers
+            .iter()
+            .find(|computer| computer.computer().id == id)
+    }
+
+    /// Returns the computers, in order of ID.
+    pub fn computers(&self) -> &[Simulator] {
+        &self.computers
+    }
+
+    /// Returns the number of ticks run so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Starts the program on the computer with the given ID, like
+    /// [`Simulator::run_program`]. It runs as the fleet ticks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fleet has no computer with the ID.
+    pub fn start_program(&mut self, id: u32, command: &str, args: &[&str]) -> SimulatorResult<()> {
+        let index = self
+            .computers
+            .iter()
+            .position(|computer| computer.computer().id == id)
+            .unwrap_or_else(|| panic!("the fleet has no computer with ID {id}"));
+
+        self.programs[index] = Some(self.computers[index].start_program(command, args)?);
+
+        Ok(())
+    }
+
+    /// Returns whether any of the computers is still running a program.
+    pub fn is_running(&self) -> bool {
+        self.programs.iter().any(Option::is_some)
+    }
+
+    /// Runs a tick, letting each running program carry out its next turtle
+    /// action.
+    ///
+    /// Programs which fail stop, but the others still get their turn. The
+    /// first failure is returned. If every program is waiting for an event
+    /// which can never happen, the first of them fails with
+    /// [`SimulatorError::EventQueueEmpty`].
+    pub fn tick(&mut self) -> Result<(), FleetError> {
+        let mut first_error = None;
+        let mut all_waiting = true;
+
+        for (computer, program) in self.computers.iter().zip(&mut self.programs) {
+            let Some(running) = program else {
+                continue;
+            };
+
+            match computer.resume_program(running) {
+                Ok(ProgramStatus::Running) => all_waiting = false,
+                Ok(ProgramStatus::Waiting) => {}
+                Ok(ProgramStatus::Finished) => *program = None,
+                Err(err) => {
+                    *program = None;
+                    first_error.get_or_insert(FleetError {
+                        id: computer.computer().id,
+                        source: err,
+                    });
+                }
+            }
+        }
+
+        if let Some(computer) = self.computers.first() {
+            computer.advance_tick();
+        }
+        self.ticks += 1;
+
+        if first_error.is_none() && all_waiting && self.is_running() {
+            let stuck = self
+                .computers
+                .iter()
+                .zip(&self.programs)
+                .all(|(computer, program)| program.is_none() || !computer.has_pending_events());
+            if stuck {
+                let index = self.programs.iter().position(Option::is_some).unwrap();
+                self.programs[index] = None;
+                first_error = Some(FleetError {
+                    id: self.computers[index].computer().id,
+                    source: SimulatorError::EventQueueEmpty,
+                });
+            }
+        }
+
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs ticks until every program finishes, or one fails.
+    pub fn run(&mut self) -> Result<(), FleetError> {
+        while self.is_running() {
+            self.tick()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::{Direction, Position};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{ComputerFamily, ComputerKind};
+
+    #[test]
+    fn test_turtles_take_turns_and_block_each_other() {
+        let first = Simulator::new().unwrap();
+        let second = first
+            .new_computer(ComputerKind::Turtle, ComputerFamily::Normal)
+            .unwrap();
+        second.move_turtle_to(Position::new(0, 0, -3));
+        second.turtle_mut().facing = Direction::South;
+        for turtle in [&first, &second] {
+            turtle
+                .filesystem_mut()
+                .write_file("walk.lua", "for _ = 1, 3 do print(turtle.forward()) end")
+                .unwrap();
+        }
+
+        let mut fleet = Fleet::new(vec![second, first]);
+        fleet.start_program(0, "walk", &[]).unwrap();
+        fleet.start_program(1, "walk", &[]).unwrap();
+        fleet.run().unwrap();
+
+        // Both turtles move in the first tick, and then the other is in the way.
+        let expected = "true\tnil\nfalse\tMovement obstructed\nfalse\tMovement obstructed\n";
+        for (computer, position) in fleet
+            .computers()
+            .iter()
+            .zip([Position::new(0, 0, -1), Position::new(0, 0, -2)])
+        {
+            assert_eq!(computer.output(), expected);
+            assert_eq!(computer.turtle().position, position);
+        }
+        assert_eq!(fleet.ticks(), 4);
+    }
+
+    #[test]
+    fn test_failing_program_does_not_stop_the_others() {
+        let first = Simulator::new().unwrap();
+        let second = first
+            .new_computer(ComputerKind::Turtle, ComputerFamily::Normal)
+            .unwrap();
+        second.move_turtle_to(Position::new(5, 0, 0));
+        second.turtle_mut().facing = Direction::North;
+        first
+            .filesystem_mut()
+            .write_file("fail.lua", "turtle.forward() error('Oops')")
+            .unwrap();
+        second
+            .filesystem_mut()
+            .write_file("walk.lua", "turtle.forward() turtle.forward()")
+            .unwrap();
+
+        let mut fleet = Fleet::new(vec![first, second]);
+        fleet.start_program(0, "fail", &[]).unwrap();
+        fleet.start_program(1, "walk", &[]).unwrap();
+
+        fleet.tick().unwrap();
+        let err = fleet.tick().unwrap_err();
+        assert_eq!(err.id, 0);
+        let SimulatorError::LuaRuntime(source) = err.source else {
+            panic!("expected a Lua runtime error");
+        };
+        assert_eq!(source.message, "fail.lua:1: Oops");
+
+        fleet.run().unwrap();
+        assert_eq!(
+            fleet.computer(1).unwrap().turtle().position,
+            Position::new(5, 0, -2)
+        );
+    }
+}
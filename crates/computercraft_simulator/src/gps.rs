@@ -0,0 +1,15 @@
+use minecraft::world::Position;
+
+use crate::ModemKind;
+
+/// The channel GPS requests and responses are sent on.
+pub const CHANNEL_GPS: u16 = 65534;
+
+/// A computer running ComputerCraft's `gps host` program, which answers the
+/// `PING`s sent by `gps.locate` with its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpsHost {
+    pub position: Position,
+    /// The kind of modem the host is listening with.
+    pub modem: ModemKind,
+}
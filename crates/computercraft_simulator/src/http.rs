@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// A request made with the `http` API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub url: String,
+    /// The method, in upper case, such as `GET`.
+    pub method: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<Vec<u8>>,
+}
+
+/// A response to an [`HttpRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// The reason phrase of the status, such as `OK`.
+    pub message: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Creates a response with the status and body, and no headers.
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            message: reason_phrase(status).to_string(),
+            headers: BTreeMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Creates a `200 OK` response with the body.
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::new(200, body)
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Returns whether the request succeeded, which is what decides between
+    /// the `http_success` and `http_failure` events.
+    pub fn is_success(&self) -> bool {
+        (200..400).contains(&self.status)
+    }
+}
+
+/// Serves the requests computers make with the `http` API.
+///
+/// The simulator doesn't go out to the internet by itself. Instead, tests
+/// install a handler which serves canned responses, or forwards requests to a
+/// server, such as [`LocalhostHandler`]. Closures taking an [`HttpRequest`]
+/// are handlers too.
+pub trait HttpHandler {
+    /// Checks whether requests can be made to the URL, returning why not if
+    /// they can't.
+    fn check(&self, url: &str) -> Result<(), String> {
+        let _ = url;
+        Ok(())
+    }
+
+    /// Serves the request, returning why it failed if there's no response.
+    fn handle(&self, request: &HttpRequest) -> Result<HttpResponse, String>;
+}
+
+impl<F> HttpHandler for F
+where
+    F: Fn(&HttpRequest) -> Result<HttpResponse, String>,
+{
+    fn handle(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+        self(request)
+    }
+}
+
+/// The default handler, which denies every request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DenyAll;
+
+impl HttpHandler for DenyAll {
+    fn check(&self, _url: &str) -> Result<(), String> {
+        Err("Domain not permitted".to_string())
+    }
+
+    fn handle(&self, _request: &HttpRequest) -> Result<HttpResponse, String> {
+        Err("Domain not permitted".to_string())
+    }
+}
+
+/// A handler which sends requests to servers on this machine over plain
+/// HTTP/1.1, and denies every other request.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalhostHandler {
+    /// How long to wait for the server.
+    pub timeout: Duration,
+}
+
+impl Default for LocalhostHandler {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl LocalhostHandler {
+    fn send(&self, url: &Url, request: &HttpRequest) -> std::io::Result<Vec<u8>> {
+        let address = (url.host.as_str(), url.port.unwrap_or(80));
+        let mut stream = TcpStream::connect(address)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let body = request.body.as_deref().unwrap_or_default();
+        let mut head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+            request.method,
+            url.path,
+            url.host,
+            body.len()
+        );
+        for (name, value) in &request.headers {
+            if !name.eq_ignore_ascii_case("host") && !name.eq_ignore_ascii_case("content-length") {
+                head.push_str(&format!("{name}: {value}\r\n"));
+            }
+        }
+        head.push_str("\r\n");
+
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(body)?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+
+        Ok(response)
+    }
+}
+
+impl HttpHandler for LocalhostHandler {
+    fn check(&self, url: &str) -> Result<(), String> {
+        let url = Url::parse(url)?;
+        if !matches!(url.host.as_str(), "localhost" | "127.0.0.1") {
+            return Err("Domain not permitted".to_string());
+        }
+        if url.scheme != "http" {
+            return Err("Only plain HTTP is supported".to_string());
+        }
+
+        Ok(())
+    }
+
+    fn handle(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+        self.check(&request.url)?;
+
+        let url = Url::parse(&request.url)?;
+        let response = self
+            .send(&url, request)
+            .map_err(|_| "Could not connect".to_string())?;
+
+        parse_response(&response).ok_or_else(|| "Malformed response".to_string())
+    }
+}
+
+/// The parts of a URL the `http` API needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Url {
+    pub scheme: String,
+    pub host: String,
+    pub port: Option<u16>,
+    /// The path and query, starting with `/`.
+    pub path: String,
+}
+
+impl Url {
+    /// Parses the URL, returning the error ComputerCraft gives if it isn't
+    /// valid.
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let Some((scheme, rest)) = url.split_once("://") else {
+            return Err("Must specify http or https".to_string());
+        };
+        let scheme = scheme.to_ascii_lowercase();
+
+        let (authority, path) = match rest.find(['/', '?', '#']) {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let path = path.split('#').next().unwrap_or_default();
+        let path = match path {
+            "" => "/".to_string(),
+            path if path.starts_with('?') => format!("/{path}"),
+            path => path.to_string(),
+        };
+
+        let authority = authority.rsplit('@').next().unwrap_or_default();
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse().map_err(|_| "URL malformed".to_string())?;
+                (host, Some(port))
+            }
+            None => (authority, None),
+        };
+        if host.is_empty() {
+            return Err("URL malformed".to_string());
+        }
+
+        Ok(Self {
+            scheme,
+            host: host.to_ascii_lowercase(),
+            port,
+            path,
+        })
+    }
+}
+
+/// Parses an HTTP/1.1 response, as read from the server until it closed
+/// the connection.
+fn parse_response(response: &[u8]) -> Option<HttpResponse> {
+    let head_end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&response[..head_end]).ok()?;
+    let mut body = response[head_end + 4..].to_vec();
+
+    let mut lines = head.split("\r\n");
+    let mut status_line = lines.next()?.splitn(3, ' ');
+    let _version = status_line.next()?;
+    let status = status_line.next()?.parse().ok()?;
+    let message = status_line.next().unwrap_or_default().to_string();
+
+    let mut headers = BTreeMap::<String, String>::new();
+    for line in lines {
+        let (name, value) = line.split_once(':')?;
+        let value = value.trim();
+        // Like in ComputerCraft, repeated headers are joined with commas.
+        headers
+            .entry(name.trim().to_string())
+            .and_modify(|existing| {
+                existing.push(',');
+                existing.push_str(value);
+            })
+            .or_insert_with(|| value.to_string());
+    }
+
+    let is_chunked = headers.iter().any(|(name, value)| {
+        name.eq_ignore_ascii_case("transfer-encoding") && value.eq_ignore_ascii_case("chunked")
+    });
+    if is_chunked {
+        body = decode_chunked(&body)?;
+    }
+
+    Some(HttpResponse {
+        status,
+        message,
+        headers,
+        body,
+    })
+}
+
+/// Decodes a body sent with the chunked transfer encoding.
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(decoded);
+        }
+
+        decoded.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+/// Returns the standard reason phrase of the status code.
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "Unknown Status",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            Url::parse("https://Example.com:8080/api?page=2#top"),
+            Ok(Url {
+                scheme: "https".to_string(),
+                host: "example.com".to_string(),
+                port: Some(8080),
+                path: "/api?page=2".to_string(),
+            })
+        );
+        assert_eq!(
+            Url::parse("http://localhost").map(|url| url.path),
+            Ok("/".to_string())
+        );
+        assert_eq!(
+            Url::parse("example.com"),
+            Err("Must specify http or https".to_string())
+        );
+        assert_eq!(Url::parse("http://"), Err("URL malformed".to_string()));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response = parse_response(
+            b"HTTP/1.1 404 Not Found\r\nSet-Cookie: a=1\r\nTransfer-Encoding: chunked\r\n\
+              Set-Cookie: b=2\r\n\r\n4\r\nNope\r\n3\r\n!!!\r\n0\r\n\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(response.status, 404);
+        assert_eq!(response.message, "Not Found");
+        assert_eq!(response.headers["Set-Cookie"], "a=1,b=2");
+        assert_eq!(response.body, b"Nope!!!");
+        assert!(!response.is_success());
+    }
+}
@@ -0,0 +1,170 @@
+use minecraft::{BlockId, ItemStack};
+
+/// A block which holds items, such as a chest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    /// The block the inventory is, which is also its peripheral type.
+    pub block: BlockId,
+    slots: Vec<Option<ItemStack>>,
+}
+
+impl Inventory {
+    /// The number of slots in a single chest.
+    pub const CHEST_SIZE: usize = 27;
+
+    /// The most items of a kind which fit in a slot.
+    pub const MAX_STACK_SIZE: u32 = 64;
+
+    pub fn new(block: BlockId, size: usize) -> Self {
+        Self {
+            block,
+            slots: vec![None; size],
+        }
+    }
+
+    /// Creates an empty chest.
+    pub fn chest() -> Self {
+        Self::new(BlockId::new_static("minecraft:chest"), Self::CHEST_SIZE)
+    }
+
+    /// Returns the number of slots in the inventory.
+    pub fn size(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the items in the slot, counting from 0.
+    pub fn slot(&self, slot: usize) -> Option<&ItemStack> {
+        self.slots.get(slot)?.as_ref()
+    }
+
+    /// Returns how many items the slot can hold. Empty slots can hold a full
+    /// stack of anything.
+    pub fn slot_limit(&self, slot: usize) -> u32 {
+        self.slot(slot)
+            .map_or(Self::MAX_STACK_SIZE, ItemStack::max_stack_size)
+    }
+
+    /// Returns the non-empty slots and their items.
+    pub fn items(&self) -> impl Iterator<Item = (usize, &ItemStack)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, stack)| Some((slot, stack.as_ref()?)))
+    }
+
+    /// Replaces the items in the slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot is out of range.
+    pub fn set_slot(&mut self, slot: usize, stack: Option<ItemStack>) {
+        self.slots[slot] = stack.filter(|stack| !stack.is_empty());
+    }
+
+    /// Takes up to `limit` items out of the slot.
+    pub fn take(&mut self, slot: usize, limit: u32) -> Option<ItemStack> {
+        take_from_slots(&mut self.slots, slot, limit)
+    }
+
+    /// Puts as many of the items as fit into the slot, or into the first slots
+    /// they fit in if no slot is given, like a hopper. Returns the items which
+    /// didn't fit.
+    pub fn insert(&mut self, stack: ItemStack, slot: Option<usize>) -> Option<ItemStack> {
+        let order = match slot {
+            Some(slot) => slot..slot + 1,
+            None => 0..self.slots.len(),
+        };
+
+        insert_into_slots(&mut self.slots, stack, order)
+    }
+}
+
+/// Takes up to `limit` items out of the slot. Turtles share this with
+/// inventories, as their slots work the same way.
+pub(crate) fn take_from_slots(
+    slots: &mut [Option<ItemStack>],
+    slot: usize,
+    limit: u32,
+) -> Option<ItemStack> {
+    let stack = slots.get_mut(slot)?.as_mut()?;
+    if limit == 0 {
+        return None;
+    }
+
+    let taken = stack.split(limit);
+    if stack.is_empty() {
+        slots[slot] = None;
+    }
+
+    Some(taken)
+}
+
+/// Puts as many of the items as fit into the slots, trying them in the given
+/// order. Returns the items which didn't fit.
+pub(crate) fn insert_into_slots(
+    slots: &mut [Option<ItemStack>],
+    mut stack: ItemStack,
+    order: impl IntoIterator<Item = usize>,
+) -> Option<ItemStack> {
+    for slot in order {
+        if stack.is_empty() {
+            break;
+        }
+
+        match &mut slots[slot] {
+            Some(existing) if existing.can_stack_with(&stack) => {
+                let count = existing.space_left().min(stack.count);
+                existing.count += count;
+                stack.count -= count;
+            }
+            Some(_) => {}
+            empty @ None => {
+                *empty = Some(stack.split(stack.max_stack_size()));
+            }
+        }
+    }
+
+    (!stack.is_empty()).then_some(stack)
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::ItemId;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_insert_and_take() {
+        let cobblestone = ItemId::new_static("minecraft:cobblestone");
+        let mut chest = Inventory::new(BlockId::new_static("minecraft:chest"), 3);
+        chest.set_slot(1, Some(ItemStack::new(cobblestone.clone(), 60)));
+
+        // The slots fill up in order.
+        let leftover = chest.insert(ItemStack::new(cobblestone.clone(), 100), None);
+        assert_eq!(leftover, None);
+        assert_eq!(
+            chest.slot(0),
+            Some(&ItemStack::new(cobblestone.clone(), 64))
+        );
+        assert_eq!(
+            chest.slot(1),
+            Some(&ItemStack::new(cobblestone.clone(), 64))
+        );
+        assert_eq!(
+            chest.slot(2),
+            Some(&ItemStack::new(cobblestone.clone(), 32))
+        );
+
+        let leftover = chest.insert(ItemStack::new(cobblestone.clone(), 40), Some(2));
+        assert_eq!(leftover, Some(ItemStack::new(cobblestone.clone(), 8)));
+
+        assert_eq!(
+            chest.take(0, 100),
+            Some(ItemStack::new(cobblestone.clone(), 64))
+        );
+        assert_eq!(chest.slot(0), None);
+        assert_eq!(chest.take(0, 100), None);
+        assert_eq!(chest.items().count(), 2);
+    }
+}
@@ -0,0 +1,155 @@
+/// A key on the keyboard, identified by its ComputerCraft key code.
+///
+/// The key codes match those in ComputerCraft's `keys` API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key(pub u32);
+
+macro_rules! keys {
+    ($($key:ident = $code:literal => $name:literal,)*) => {
+        impl Key {
+            $(pub const $key: Self = Self($code);)*
+        }
+
+        /// All of the keys, along with their names in the `keys` API.
+        pub(crate) const KEYS: &[(Key, &str)] = &[$((Key::$key, $name),)*];
+    };
+}
+
+keys! {
+    SPACE = 32 => "space",
+    APOSTROPHE = 39 => "apostrophe",
+    COMMA = 44 => "comma",
+    MINUS = 45 => "minus",
+    PERIOD = 46 => "period",
+    SLASH = 47 => "slash",
+    ZERO = 48 => "zero",
+    ONE = 49 => "one",
+    TWO = 50 => "two",
+    THREE = 51 => "three",
+    FOUR = 52 => "four",
+    FIVE = 53 => "five",
+    SIX = 54 => "six",
+    SEVEN = 55 => "seven",
+    EIGHT = 56 => "eight",
+    NINE = 57 => "nine",
+    SEMICOLON = 59 => "semicolon",
+    EQUALS = 61 => "equals",
+    A = 65 => "a",
+    B = 66 => "b",
+    C = 67 => "c",
+    D = 68 => "d",
+    E = 69 => "e",
+    F = 70 => "f",
+    G = 71 => "g",
+    H = 72 => "h",
+    I = 73 => "i",
+    J = 74 => "j",
+    K = 75 => "k",
+    L = 76 => "l",
+    M = 77 => "m",
+    N = 78 => "n",
+    O = 79 => "o",
+    P = 80 => "p",
+    Q = 81 => "q",
+    R = 82 => "r",
+    S = 83 => "s",
+    T = 84 => "t",
+    U = 85 => "u",
+    V = 86 => "v",
+    W = 87 => "w",
+    X = 88 => "x",
+    Y = 89 => "y",
+    Z = 90 => "z",
+    LEFT_BRACKET = 91 => "leftBracket",
+    BACKSLASH = 92 => "backslash",
+    RIGHT_BRACKET = 93 => "rightBracket",
+    GRAVE = 96 => "grave",
+    ENTER = 257 => "enter",
+    TAB = 258 => "tab",
+    BACKSPACE = 259 => "backspace",
+    INSERT = 260 => "insert",
+    DELETE = 261 => "delete",
+    RIGHT = 262 => "right",
+    LEFT = 263 => "left",
+    DOWN = 264 => "down",
+    UP = 265 => "up",
+    PAGE_UP = 266 => "pageUp",
+    PAGE_DOWN = 267 => "pageDown",
+    HOME = 268 => "home",
+    END = 269 => "end",
+    CAPS_LOCK = 280 => "capsLock",
+    SCROLL_LOCK = 281 => "scrollLock",
+    NUM_LOCK = 282 => "numLock",
+    PRINT_SCREEN = 283 => "printScreen",
+    PAUSE = 284 => "pause",
+    F1 = 290 => "f1",
+    F2 = 291 => "f2",
+    F3 = 292 => "f3",
+    F4 = 293 => "f4",
+    F5 = 294 => "f5",
+    F6 = 295 => "f6",
+    F7 = 296 => "f7",
+    F8 = 297 => "f8",
+    F9 = 298 => "f9",
+    F10 = 299 => "f10",
+    F11 = 300 => "f11",
+    F12 = 301 => "f12",
+    F13 = 302 => "f13",
+    F14 = 303 => "f14",
+    F15 = 304 => "f15",
+    F16 = 305 => "f16",
+    F17 = 306 => "f17",
+    F18 = 307 => "f18",
+    F19 = 308 => "f19",
+    F20 = 309 => "f20",
+    F21 = 310 => "f21",
+    F22 = 311 => "f22",
+    F23 = 312 => "f23",
+    F24 = 313 => "f24",
+    F25 = 314 => "f25",
+    NUM_PAD_0 = 320 => "numPad0",
+    NUM_PAD_1 = 321 => "numPad1",
+    NUM_PAD_2 = 322 => "numPad2",
+    NUM_PAD_3 = 323 => "numPad3",
+    NUM_PAD_4 = 324 => "numPad4",
+    NUM_PAD_5 = 325 => "numPad5",
+    NUM_PAD_6 = 326 => "numPad6",
+    NUM_PAD_7 = 327 => "numPad7",
+    NUM_PAD_8 = 328 => "numPad8",
+    NUM_PAD_9 = 329 => "numPad9",
+    NUM_PAD_DECIMAL = 330 => "numPadDecimal",
+    NUM_PAD_DIVIDE = 331 => "numPadDivide",
+    NUM_PAD_MULTIPLY = 332 => "numPadMultiply",
+    NUM_PAD_SUBTRACT = 333 => "numPadSubtract",
+    NUM_PAD_ADD = 334 => "numPadAdd",
+    NUM_PAD_ENTER = 335 => "numPadEnter",
+    NUM_PAD_EQUAL = 336 => "numPadEqual",
+    LEFT_SHIFT = 340 => "leftShift",
+    LEFT_CTRL = 341 => "leftCtrl",
+    LEFT_ALT = 342 => "leftAlt",
+    LEFT_SUPER = 343 => "leftSuper",
+    RIGHT_SHIFT = 344 => "rightShift",
+    RIGHT_CTRL = 345 => "rightCtrl",
+    RIGHT_ALT = 346 => "rightAlt",
+    MENU = 348 => "menu",
+}
+
+impl Key {
+    /// Returns the key with the given name in the `keys` API.
+    pub fn from_name(name: &str) -> Option<Self> {
+        KEYS.iter()
+            .find(|(_, key_name)| *key_name == name)
+            .map(|(key, _)| *key)
+    }
+
+    /// Returns the name of the key in the `keys` API.
+    pub fn name(self) -> Option<&'static str> {
+        KEYS.iter()
+            .find(|(key, _)| *key == self)
+            .map(|(_, name)| *name)
+    }
+
+    pub fn code(self) -> u32 {
+        self.0
+    }
+}
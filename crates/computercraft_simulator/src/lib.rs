@@ -1,5 +1,39 @@
+mod color;
+mod computer;
+mod disk_drive;
+mod event;
+mod filesystem;
+mod fleet;
+mod gps;
+mod http;
+mod inventory;
+mod keys;
+mod monitor;
+mod network;
+mod peripheral;
+mod printer;
+mod redstone;
 mod simulator;
+mod speaker;
+mod terminal;
 mod turtle;
 
+pub use crate::color::*;
+pub use crate::computer::*;
+pub use crate::disk_drive::*;
+pub use crate::event::*;
+pub use crate::filesystem::*;
+pub use crate::fleet::*;
+pub use crate::gps::*;
+pub use crate::http::*;
+pub use crate::inventory::*;
+pub use crate::keys::*;
+pub use crate::monitor::*;
+pub use crate::network::*;
+pub use crate::peripheral::*;
+pub use crate::printer::*;
+pub use crate::redstone::*;
 pub use crate::simulator::*;
+pub use crate::speaker::*;
+pub use crate::terminal::*;
 pub use crate::turtle::*;
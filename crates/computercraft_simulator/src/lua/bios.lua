@@ -0,0 +1,622 @@
+-- The parts of ComputerCraft's `bios.lua` that need to be written in Lua, as
+-- they yield to the simulator's event loop.
+
+-- Converts an error raised by one of the simulator's native functions into a
+-- string, leaving any other error untouched. Also returns whether the program
+-- has been interrupted, in which case errors can't be caught. Turtle actions
+-- yield `turtle_tick` to end the computer's turn.
+local native_error_message, is_interrupted, turtle_tick = ...
+
+local expect = require("cc.expect").expect
+
+-- Errors raised by native functions are Rust values rather than strings, so
+-- convert them wherever Lua code can catch an error.
+do
+    local native_pcall, native_xpcall, native_resume = pcall, xpcall, coroutine.resume
+
+    -- Once the program is interrupted, keep raising the error until it
+    -- reaches the simulator, as the program would have stopped.
+    local function check_interrupted(ok, ...)
+        if not ok and is_interrupted() then
+            error((...), 0)
+        end
+
+        return ok, ...
+    end
+
+    local function convert(ok, ...)
+        if ok then
+            return ok, ...
+        end
+
+        check_interrupted(ok, ...)
+        return ok, native_error_message((...))
+    end
+
+    function pcall(fn, ...)
+        return convert(native_pcall(fn, ...))
+    end
+
+    function xpcall(fn, handler, ...)
+        return check_interrupted(native_xpcall(fn, function(err)
+            if is_interrupted() then
+                return err
+            end
+
+            return handler(native_error_message(err))
+        end, ...))
+    end
+
+    function coroutine.resume(co, ...)
+        local result = table.pack(native_resume(co, ...))
+
+        -- Turtle actions in the coroutine end the whole computer's turn, so
+        -- pass their yields on rather than returning them.
+        while result[1] and result[2] == turtle_tick and coroutine.status(co) == "suspended" do
+            coroutine.yield(turtle_tick)
+            result = table.pack(native_resume(co))
+        end
+
+        return convert(table.unpack(result, 1, result.n))
+    end
+
+    function coroutine.wrap(fn)
+        local co = coroutine.create(fn)
+        return function(...)
+            local result = table.pack(coroutine.resume(co, ...))
+            if not result[1] then
+                error(result[2], 0)
+            end
+
+            return table.unpack(result, 2, result.n)
+        end
+    end
+end
+
+-- Lua's `loadfile` and `dofile` read from the host, so replace them with
+-- ComputerCraft's, which read from the computer's filesystem.
+function loadfile(filename, mode, env)
+    -- Support the previous `loadfile(filename, env)` form instead.
+    if type(mode) == "table" and env == nil then
+        mode, env = nil, mode
+    end
+
+    expect(1, filename, "string")
+    expect(2, mode, "string", "nil")
+    expect(3, env, "table", "nil")
+
+    local file = fs.open(filename, "r")
+    if not file then
+        return nil, "File not found"
+    end
+
+    local fn, err = load(file.readAll(), "@" .. fs.combine(filename), mode, env)
+    file.close()
+    return fn, err
+end
+
+function dofile(filename)
+    expect(1, filename, "string")
+
+    local fn, err = loadfile(filename, nil, _G)
+    if fn then
+        return fn()
+    else
+        error(err, 2)
+    end
+end
+
+function os.run(env, path, ...)
+    expect(1, env, "table")
+    expect(2, path, "string")
+
+    setmetatable(env, { __index = _G })
+
+    local fn, err = loadfile(path, nil, env)
+    if fn then
+        local ok, err = pcall(fn, ...)
+        if not ok then
+            if err and err ~= "" then
+                printError(err)
+            end
+            return false
+        end
+        return true
+    end
+
+    if err and err ~= "" then
+        printError(err)
+    end
+    return false
+end
+
+function fs.complete(path, location, include_files, include_dirs)
+    expect(1, path, "string")
+    expect(2, location, "string")
+
+    local include_hidden
+    if type(include_files) == "table" then
+        local options = include_files
+        include_files = expect(3, options.include_files, "boolean", "nil")
+        include_dirs = expect(3, options.include_dirs, "boolean", "nil")
+        include_hidden = expect(3, options.include_hidden, "boolean", "nil")
+    else
+        expect(3, include_files, "boolean", "nil")
+        expect(4, include_dirs, "boolean", "nil")
+    end
+
+    include_hidden = include_hidden ~= false
+    include_files = include_files ~= false
+    include_dirs = include_dirs ~= false
+
+    local dir = location
+    local start = 1
+    if path:find("[/\\]") == 1 then
+        dir = ""
+        start = 2
+    end
+
+    local name
+    while not name do
+        local slash = path:find("[/\\]", start)
+        if slash then
+            dir = fs.combine(dir, path:sub(start, slash - 1))
+            start = slash + 1
+        else
+            name = path:sub(start)
+        end
+    end
+
+    if not fs.isDir(dir) then
+        return {}
+    end
+
+    local results = {}
+    if include_dirs and path == "" then
+        results[#results + 1] = "."
+    end
+    if dir ~= "" then
+        if path == "" then
+            results[#results + 1] = include_dirs and ".." or "../"
+        elseif path == "." then
+            results[#results + 1] = include_dirs and "." or "./"
+        end
+    end
+
+    for _, file in ipairs(fs.list(dir)) do
+        if #file >= #name and file:sub(1, #name) == name
+            and (include_hidden or file:sub(1, 1) ~= "." or name:sub(1, 1) == ".")
+        then
+            local result = file:sub(#name + 1)
+            if fs.isDir(fs.combine(dir, file)) then
+                results[#results + 1] = result .. "/"
+                if include_dirs and #result > 0 then
+                    results[#results + 1] = result
+                end
+            elseif include_files and #result > 0 then
+                results[#results + 1] = result
+            end
+        end
+    end
+
+    return results
+end
+
+-- Like in ComputerCraft, the computer turns off once the program yields, so
+-- never return to it.
+do
+    local native_shutdown, native_reboot = os.shutdown, os.reboot
+
+    function os.shutdown()
+        native_shutdown()
+        while true do
+            coroutine.yield()
+        end
+    end
+
+    function os.reboot()
+        native_reboot()
+        while true do
+            coroutine.yield()
+        end
+    end
+end
+
+function os.pullEventRaw(filter)
+    return coroutine.yield(filter)
+end
+
+function os.pullEvent(filter)
+    local event = table.pack(os.pullEventRaw(filter))
+    if event[1] == "terminate" then
+        error("Terminated", 0)
+    end
+
+    return table.unpack(event, 1, event.n)
+end
+
+function sleep(time)
+    expect(1, time, "number", "nil")
+
+    local timer = os.startTimer(time or 0)
+    repeat
+        local _, id = os.pullEvent("timer")
+    until id == timer
+end
+
+function read(replace_char, history, complete_fn, default)
+    expect(1, replace_char, "string", "nil")
+    expect(2, history, "table", "nil")
+    expect(3, complete_fn, "function", "nil")
+    expect(4, default, "string", "nil")
+
+    term.setCursorBlink(true)
+
+    local line = default or ""
+    local history_pos
+    local pos, scroll = #line, 0
+    if replace_char then
+        replace_char = replace_char:sub(1, 1)
+    end
+
+    local completions
+    local completion
+    local function recomplete()
+        if complete_fn and pos == #line then
+            completions = complete_fn(line)
+            if completions and #completions > 0 then
+                completion = 1
+            else
+                completion = nil
+            end
+        else
+            completions = nil
+            completion = nil
+        end
+    end
+
+    local function uncomplete()
+        completions = nil
+        completion = nil
+    end
+
+    local width = term.getSize()
+    local start_x = term.getCursorPos()
+
+    local function redraw(clear)
+        local cursor_pos = pos - scroll
+        if start_x + cursor_pos >= width then
+            -- We've moved beyond the right-hand side, ensure we're on the edge.
+            scroll = start_x + pos - width
+        elseif cursor_pos < 0 then
+            -- We've moved beyond the left-hand side, ensure we're on the edge.
+            scroll = pos
+        end
+
+        local _, cursor_y = term.getCursorPos()
+        term.setCursorPos(start_x, cursor_y)
+
+        local replace = clear and " " or replace_char
+        if replace then
+            term.write(replace:rep(math.max(#line - scroll, 0)))
+        else
+            term.write(line:sub(scroll + 1))
+        end
+
+        if completion then
+            local suggestion = completions[completion]
+            local old_text, old_background
+            if not clear then
+                old_text = term.getTextColor()
+                old_background = term.getBackgroundColor()
+                term.setTextColor(colors.white)
+                term.setBackgroundColor(colors.gray)
+            end
+
+            if replace then
+                term.write(replace:rep(#suggestion))
+            else
+                term.write(suggestion)
+            end
+
+            if not clear then
+                term.setTextColor(old_text)
+                term.setBackgroundColor(old_background)
+            end
+        end
+
+        term.setCursorPos(start_x + pos - scroll, cursor_y)
+    end
+
+    local function clear()
+        redraw(true)
+    end
+
+    local function accept_completion()
+        if completion then
+            clear()
+
+            line = line .. completions[completion]
+            pos = #line
+
+            recomplete()
+            redraw()
+        end
+    end
+
+    recomplete()
+    redraw()
+
+    while true do
+        local event, param, x, y = os.pullEvent()
+        if event == "char" or event == "paste" then
+            clear()
+            line = line:sub(1, pos) .. param .. line:sub(pos + 1)
+            pos = pos + #param
+            recomplete()
+            redraw()
+        elseif event == "key" then
+            if param == keys.enter or param == keys.numPadEnter then
+                if completion then
+                    clear()
+                    uncomplete()
+                    redraw()
+                end
+
+                break
+            elseif param == keys.left then
+                if pos > 0 then
+                    clear()
+                    pos = pos - 1
+                    recomplete()
+                    redraw()
+                end
+            elseif param == keys.right then
+                if pos < #line then
+                    clear()
+                    pos = pos + 1
+                    recomplete()
+                    redraw()
+                else
+                    accept_completion()
+                end
+            elseif param == keys.up or param == keys.down then
+                if completion then
+                    -- Cycle through the completions.
+                    clear()
+                    if param == keys.up then
+                        completion = completion - 1
+                        if completion < 1 then
+                            completion = #completions
+                        end
+                    else
+                        completion = completion + 1
+                        if completion > #completions then
+                            completion = 1
+                        end
+                    end
+                    redraw()
+                elseif history then
+                    -- Cycle through the history.
+                    clear()
+                    if param == keys.up then
+                        if history_pos == nil then
+                            if #history > 0 then
+                                history_pos = #history
+                            end
+                        elseif history_pos > 1 then
+                            history_pos = history_pos - 1
+                        end
+                    else
+                        if history_pos == #history then
+                            history_pos = nil
+                        elseif history_pos ~= nil then
+                            history_pos = history_pos + 1
+                        end
+                    end
+
+                    if history_pos then
+                        line = history[history_pos]
+                        pos, scroll = #line, 0
+                    else
+                        line = ""
+                        pos, scroll = 0, 0
+                    end
+                    uncomplete()
+                    redraw()
+                end
+            elseif param == keys.backspace then
+                if pos > 0 then
+                    clear()
+                    line = line:sub(1, pos - 1) .. line:sub(pos + 1)
+                    pos = pos - 1
+                    if scroll > 0 then
+                        scroll = scroll - 1
+                    end
+                    recomplete()
+                    redraw()
+                end
+            elseif param == keys.home then
+                if pos > 0 then
+                    clear()
+                    pos = 0
+                    recomplete()
+                    redraw()
+                end
+            elseif param == keys.delete then
+                if pos < #line then
+                    clear()
+                    line = line:sub(1, pos) .. line:sub(pos + 2)
+                    recomplete()
+                    redraw()
+                end
+            elseif param == keys["end"] then
+                if pos < #line then
+                    clear()
+                    pos = #line
+                    recomplete()
+                    redraw()
+                end
+            elseif param == keys.tab then
+                accept_completion()
+            end
+        elseif event == "mouse_click" or (event == "mouse_drag" and param == 1) then
+            local _, cursor_y = term.getCursorPos()
+            if x >= start_x and x <= width and y == cursor_y then
+                -- Ensure we don't scroll beyond the current line.
+                pos = math.min(math.max(scroll + x - start_x, 0), #line)
+                redraw()
+            end
+        elseif event == "term_resize" then
+            width = term.getSize()
+            redraw()
+        end
+    end
+
+    local _, cursor_y = term.getCursorPos()
+    term.setCursorBlink(false)
+    term.setCursorPos(width + 1, cursor_y)
+    print()
+
+    return line
+end
+
+-- The parts of `textutils` that write to the terminal, as they may need to
+-- wait for a key press before scrolling.
+
+local function make_paged_scroll(target, free_lines)
+    local native_scroll = target.scroll
+    free_lines = free_lines or 0
+
+    return function(lines)
+        for _ = 1, lines do
+            native_scroll(1)
+
+            if free_lines <= 0 then
+                local _, height = target.getSize()
+                target.setCursorPos(1, height)
+                target.write("Press any key to continue")
+                os.pullEvent("key")
+                target.clearLine()
+                target.setCursorPos(1, height)
+            else
+                free_lines = free_lines - 1
+            end
+        end
+    end
+end
+
+function textutils.pagedPrint(text, free_lines)
+    expect(2, free_lines, "number", "nil")
+
+    local previous = term.current()
+    local paged = {}
+    for name, fn in pairs(previous) do
+        paged[name] = fn
+    end
+    paged.scroll = make_paged_scroll(previous, free_lines)
+    term.redirect(paged)
+
+    local result
+    local ok, err = pcall(function()
+        if text ~= nil then
+            result = print(text)
+        else
+            result = print()
+        end
+    end)
+
+    term.redirect(previous)
+
+    if not ok then
+        error(err, 0)
+    end
+
+    return result
+end
+
+local function tabulate_common(paged, ...)
+    local all = table.pack(...)
+    for i = 1, all.n do
+        expect(i, all[i], "number", "table")
+    end
+
+    local width, height = term.getSize()
+    local max_len = width / 8
+    for n, t in ipairs(all) do
+        if type(t) == "table" then
+            for nu, item in pairs(t) do
+                local item_type = type(item)
+                if item_type ~= "string" and item_type ~= "number" then
+                    error("bad argument #" .. n .. "." .. nu .. " (string expected, got " .. item_type .. ")", 3)
+                end
+                max_len = math.max(#tostring(item) + 1, max_len)
+            end
+        end
+    end
+
+    local columns = math.floor(width / max_len)
+    local lines = 0
+    local function new_line()
+        if paged and lines >= height - 3 then
+            textutils.pagedPrint()
+        else
+            print()
+        end
+        lines = lines + 1
+    end
+
+    local function draw_columns(items)
+        local column = 1
+        for _, item in ipairs(items) do
+            if column > columns then
+                column = 1
+                new_line()
+            end
+
+            local _, y = term.getCursorPos()
+            term.setCursorPos(1 + (column - 1) * max_len, y)
+            term.write(item)
+
+            column = column + 1
+        end
+        print()
+    end
+
+    local previous_color = term.getTextColour()
+    for _, t in ipairs(all) do
+        if type(t) == "table" then
+            if #t > 0 then
+                draw_columns(t)
+            end
+        elseif type(t) == "number" then
+            term.setTextColor(t)
+        end
+    end
+    term.setTextColor(previous_color)
+end
+
+function textutils.tabulate(...)
+    return tabulate_common(false, ...)
+end
+
+function textutils.pagedTabulate(...)
+    return tabulate_common(true, ...)
+end
+
+-- Define ComputerCraft's settings which the simulator uses, then load any
+-- which have been saved.
+settings.define("shell.allow_startup", {
+    default = true,
+    description = "Run startup files when the computer turns on.",
+    type = "boolean",
+})
+settings.define("shell.autocomplete_hidden", {
+    default = false,
+    description = "Autocomplete hidden files and folders.",
+    type = "boolean",
+})
+
+if fs.exists(".settings") then
+    settings.load(".settings")
+end
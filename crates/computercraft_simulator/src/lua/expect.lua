@@ -0,0 +1,56 @@
+-- A port of ComputerCraft's `cc.expect` module, which checks the arguments
+-- passed to functions. The simulator's APIs share it, and programs can
+-- `require("cc.expect")` it like in ComputerCraft.
+--
+-- Errors are raised at the caller of the function doing the checking, with
+-- the same messages as the simulator's native functions.
+
+local type_of = type
+
+local function check(value, ...)
+    local actual = type_of(value)
+    for i = 1, select("#", ...) do
+        if actual == select(i, ...) then
+            return true, actual
+        end
+    end
+
+    return false, actual
+end
+
+local function expect(index, value, ...)
+    local ok, actual = check(value, ...)
+    if ok then
+        return value
+    end
+
+    local expected = table.concat({ ... }, " or ")
+    error(("bad argument #%d (%s expected, got %s)"):format(index, expected, actual), 3)
+end
+
+local function field(tbl, key, ...)
+    local value = tbl[key]
+    local ok, actual = check(value, ...)
+    if ok then
+        return value
+    end
+
+    local expected = table.concat({ ... }, " or ")
+    error(("bad field '%s' (%s expected, got %s)"):format(key, expected, actual), 3)
+end
+
+local function range(num, min, max)
+    expect(1, num, "number")
+    if num ~= num or num < (min or -math.huge) or num > (max or math.huge) then
+        error(("number outside of range (expected %s to be within %s and %s)"):format(
+            num, min or -math.huge, max or math.huge), 3)
+    end
+
+    return num
+end
+
+return {
+    expect = expect,
+    field = field,
+    range = range,
+}
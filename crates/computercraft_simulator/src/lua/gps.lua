@@ -0,0 +1,219 @@
+-- A port of ComputerCraft's `gps` API, which finds the computer's position by
+-- pinging GPS hosts and trilaterating from their distances.
+
+local expect = require("cc.expect").expect
+
+-- The parts of ComputerCraft's `vector` API which trilateration needs.
+local vector = {}
+local vector_meta = { __index = vector }
+
+local function new_vector(x, y, z)
+    return setmetatable({ x = x, y = y, z = z }, vector_meta)
+end
+
+function vector_meta.__add(a, b)
+    return new_vector(a.x + b.x, a.y + b.y, a.z + b.z)
+end
+
+function vector_meta.__sub(a, b)
+    return new_vector(a.x - b.x, a.y - b.y, a.z - b.z)
+end
+
+function vector_meta.__mul(a, m)
+    return new_vector(a.x * m, a.y * m, a.z * m)
+end
+
+function vector_meta.__tostring(a)
+    return a.x .. "," .. a.y .. "," .. a.z
+end
+
+function vector.dot(a, b)
+    return a.x * b.x + a.y * b.y + a.z * b.z
+end
+
+function vector.cross(a, b)
+    return new_vector(a.y * b.z - a.z * b.y, a.z * b.x - a.x * b.z, a.x * b.y - a.y * b.x)
+end
+
+function vector.length(a)
+    return math.sqrt(a.x * a.x + a.y * a.y + a.z * a.z)
+end
+
+function vector.normalize(a)
+    return a * (1 / a:length())
+end
+
+function vector.round(a, tolerance)
+    tolerance = tolerance or 1
+    return new_vector(
+        math.floor((a.x + tolerance * 0.5) / tolerance) * tolerance,
+        math.floor((a.y + tolerance * 0.5) / tolerance) * tolerance,
+        math.floor((a.z + tolerance * 0.5) / tolerance) * tolerance
+    )
+end
+
+local gps = {}
+
+-- The channel GPS requests and responses are sent on.
+gps.CHANNEL_GPS = 65534
+
+-- Returns the points at the given distances from the three fixes. There are
+-- two unless the point is in the same plane as the fixes.
+local function trilaterate(a, b, c)
+    local a2b = b.position - a.position
+    local a2c = c.position - a.position
+
+    if math.abs(a2b:normalize():dot(a2c:normalize())) > 0.999 then
+        return nil
+    end
+
+    local d = a2b:length()
+    local ex = a2b:normalize()
+    local i = ex:dot(a2c)
+    local ey = (a2c - ex * i):normalize()
+    local j = ey:dot(a2c)
+    local ez = ex:cross(ey)
+
+    local r1 = a.distance
+    local r2 = b.distance
+    local r3 = c.distance
+
+    local x = (r1 * r1 - r2 * r2 + d * d) / (2 * d)
+    local y = (r1 * r1 - r3 * r3 - x * x + (x - i) * (x - i) + j * j) / (2 * j)
+
+    local result = a.position + ex * x + ey * y
+
+    local z_squared = r1 * r1 - x * x - y * y
+    if z_squared > 0 then
+        local z = math.sqrt(z_squared)
+        local result1 = (result + ez * z):round(0.01)
+        local result2 = (result - ez * z):round(0.01)
+        if result1.x ~= result2.x or result1.y ~= result2.y or result1.z ~= result2.z then
+            return result1, result2
+        end
+
+        return result1
+    end
+
+    return result:round(0.01)
+end
+
+-- Picks whichever of the two points is at the right distance from the fix.
+local function narrow(p1, p2, fix)
+    local distance1 = math.abs((p1 - fix.position):length() - fix.distance)
+    local distance2 = math.abs((p2 - fix.position):length() - fix.distance)
+
+    if math.abs(distance1 - distance2) < 0.01 then
+        return p1, p2
+    elseif distance1 < distance2 then
+        return p1:round(0.01)
+    else
+        return p2:round(0.01)
+    end
+end
+
+function gps.locate(timeout, debug)
+    expect(1, timeout, "number", "nil")
+    expect(2, debug, "boolean", "nil")
+
+    local modem_side
+    for _, side in ipairs({ "top", "bottom", "left", "right", "front", "back" }) do
+        if peripheral.getType(side) == "modem" and peripheral.call(side, "isWireless") then
+            modem_side = side
+            break
+        end
+    end
+
+    if modem_side == nil then
+        if debug then
+            print("No wireless modem attached")
+        end
+        return nil
+    end
+
+    if debug then
+        print("Finding position...")
+    end
+
+    -- Listen for the hosts' responses.
+    local modem = peripheral.wrap(modem_side)
+    local close_channel = false
+    if not modem.isOpen(gps.CHANNEL_GPS) then
+        modem.open(gps.CHANNEL_GPS)
+        close_channel = true
+    end
+
+    modem.transmit(gps.CHANNEL_GPS, gps.CHANNEL_GPS, "PING")
+
+    local fixes = {}
+    local pos1, pos2
+    local timer = os.startTimer(timeout or 2)
+    while true do
+        local event, p1, p2, p3, p4, p5 = os.pullEvent()
+        if event == "modem_message" then
+            local side, channel, reply_channel, message, distance = p1, p2, p3, p4, p5
+            if side == modem_side and channel == gps.CHANNEL_GPS and reply_channel == gps.CHANNEL_GPS and distance
+                and type(message) == "table" and #message == 3
+                and tonumber(message[1]) and tonumber(message[2]) and tonumber(message[3])
+            then
+                local fix = { position = new_vector(message[1], message[2], message[3]), distance = distance }
+                if debug then
+                    print(fix.distance .. " metres from " .. tostring(fix.position))
+                end
+
+                if fix.distance == 0 then
+                    pos1, pos2 = fix.position, nil
+                else
+                    -- Keep at most three fixes, replacing any close to the
+                    -- new one.
+                    local index = math.min(3, #fixes + 1)
+                    for i, older in pairs(fixes) do
+                        if (older.position - fix.position):length() < 1 then
+                            index = i
+                            break
+                        end
+                    end
+                    fixes[index] = fix
+
+                    if #fixes >= 3 then
+                        if not pos1 then
+                            pos1, pos2 = trilaterate(fixes[1], fixes[2], fixes[3])
+                        else
+                            pos1, pos2 = narrow(pos1, pos2, fixes[3])
+                        end
+                    end
+                end
+
+                if pos1 and not pos2 then
+                    break
+                end
+            end
+        elseif event == "timer" and p1 == timer then
+            break
+        end
+    end
+
+    if close_channel then
+        modem.close(gps.CHANNEL_GPS)
+    end
+
+    if pos1 and pos2 then
+        if debug then
+            print("Ambiguous position")
+            print("Could be " .. tostring(pos1) .. " or " .. tostring(pos2))
+        end
+        return nil
+    elseif pos1 then
+        if debug then
+            print("Position is " .. tostring(pos1))
+        end
+        return pos1.x, pos1.y, pos1.z
+    else
+        if debug then
+            print("Could not determine position")
+        end
+        return nil
+    end
+end
+
+return gps
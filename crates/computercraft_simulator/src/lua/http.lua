@@ -0,0 +1,187 @@
+-- A port of the `http` API from ComputerCraft's `bios.lua`, which wraps the
+-- simulator's native functions. Requests are answered by the simulator's
+-- HTTP handler, with `http_success` and `http_failure` events. Websockets
+-- connect to the simulator's websocket servers.
+
+local native = ...
+
+local expect = require("cc.expect").expect
+
+local methods = {
+    GET = true, POST = true, HEAD = true,
+    OPTIONS = true, PUT = true, DELETE = true,
+    PATCH = true, TRACE = true,
+}
+
+local function checkKey(options, key, ty, opt)
+    local value = options[key]
+    local valueTy = type(value)
+
+    if (value ~= nil or not opt) and valueTy ~= ty then
+        error(("bad field '%s' (%s expected, got %s"):format(key, ty, valueTy), 4)
+    end
+end
+
+local function checkOptions(options, body)
+    checkKey(options, "url", "string")
+    if body == false then
+        checkKey(options, "body", "nil")
+    else
+        checkKey(options, "body", "string", not body)
+    end
+    checkKey(options, "headers", "table", true)
+    checkKey(options, "method", "string", true)
+    checkKey(options, "redirect", "boolean", true)
+    checkKey(options, "timeout", "number", true)
+
+    if options.method and not methods[options.method] then
+        error("Unsupported HTTP method", 3)
+    end
+end
+
+local function wrapRequest(_url, ...)
+    local ok, err = native.request(...)
+    if ok then
+        while true do
+            local event, param1, param2, param3 = os.pullEvent()
+            if event == "http_success" and param1 == _url then
+                return param2
+            elseif event == "http_failure" and param1 == _url then
+                return nil, param2, param3
+            end
+        end
+    end
+    return nil, err
+end
+
+local http = {}
+
+function http.get(_url, _headers, _binary)
+    if type(_url) == "table" then
+        checkOptions(_url, false)
+        return wrapRequest(_url.url, _url)
+    end
+
+    expect(1, _url, "string")
+    expect(2, _headers, "table", "nil")
+    expect(3, _binary, "boolean", "nil")
+    return wrapRequest(_url, _url, nil, _headers, _binary)
+end
+
+function http.post(_url, _post, _headers, _binary)
+    if type(_url) == "table" then
+        checkOptions(_url, true)
+        return wrapRequest(_url.url, _url)
+    end
+
+    expect(1, _url, "string")
+    expect(2, _post, "string")
+    expect(3, _headers, "table", "nil")
+    expect(4, _binary, "boolean", "nil")
+    return wrapRequest(_url, _url, _post, _headers, _binary)
+end
+
+function http.request(_url, _post, _headers, _binary)
+    local url
+    if type(_url) == "table" then
+        checkOptions(_url)
+        url = _url.url
+    else
+        expect(1, _url, "string")
+        expect(2, _post, "string", "nil")
+        expect(3, _headers, "table", "nil")
+        expect(4, _binary, "boolean", "nil")
+        url = _url
+    end
+
+    local ok, err = native.request(_url, _post, _headers, _binary)
+    if not ok then
+        os.queueEvent("http_failure", url, err)
+    end
+
+    -- Return true/false for legacy reasons. Undocumented, as it shouldn't be
+    -- relied on.
+    return ok, err
+end
+
+function http.checkURLAsync(_url)
+    expect(1, _url, "string")
+    return native.checkURL(_url)
+end
+
+function http.checkURL(_url)
+    expect(1, _url, "string")
+    local ok, err = native.checkURL(_url)
+    if not ok then
+        return ok, err
+    end
+
+    while true do
+        local _, url, ok, err = os.pullEvent("http_check")
+        if url == _url then
+            return ok, err
+        end
+    end
+end
+
+function http.websocketAsync(_url, _headers)
+    expect(1, _url, "string")
+    expect(2, _headers, "table", "nil")
+
+    local ok, err = native.websocket(_url, _headers)
+    if not ok then
+        os.queueEvent("websocket_failure", _url, err)
+    end
+
+    return ok, err
+end
+
+function http.websocket(_url, _headers)
+    expect(1, _url, "string")
+    expect(2, _headers, "table", "nil")
+
+    local ok, err = native.websocket(_url, _headers)
+    if not ok then
+        return ok, err
+    end
+
+    while true do
+        local event, url, param = os.pullEvent()
+        if event == "websocket_success" and url == _url then
+            return param
+        elseif event == "websocket_failure" and url == _url then
+            return false, param
+        end
+    end
+end
+
+-- Adds `receive` to a websocket handle, which waits for the next
+-- `websocket_message` event from its server.
+local function wrapWebsocket(handle, _url, isOpen)
+    function handle.receive(timeout)
+        expect(1, timeout, "number", "nil")
+        if not isOpen() then
+            error("attempt to use a closed file", 2)
+        end
+
+        local timer = timeout and os.startTimer(timeout)
+        while true do
+            local event, param1, param2, param3 = os.pullEvent()
+            if event == "websocket_message" and param1 == _url then
+                if timer then
+                    os.cancelTimer(timer)
+                end
+                return param2, param3
+            elseif event == "websocket_closed" and param1 == _url then
+                if timer then
+                    os.cancelTimer(timer)
+                end
+                return nil
+            elseif event == "timer" and param1 == timer then
+                return nil
+            end
+        end
+    end
+end
+
+return http, wrapWebsocket
@@ -0,0 +1,300 @@
+-- A port of ComputerCraft's `io` library, which emulates Lua's standard `io`
+-- library on top of the `fs` API.
+
+local type_of = type
+
+local expect = require("cc.expect").expect
+
+local io = {}
+
+local handle_metatable
+
+local function make_file(handle)
+    return setmetatable({ _handle = handle }, handle_metatable)
+end
+
+local function check_file(file)
+    if type_of(file) ~= "table" or getmetatable(file) ~= handle_metatable then
+        error("bad argument #1 (FILE expected, got " .. type_of(file) .. ")", 3)
+    end
+
+    if file._closed then
+        error("attempt to use a closed file", 3)
+    end
+end
+
+-- Closes files opened by `io.lines` once they have been read to the end.
+local function check_result(file, ...)
+    if ... == nil and file._autoclose and not file._closed then
+        file:close()
+    end
+
+    return ...
+end
+
+handle_metatable = {
+    __name = "FILE*",
+    __tostring = function(self)
+        if self._closed then
+            return "file (closed)"
+        end
+
+        local hash = tostring(self._handle):match("table: (%x+)")
+        return "file (" .. hash .. ")"
+    end,
+    __index = {
+        close = function(self)
+            check_file(self)
+
+            local handle = self._handle
+            if not handle.close then
+                return nil, "attempt to close standard stream"
+            end
+
+            self._closed = true
+            handle.close()
+            return true
+        end,
+        flush = function(self)
+            check_file(self)
+
+            local handle = self._handle
+            if handle.flush then
+                handle.flush()
+            end
+            return true
+        end,
+        lines = function(self, ...)
+            check_file(self)
+
+            if not self._handle.read and not self._handle.readLine then
+                return nil, "file is not readable"
+            end
+
+            local args = table.pack(...)
+            return function()
+                if self._closed then
+                    error("file is already closed", 2)
+                end
+
+                return check_result(self, self:read(table.unpack(args, 1, args.n)))
+            end
+        end,
+        read = function(self, ...)
+            check_file(self)
+
+            local handle = self._handle
+            if not handle.read and not handle.readLine then
+                return nil, "Not opened for reading"
+            end
+
+            local n = select("#", ...)
+            local output = {}
+            for i = 1, n do
+                local arg = select(i, ...)
+                local result
+                if type_of(arg) == "number" then
+                    if handle.read then
+                        result = handle.read(arg)
+                    end
+                elseif type_of(arg) == "string" then
+                    local format = arg:gsub("^%*", ""):sub(1, 1)
+
+                    if format == "l" then
+                        if handle.readLine then
+                            result = handle.readLine()
+                        end
+                    elseif format == "L" then
+                        if handle.readLine then
+                            result = handle.readLine(true)
+                        end
+                    elseif format == "a" then
+                        if handle.readAll then
+                            result = handle.readAll() or ""
+                        end
+                    elseif format == "n" then
+                        -- Numbers can't be read, so like ComputerCraft, skip this format.
+                        result = nil
+                    else
+                        error("bad argument #" .. i .. " (invalid format)", 2)
+                    end
+                else
+                    error("bad argument #" .. i .. " (expected string, got " .. type_of(arg) .. ")", 2)
+                end
+
+                output[i] = result
+                if not result then
+                    break
+                end
+            end
+
+            -- Default to reading a line.
+            if n == 0 and handle.readLine then
+                return handle.readLine()
+            end
+
+            return table.unpack(output, 1, n)
+        end,
+        seek = function(self, whence, offset)
+            check_file(self)
+
+            local handle = self._handle
+            if not handle.seek then
+                return nil, "file is not seekable"
+            end
+
+            return handle.seek(whence, offset)
+        end,
+        setvbuf = function(self, mode, size) end,
+        write = function(self, ...)
+            check_file(self)
+
+            local handle = self._handle
+            if not handle.write then
+                return nil, "file is not writable"
+            end
+
+            for i = 1, select("#", ...) do
+                local arg = select(i, ...)
+                expect(i, arg, "string", "number")
+                handle.write(arg)
+            end
+
+            return self
+        end,
+    },
+}
+
+local default_input = make_file({
+    readLine = function()
+        return _G.read()
+    end,
+})
+local default_output = make_file({
+    write = function(text)
+        _G.write(text)
+    end,
+})
+local default_error = make_file({
+    write = function(text)
+        local previous_color
+        if term.isColour() then
+            previous_color = term.getTextColour()
+            term.setTextColour(colors.red)
+        end
+
+        _G.write(text)
+
+        if previous_color then
+            term.setTextColour(previous_color)
+        end
+    end,
+})
+
+local current_input = default_input
+local current_output = default_output
+
+io.stdin = default_input
+io.stdout = default_output
+io.stderr = default_error
+
+function io.close(file)
+    if file == nil then
+        return current_output:close()
+    end
+
+    if type_of(file) ~= "table" or getmetatable(file) ~= handle_metatable then
+        error("bad argument #1 (FILE expected, got " .. type_of(file) .. ")", 2)
+    end
+
+    return file:close()
+end
+
+function io.flush()
+    return current_output:flush()
+end
+
+function io.input(file)
+    if type_of(file) == "string" then
+        local result, err = io.open(file, "r")
+        if not result then
+            error(err, 2)
+        end
+
+        current_input = result
+    elseif type_of(file) == "table" and getmetatable(file) == handle_metatable then
+        current_input = file
+    elseif file ~= nil then
+        error("bad argument #1 (FILE expected, got " .. type_of(file) .. ")", 2)
+    end
+
+    return current_input
+end
+
+function io.lines(filename, ...)
+    expect(1, filename, "string", "nil")
+
+    if not filename then
+        return current_input:lines(...)
+    end
+
+    local file, err = io.open(filename, "r")
+    if not file then
+        error(err, 2)
+    end
+
+    file._autoclose = true
+    return file:lines(...)
+end
+
+function io.open(filename, mode)
+    expect(1, filename, "string")
+    expect(2, mode, "string", "nil")
+
+    local fs_mode = mode and mode:gsub("%+", "") or "r"
+    local handle, err = fs.open(filename, fs_mode)
+    if not handle then
+        return nil, err
+    end
+
+    return make_file(handle)
+end
+
+function io.output(file)
+    if type_of(file) == "string" then
+        local result, err = io.open(file, "wb")
+        if not result then
+            error(err, 2)
+        end
+
+        current_output = result
+    elseif type_of(file) == "table" and getmetatable(file) == handle_metatable then
+        current_output = file
+    elseif file ~= nil then
+        error("bad argument #1 (FILE expected, got " .. type_of(file) .. ")", 2)
+    end
+
+    return current_output
+end
+
+function io.read(...)
+    return current_input:read(...)
+end
+
+function io.type(obj)
+    if type_of(obj) == "table" and getmetatable(obj) == handle_metatable then
+        if obj._closed then
+            return "closed file"
+        end
+
+        return "file"
+    end
+
+    return nil
+end
+
+function io.write(...)
+    return current_output:write(...)
+end
+
+return io
@@ -0,0 +1,169 @@
+-- A port of ComputerCraft's `peripheral` API, built on the native functions
+-- which act on a single peripheral.
+
+local native = ...
+
+local sides = { "bottom", "top", "back", "front", "right", "left" }
+
+local expect = require("cc.expect").expect
+
+local peripheral = {}
+
+function peripheral.getNames()
+    local names = {}
+    for _, side in ipairs(sides) do
+        if native.isPresent(side) then
+            table.insert(names, side)
+            if native.hasType(side, "peripheral_hub") then
+                for _, name in ipairs(native.call(side, "getNamesRemote")) do
+                    table.insert(names, name)
+                end
+            end
+        end
+    end
+
+    return names
+end
+
+-- Returns the side of the wired modem the remote peripheral is on, if any.
+local function find_hub(name)
+    for _, side in ipairs(sides) do
+        if native.hasType(side, "peripheral_hub") and native.call(side, "isPresentRemote", name) then
+            return side
+        end
+    end
+
+    return nil
+end
+
+function peripheral.isPresent(name)
+    expect(1, name, "string")
+
+    return native.isPresent(name) or find_hub(name) ~= nil
+end
+
+local function get_wrapped(value)
+    local meta = getmetatable(value)
+    if type(meta) == "table" and meta.__name == "peripheral" and type(meta.name) == "string" then
+        return meta
+    end
+
+    error("bad argument #1 (table is not a peripheral)", 3)
+end
+
+function peripheral.getType(value)
+    expect(1, value, "string", "table")
+    if type(value) == "table" then
+        return table.unpack(get_wrapped(value).types)
+    end
+
+    if native.isPresent(value) then
+        return native.getType(value)
+    end
+
+    local hub = find_hub(value)
+    if hub then
+        return native.call(hub, "getTypeRemote", value)
+    end
+
+    return nil
+end
+
+function peripheral.hasType(value, kind)
+    expect(1, value, "string", "table")
+    expect(2, kind, "string")
+    if type(value) == "table" then
+        return get_wrapped(value).types[kind] ~= nil
+    end
+
+    if native.isPresent(value) then
+        return native.hasType(value, kind)
+    end
+
+    local hub = find_hub(value)
+    if hub then
+        return native.call(hub, "hasTypeRemote", value, kind)
+    end
+
+    return nil
+end
+
+function peripheral.getMethods(name)
+    expect(1, name, "string")
+    if native.isPresent(name) then
+        return native.getMethods(name)
+    end
+
+    local hub = find_hub(name)
+    if hub then
+        return native.call(hub, "getMethodsRemote", name)
+    end
+
+    return nil
+end
+
+function peripheral.getName(value)
+    expect(1, value, "table")
+
+    return get_wrapped(value).name
+end
+
+function peripheral.call(name, method, ...)
+    expect(1, name, "string")
+    expect(2, method, "string")
+    if not native.isPresent(name) then
+        local hub = find_hub(name)
+        if hub then
+            return native.call(hub, "callRemote", name, method, ...)
+        end
+    end
+
+    return native.call(name, method, ...)
+end
+
+function peripheral.wrap(name)
+    expect(1, name, "string")
+
+    local methods = peripheral.getMethods(name)
+    if not methods then
+        return nil
+    end
+
+    local types = { peripheral.getType(name) }
+    for _, kind in ipairs(types) do
+        types[kind] = true
+    end
+
+    local result = setmetatable({}, {
+        __name = "peripheral",
+        name = name,
+        type = types[1],
+        types = types,
+    })
+    for _, method in ipairs(methods) do
+        result[method] = function(...)
+            return peripheral.call(name, method, ...)
+        end
+    end
+
+    return result
+end
+
+function peripheral.find(kind, filter)
+    expect(1, kind, "string")
+    expect(2, filter, "function", "nil")
+
+    local results = {}
+    for _, name in ipairs(peripheral.getNames()) do
+        if peripheral.hasType(name, kind) then
+            local wrapped = peripheral.wrap(name)
+            if filter == nil or filter(name, wrapped) then
+                table.insert(results, wrapped)
+            end
+        end
+    end
+
+    return table.unpack(results)
+end
+
+return peripheral
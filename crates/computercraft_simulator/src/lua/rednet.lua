@@ -0,0 +1,287 @@
+-- A port of ComputerCraft's `rednet` API, which sends messages between
+-- computers over modems.
+--
+-- In ComputerCraft, `rednet.run` runs alongside the shell, turning
+-- `modem_message` events into `rednet_message` ones and answering lookups.
+-- The simulator does the same by handing every event to the function this
+-- returns alongside the API, before the program sees it.
+
+local expect = require("cc.expect").expect
+
+local rednet = {}
+
+-- The channel used to send messages to every computer.
+rednet.CHANNEL_BROADCAST = 65535
+
+-- The channel repeaters listen on, to pass messages on to computers out of
+-- the sender's range.
+rednet.CHANNEL_REPEAT = 65533
+
+-- The number of channels reserved for computer IDs. Computers with higher IDs
+-- share channels with lower ones.
+rednet.MAX_ID_CHANNELS = 65500
+
+-- The IDs of messages which have already been received, and when they can be
+-- forgotten. Repeaters pass messages on more than once, so this stops them
+-- being received twice.
+local received_messages = {}
+local prune_received_timer
+
+local hostnames = {}
+
+local function id_as_channel(id)
+    return (id or os.getComputerID()) % rednet.MAX_ID_CHANNELS
+end
+
+local function remember_message(message_id)
+    received_messages[message_id] = os.clock() + 9.5
+    if not prune_received_timer then
+        prune_received_timer = os.startTimer(10)
+    end
+end
+
+function rednet.open(modem)
+    expect(1, modem, "string")
+    if peripheral.getType(modem) ~= "modem" then
+        error("No such modem: " .. modem, 2)
+    end
+
+    peripheral.call(modem, "open", id_as_channel())
+    peripheral.call(modem, "open", rednet.CHANNEL_BROADCAST)
+end
+
+function rednet.close(modem)
+    expect(1, modem, "string", "nil")
+    if modem then
+        if peripheral.getType(modem) ~= "modem" then
+            error("No such modem: " .. modem, 2)
+        end
+
+        peripheral.call(modem, "close", id_as_channel())
+        peripheral.call(modem, "close", rednet.CHANNEL_BROADCAST)
+    else
+        for _, name in ipairs(peripheral.getNames()) do
+            if rednet.isOpen(name) then
+                rednet.close(name)
+            end
+        end
+    end
+end
+
+function rednet.isOpen(modem)
+    expect(1, modem, "string", "nil")
+    if modem then
+        if peripheral.getType(modem) == "modem" then
+            return peripheral.call(modem, "isOpen", id_as_channel())
+                and peripheral.call(modem, "isOpen", rednet.CHANNEL_BROADCAST)
+        end
+    else
+        for _, name in ipairs(peripheral.getNames()) do
+            if rednet.isOpen(name) then
+                return true
+            end
+        end
+    end
+
+    return false
+end
+
+function rednet.send(recipient, message, protocol)
+    expect(1, recipient, "number")
+    expect(3, protocol, "string", "nil")
+
+    local message_id = math.random(1, 2147483647)
+    remember_message(message_id)
+
+    local reply_channel = id_as_channel()
+    local message_wrapper = {
+        nMessageID = message_id,
+        nRecipient = recipient,
+        nSender = os.getComputerID(),
+        message = message,
+        sProtocol = protocol,
+    }
+
+    if recipient == os.getComputerID() then
+        os.queueEvent("rednet_message", os.getComputerID(), message, protocol)
+        return true
+    end
+
+    local channel = recipient
+    if recipient ~= rednet.CHANNEL_BROADCAST then
+        channel = id_as_channel(recipient)
+    end
+
+    local sent = false
+    for _, name in ipairs(peripheral.getNames()) do
+        if rednet.isOpen(name) then
+            peripheral.call(name, "transmit", channel, reply_channel, message_wrapper)
+            peripheral.call(name, "transmit", rednet.CHANNEL_REPEAT, reply_channel, message_wrapper)
+            sent = true
+        end
+    end
+
+    return sent
+end
+
+function rednet.broadcast(message, protocol)
+    expect(2, protocol, "string", "nil")
+
+    rednet.send(rednet.CHANNEL_BROADCAST, message, protocol)
+end
+
+function rednet.receive(protocol_filter, timeout)
+    -- The parameters used to be ( nTimeout ), detect this case for backwards
+    -- compatibility.
+    if type(protocol_filter) == "number" and timeout == nil then
+        protocol_filter, timeout = nil, protocol_filter
+    end
+    expect(1, protocol_filter, "string", "nil")
+    expect(2, timeout, "number", "nil")
+
+    local timer, event_filter
+    if timeout then
+        timer = os.startTimer(timeout)
+    else
+        event_filter = "rednet_message"
+    end
+
+    while true do
+        local event, p1, p2, p3 = os.pullEvent(event_filter)
+        if event == "rednet_message" then
+            local sender_id, message, protocol = p1, p2, p3
+            if protocol_filter == nil or protocol == protocol_filter then
+                return sender_id, message, protocol
+            end
+        elseif event == "timer" and p1 == timer then
+            return nil
+        end
+    end
+end
+
+function rednet.host(protocol, hostname)
+    expect(1, protocol, "string")
+    expect(2, hostname, "string")
+    if hostname == "localhost" then
+        error("Reserved hostname", 2)
+    end
+
+    if hostnames[protocol] ~= hostname then
+        if rednet.lookup(protocol, hostname) ~= nil then
+            error("Hostname in use", 2)
+        end
+
+        hostnames[protocol] = hostname
+    end
+end
+
+function rednet.unhost(protocol)
+    expect(1, protocol, "string")
+
+    hostnames[protocol] = nil
+end
+
+function rednet.lookup(protocol, hostname)
+    expect(1, protocol, "string")
+    expect(2, hostname, "string", "nil")
+
+    local results
+    if hostname == nil then
+        results = {}
+    end
+
+    if hostnames[protocol] then
+        if hostname == nil then
+            table.insert(results, os.getComputerID())
+        elseif hostname == "localhost" or hostname == hostnames[protocol] then
+            return os.getComputerID()
+        end
+    end
+
+    if not rednet.isOpen() then
+        if results then
+            return table.unpack(results)
+        end
+
+        return nil
+    end
+
+    rednet.broadcast({ sType = "lookup", sProtocol = protocol, sHostname = hostname }, "dns")
+
+    -- Collect the replies for a couple of seconds.
+    local timer = os.startTimer(2)
+    while true do
+        local event, p1, p2, p3 = os.pullEvent()
+        if event == "rednet_message" then
+            local sender_id, message, message_protocol = p1, p2, p3
+            if message_protocol == "dns" and type(message) == "table"
+                and message.sType == "lookup response" and message.sProtocol == protocol
+            then
+                if hostname == nil then
+                    table.insert(results, sender_id)
+                elseif message.sHostname == hostname then
+                    return sender_id
+                end
+            end
+        elseif event == "timer" and p1 == timer then
+            break
+        end
+    end
+
+    if results then
+        return table.unpack(results)
+    end
+
+    return nil
+end
+
+function rednet.run()
+    error("rednet is already running", 2)
+end
+
+-- The body of ComputerCraft's `rednet.run` loop.
+local function handle_event(event, p1, p2, p3, p4)
+    if event == "modem_message" then
+        local modem, channel, reply_channel, message = p1, p2, p3, p4
+        if (channel == id_as_channel() or channel == rednet.CHANNEL_BROADCAST)
+            and type(message) == "table" and type(message.nMessageID) == "number"
+            and not received_messages[message.nMessageID]
+            and (message.nRecipient == nil or message.nRecipient == os.getComputerID()
+                or message.nRecipient == rednet.CHANNEL_BROADCAST)
+            and rednet.isOpen(modem)
+        then
+            remember_message(message.nMessageID)
+            os.queueEvent("rednet_message", message.nSender or reply_channel, message.message, message.sProtocol)
+        end
+    elseif event == "rednet_message" then
+        -- Answer lookups for the protocols this computer hosts.
+        local sender_id, message, protocol = p1, p2, p3
+        if protocol == "dns" and type(message) == "table" and message.sType == "lookup" then
+            local hostname = hostnames[message.sProtocol]
+            if hostname ~= nil and (message.sHostname == nil or message.sHostname == hostname) then
+                rednet.send(sender_id, {
+                    sType = "lookup response",
+                    sHostname = hostname,
+                    sProtocol = message.sProtocol,
+                }, "dns")
+            end
+        end
+    elseif event == "timer" and p1 == prune_received_timer then
+        -- Forget the messages which are too old to be repeated.
+        prune_received_timer = nil
+        local now, has_more = os.clock(), false
+        for message_id, deadline in pairs(received_messages) do
+            if deadline <= now then
+                received_messages[message_id] = nil
+            else
+                has_more = true
+            end
+        end
+
+        if has_more then
+            prune_received_timer = os.startTimer(10)
+        end
+    end
+end
+
+return rednet, handle_event
@@ -0,0 +1,87 @@
+-- A port of ComputerCraft's `repeat` program, which passes rednet messages on
+-- to computers out of the sender's range.
+--
+-- The simulator puts it in the ROM at `/rom/programs/rednet/repeat.lua`.
+
+local modems = {}
+for _, name in ipairs(peripheral.getNames()) do
+    if peripheral.getType(name) == "modem" then
+        modems[#modems + 1] = name
+    end
+end
+
+if #modems == 0 then
+    print("No modems found.")
+    return
+elseif #modems == 1 then
+    print("1 modem found.")
+else
+    print(#modems .. " modems found.")
+end
+
+local function open(channel)
+    for _, modem in ipairs(modems) do
+        peripheral.call(modem, "open", channel)
+    end
+end
+
+local function close(channel)
+    for _, modem in ipairs(modems) do
+        peripheral.call(modem, "close", channel)
+    end
+end
+
+print("0 messages repeated.")
+open(rednet.CHANNEL_REPEAT)
+
+local ok, err = pcall(function()
+    local received_messages = {}
+    local received_message_timeouts = {}
+    local transmitted_messages = 0
+
+    while true do
+        local event, modem, channel, reply_channel, message = os.pullEvent()
+        if event == "modem_message" then
+            -- Pass on rednet messages, but only the first time they're heard.
+            if channel == rednet.CHANNEL_REPEAT and type(message) == "table"
+                and message.nMessageID and type(message.nRecipient) == "number"
+                and not received_messages[message.nMessageID]
+            then
+                received_messages[message.nMessageID] = true
+                received_message_timeouts[os.startTimer(30)] = message.nMessageID
+
+                local recipient_channel = message.nRecipient
+                if message.nRecipient ~= rednet.CHANNEL_BROADCAST then
+                    recipient_channel = recipient_channel % rednet.MAX_ID_CHANNELS
+                end
+
+                -- Send it to the recipient, and to other repeaters.
+                for _, other_modem in ipairs(modems) do
+                    peripheral.call(other_modem, "transmit", rednet.CHANNEL_REPEAT, reply_channel, message)
+                    peripheral.call(other_modem, "transmit", recipient_channel, reply_channel, message)
+                end
+
+                transmitted_messages = transmitted_messages + 1
+                if transmitted_messages == 1 then
+                    print("1 message repeated.")
+                else
+                    print(transmitted_messages .. " messages repeated.")
+                end
+            end
+        elseif event == "timer" then
+            -- Forget the messages which are too old to be repeated again.
+            local timer = modem
+            local message_id = received_message_timeouts[timer]
+            if message_id then
+                received_message_timeouts[timer] = nil
+                received_messages[message_id] = nil
+            end
+        end
+    end
+end)
+
+if not ok then
+    printError(err)
+end
+
+close(rednet.CHANNEL_REPEAT)
@@ -0,0 +1,132 @@
+-- A port of ComputerCraft's `cc.require` module, which implements `require`
+-- and `package` on top of the `fs` API.
+--
+-- Takes the `cc.expect` module, which every program can require, and whether
+-- the computer is a turtle, and returns a function which creates a `require` function and `package` table for a
+-- program running in the given environment and directory.
+
+local cc_expect, is_turtle = ...
+local expect = cc_expect.expect
+
+local function preload(package)
+    return function(name)
+        if package.preload[name] then
+            return package.preload[name]
+        else
+            return nil, "no field package.preload['" .. name .. "']"
+        end
+    end
+end
+
+local function from_file(package, env)
+    return function(name)
+        local path, err = package.searchpath(name, package.path)
+        if not path then
+            return nil, err
+        end
+
+        local fn, err = loadfile(path, nil, env)
+        if fn then
+            return fn, path
+        else
+            return nil, err
+        end
+    end
+end
+
+local function make_searchpath(dir)
+    return function(name, path, sep, rep)
+        expect(1, name, "string")
+        expect(2, path, "string")
+        expect(3, sep, "string", "nil")
+        expect(4, rep, "string", "nil")
+
+        if not sep or sep ~= "" then
+            name = name:gsub((sep or "."):gsub("%p", "%%%0"), rep or "/")
+        end
+
+        local tried = {}
+        for pattern in path:gmatch("[^;]+") do
+            local file = pattern:gsub("%?", (name:gsub("%%", "%%%%")))
+            if file:sub(1, 1) ~= "/" then
+                file = fs.combine(dir, file)
+            end
+
+            if fs.exists(file) and not fs.isDir(file) then
+                return file
+            end
+
+            tried[#tried + 1] = "no file '" .. file .. "'"
+        end
+
+        return nil, table.concat(tried, "\n  ")
+    end
+end
+
+-- Marks a module which is still being loaded, so loops can be detected.
+local sentinel = {}
+
+local function make_require(package)
+    return function(name)
+        expect(1, name, "string")
+
+        if package.loaded[name] == sentinel then
+            error("loop or previous error loading module '" .. name .. "'", 0)
+        end
+
+        if package.loaded[name] then
+            return package.loaded[name]
+        end
+
+        local err = "module '" .. name .. "' not found:"
+        for _, searcher in ipairs(package.loaders) do
+            local loader = table.pack(searcher(name))
+            if loader[1] then
+                package.loaded[name] = sentinel
+                local result = loader[1](name, table.unpack(loader, 2, loader.n))
+                if result == nil then
+                    result = true
+                end
+
+                package.loaded[name] = result
+                return result
+            else
+                err = err .. "\n  " .. loader[2]
+            end
+        end
+
+        error(err, 2)
+    end
+end
+
+return function(env, dir)
+    expect(1, env, "table")
+    expect(2, dir, "string")
+
+    local package = {}
+    package.loaded = {
+        _G = _G,
+        bit32 = bit32,
+        ["cc.expect"] = cc_expect,
+        coroutine = coroutine,
+        math = math,
+        package = package,
+        string = string,
+        table = table,
+    }
+    -- Like in ComputerCraft, relative templates are resolved against the
+    -- running program's directory. Programs in this repository require
+    -- modules from the root of the project, so it is searched as well.
+    package.path = "?;?.lua;?/init.lua;/?;/?.lua;/?/init.lua;"
+        .. "/rom/modules/main/?;/rom/modules/main/?.lua;/rom/modules/main/?/init.lua"
+    if is_turtle then
+        package.path = package.path
+            .. ";/rom/modules/turtle/?;/rom/modules/turtle/?.lua;/rom/modules/turtle/?/init.lua"
+    end
+    package.config = "/\n;\n?\n!\n-"
+    package.preload = {}
+    package.loaders = { preload(package), from_file(package, env) }
+    package.searchpath = make_searchpath(dir)
+
+    return make_require(package), package
+end
@@ -0,0 +1,192 @@
+-- A port of ComputerCraft's `settings` API, which stores options on the
+-- computer's filesystem so programs can be configured without editing them.
+
+local type_of = type
+
+local cc_expect = require("cc.expect")
+local expect, field = cc_expect.expect, cc_expect.field
+
+local settings = {}
+
+local details, values = {}, {}
+
+-- Round-trips tables through `textutils.serialize`, both to copy them and to
+-- reject values which couldn't be saved.
+local function reserialize(value)
+    if type_of(value) ~= "table" then
+        return value
+    end
+
+    return textutils.unserialize(textutils.serialize(value))
+end
+
+local function copy(value)
+    if type_of(value) ~= "table" then
+        return value
+    end
+
+    local result = {}
+    for k, v in pairs(value) do
+        result[k] = copy(v)
+    end
+
+    return result
+end
+
+local valid_types = { "number", "string", "boolean", "table" }
+for _, ty in ipairs(valid_types) do
+    valid_types[ty] = true
+end
+
+function settings.define(name, options)
+    expect(1, name, "string")
+    expect(2, options, "table", "nil")
+
+    if options then
+        options = {
+            description = field(options, "description", "string", "nil"),
+            default = reserialize(field(options, "default", "number", "string", "boolean", "table", "nil")),
+            type = field(options, "type", "string", "nil"),
+        }
+
+        if options.type and not valid_types[options.type] then
+            error(("Unknown type %q. Expected one of %s."):format(options.type, table.concat(valid_types, ", ")), 2)
+        end
+    else
+        options = {}
+    end
+
+    details[name] = options
+end
+
+function settings.undefine(name)
+    expect(1, name, "string")
+    details[name] = nil
+end
+
+local function set_value(name, new)
+    local old = values[name]
+    if old == nil then
+        local options = details[name]
+        old = options and options.default
+    end
+
+    values[name] = new
+    if old ~= new then
+        os.queueEvent("setting_changed", name, new, old)
+    end
+end
+
+function settings.set(name, value)
+    expect(1, name, "string")
+    expect(2, value, "number", "string", "boolean", "table")
+
+    local options = details[name]
+    if options and options.type then
+        expect(2, value, options.type)
+    end
+
+    set_value(name, reserialize(value))
+end
+
+function settings.get(name, default)
+    expect(1, name, "string")
+
+    local result = values[name]
+    if result ~= nil then
+        return copy(result)
+    elseif default ~= nil then
+        return default
+    end
+
+    local options = details[name]
+    return options and copy(options.default)
+end
+
+function settings.getDetails(name)
+    expect(1, name, "string")
+
+    local result = copy(details[name]) or {}
+    result.value = values[name]
+    result.changed = result.value ~= nil
+    if result.value == nil then
+        result.value = result.default
+    end
+
+    return result
+end
+
+function settings.unset(name)
+    expect(1, name, "string")
+    set_value(name, nil)
+end
+
+function settings.clear()
+    for name in pairs(values) do
+        set_value(name, nil)
+    end
+end
+
+function settings.getNames()
+    local result = {}
+    for name in pairs(details) do
+        result[#result + 1] = name
+    end
+    for name in pairs(values) do
+        if not details[name] then
+            result[#result + 1] = name
+        end
+    end
+
+    table.sort(result)
+    return result
+end
+
+function settings.load(path)
+    expect(1, path, "string", "nil")
+
+    local file = fs.open(path or ".settings", "r")
+    if not file then
+        return false
+    end
+
+    local text = file.readAll()
+    file.close()
+
+    local loaded = textutils.unserialize(text)
+    if type_of(loaded) ~= "table" then
+        return false
+    end
+
+    for name, value in pairs(loaded) do
+        local ty = type_of(value)
+        if type_of(name) == "string" and valid_types[ty] then
+            local options = details[name]
+            if not options or not options.type or ty == options.type then
+                -- Recursive tables can't be serialized, so skip them.
+                local ok, value = pcall(reserialize, value)
+                if ok then
+                    set_value(name, value)
+                end
+            end
+        end
+    end
+
+    return true
+end
+
+function settings.save(path)
+    expect(1, path, "string", "nil")
+
+    local file = fs.open(path or ".settings", "w")
+    if not file then
+        return false
+    end
+
+    file.write(textutils.serialize(values))
+    file.close()
+
+    return true
+end
+
+return settings
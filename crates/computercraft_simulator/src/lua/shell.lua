@@ -0,0 +1,382 @@
+-- A port of ComputerCraft's `shell` API, which runs programs from the
+-- computer's filesystem.
+--
+-- Returns the `shell` table, a function which runs a program like
+-- `shell.execute` but raises its errors rather than printing them, and a
+-- function which runs the startup programs.
+
+local make_require = ...
+
+local expect = require("cc.expect").expect
+
+local shell = {}
+
+local current_dir = ""
+-- Like `/rom/programs`, the repository's `programs` directory is on the path,
+-- so its programs can be run by name.
+local path = ".:/programs:/rom/programs:/rom/programs/rednet:/rom/programs/turtle"
+local aliases = {
+    ls = "list",
+    dir = "list",
+    cp = "copy",
+    mv = "move",
+    rm = "delete",
+    clr = "clear",
+    rs = "redstone",
+    sh = "shell",
+}
+local program_stack = {}
+local completion_info = {}
+local next_tab = 1
+
+local function create_shell_env(dir)
+    local env = { shell = shell }
+    env.require, env.package = make_require(env, dir)
+    return env
+end
+
+-- Splits the arguments into words, keeping quoted strings together.
+local function tokenise(...)
+    local line = table.concat({ ... }, " ")
+    local words = {}
+    local quoted = false
+    for match in (line .. "\""):gmatch("(.-)\"") do
+        if quoted then
+            words[#words + 1] = match
+        else
+            for word in match:gmatch("[^ \t]+") do
+                words[#words + 1] = word
+            end
+        end
+        quoted = not quoted
+    end
+
+    return words
+end
+
+local function path_with_extension(path, extension)
+    local last = path:sub(-1)
+    if last == "/" or last == "\\" then
+        path = path:sub(1, -2)
+    end
+
+    return path .. "." .. extension
+end
+
+local function find_file(path)
+    if fs.exists(path) and not fs.isDir(path) then
+        return path
+    end
+
+    local lua_path = path_with_extension(path, "lua")
+    if fs.exists(lua_path) and not fs.isDir(lua_path) then
+        return lua_path
+    end
+
+    return nil
+end
+
+function shell.execute(command, ...)
+    expect(1, command, "string")
+    for i = 1, select("#", ...) do
+        expect(i + 1, select(i, ...), "string")
+    end
+
+    local program = shell.resolveProgram(command)
+    if program == nil then
+        printError("No such program")
+        return false
+    end
+
+    program_stack[#program_stack + 1] = program
+
+    local env = create_shell_env(fs.getDir(program))
+    env.arg = { [0] = command, ... }
+    local result = os.run(env, program, ...)
+
+    program_stack[#program_stack] = nil
+    return result
+end
+
+function shell.run(...)
+    local words = tokenise(...)
+    local command = words[1]
+    if command then
+        return shell.execute(command, table.unpack(words, 2))
+    end
+
+    return false
+end
+
+function shell.dir()
+    return current_dir
+end
+
+function shell.setDir(dir)
+    expect(1, dir, "string")
+    if not fs.isDir(dir) then
+        error("Not a directory", 2)
+    end
+
+    current_dir = fs.combine(dir, "")
+end
+
+function shell.path()
+    return path
+end
+
+function shell.setPath(new_path)
+    expect(1, new_path, "string")
+    path = new_path
+end
+
+function shell.resolve(path)
+    expect(1, path, "string")
+
+    local first = path:sub(1, 1)
+    if first == "/" or first == "\\" then
+        return fs.combine("", path)
+    else
+        return fs.combine(current_dir, path)
+    end
+end
+
+function shell.resolveProgram(command)
+    expect(1, command, "string")
+
+    if aliases[command] ~= nil then
+        command = aliases[command]
+    end
+
+    -- Paths are resolved directly rather than being looked up on the path.
+    if command:find("/") or command:find("\\") then
+        return find_file(shell.resolve(command))
+    end
+
+    for dir in path:gmatch("[^:]+") do
+        local program = find_file(fs.combine(shell.resolve(dir), command))
+        if program then
+            return program
+        end
+    end
+
+    return nil
+end
+
+function shell.programs(include_hidden)
+    expect(1, include_hidden, "boolean", "nil")
+
+    local programs = {}
+    for dir in path:gmatch("[^:]+") do
+        dir = shell.resolve(dir)
+        if fs.isDir(dir) then
+            for _, file in ipairs(fs.list(dir)) do
+                if not fs.isDir(fs.combine(dir, file)) and (include_hidden or file:sub(1, 1) ~= ".") then
+                    if #file > 4 and file:sub(-4) == ".lua" then
+                        file = file:sub(1, -5)
+                    end
+                    programs[file] = true
+                end
+            end
+        end
+    end
+
+    local sorted = {}
+    for program in pairs(programs) do
+        sorted[#sorted + 1] = program
+    end
+    table.sort(sorted)
+
+    return sorted
+end
+
+local function complete_program(line)
+    local include_hidden = settings.get("shell.autocomplete_hidden")
+    if #line > 0 and (line:sub(1, 1) == "/" or line:sub(1, 1) == "\\") then
+        return fs.complete(line, current_dir, {
+            include_files = true,
+            include_dirs = false,
+            include_hidden = include_hidden,
+        })
+    end
+
+    local results = {}
+    local seen = {}
+    local function add(candidate)
+        if #candidate > #line and candidate:sub(1, #line) == line then
+            local result = candidate:sub(#line + 1)
+            if not seen[result] then
+                results[#results + 1] = result
+                seen[result] = true
+            end
+        end
+    end
+
+    for alias in pairs(aliases) do
+        add(alias)
+    end
+
+    -- Files are added from the path below, so only include directories here.
+    for _, dir in ipairs(fs.complete(line, current_dir, {
+        include_files = false,
+        include_dirs = false,
+        include_hidden = include_hidden,
+    })) do
+        add(line .. dir)
+    end
+
+    for _, program in ipairs(shell.programs(include_hidden)) do
+        add(program)
+    end
+
+    table.sort(results)
+    return results
+end
+
+function shell.complete(line)
+    expect(1, line, "string")
+    if #line == 0 then
+        return nil
+    end
+
+    local words = tokenise(line)
+    local index = #words
+    if line:sub(-1) == " " then
+        index = index + 1
+    end
+
+    if index == 1 then
+        local partial = words[1] or ""
+        if completion_info[shell.resolveProgram(partial) or ""] then
+            return { " " }
+        end
+
+        local results = complete_program(partial)
+        for i, result in ipairs(results) do
+            if completion_info[shell.resolveProgram(partial .. result) or ""] then
+                results[i] = result .. " "
+            end
+        end
+
+        return results
+    end
+
+    local info = completion_info[shell.resolveProgram(words[1]) or ""]
+    if info then
+        local current = words[index] or ""
+        words[index] = nil
+        return info.fnComplete(shell, index - 1, current, words)
+    end
+
+    return nil
+end
+
+function shell.completeProgram(line)
+    expect(1, line, "string")
+    return complete_program(line)
+end
+
+function shell.setCompletionFunction(program, complete)
+    expect(1, program, "string")
+    expect(2, complete, "function")
+
+    completion_info[program] = { fnComplete = complete }
+end
+
+function shell.getCompletionInfo()
+    return completion_info
+end
+
+function shell.getRunningProgram()
+    return program_stack[#program_stack]
+end
+
+function shell.setAlias(command, program)
+    expect(1, command, "string")
+    expect(2, program, "string")
+
+    aliases[command] = program
+end
+
+function shell.clearAlias(command)
+    expect(1, command, "string")
+
+    aliases[command] = nil
+end
+
+function shell.aliases()
+    local copy = {}
+    for alias, command in pairs(aliases) do
+        copy[alias] = command
+    end
+
+    return copy
+end
+
+-- The simulator only has a single tab, so programs opened in a new tab are
+-- run to completion straight away, rather than alongside the current program.
+function shell.openTab(...)
+    local words = tokenise(...)
+    local command = words[1]
+    if command then
+        next_tab = next_tab + 1
+        shell.execute(command, table.unpack(words, 2))
+        return next_tab
+    end
+end
+
+function shell.switchTab(id)
+    expect(1, id, "number")
+end
+
+local function run_program(command, ...)
+    local program = shell.resolveProgram(command)
+    if program == nil then
+        error("No such program", 0)
+    end
+
+    program_stack = { program }
+
+    -- Unlike `shell.execute`, `arg[0]` is the program's path rather than the
+    -- command, as it wasn't typed into the shell.
+    local env = setmetatable(create_shell_env(fs.getDir(program)), { __index = _G })
+    env.arg = { [0] = program, ... }
+    local fn, err = loadfile(program, nil, env)
+    if not fn then
+        error(err, 0)
+    end
+
+    fn(...)
+    program_stack = {}
+end
+
+-- Like ComputerCraft's `/rom/startup.lua`, runs `startup.lua`, followed by
+-- the programs in the `startup` directory, unless `shell.allow_startup` is
+-- turned off.
+local function run_startup()
+    if not settings.get("shell.allow_startup") then
+        return
+    end
+
+    local startups = {}
+
+    local startup = shell.resolveProgram("/startup")
+    if startup then
+        startups[#startups + 1] = startup
+    end
+
+    if fs.isDir("startup") then
+        for _, file in ipairs(fs.list("startup")) do
+            local path = fs.combine("startup", file)
+            if not fs.isDir(path) then
+                startups[#startups + 1] = path
+            end
+        end
+    end
+
+    for _, program in ipairs(startups) do
+        run_program("/" .. program)
+    end
+end
+
+return shell, run_program, run_startup
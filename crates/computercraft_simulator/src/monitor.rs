@@ -0,0 +1,152 @@
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+
+use crate::Terminal;
+
+/// A monitor, which displays a terminal on the blocks it is made of.
+#[derive(Debug)]
+pub struct Monitor {
+    /// The width and height of the monitor, in blocks.
+    size: (u32, u32),
+    /// The text scale, in halves.
+    text_scale: u32,
+    terminal: Rc<RefCell<Terminal>>,
+}
+
+impl Monitor {
+    /// The widest a monitor can be, in blocks.
+    pub const MAX_WIDTH: u32 = 8;
+
+    /// The tallest a monitor can be, in blocks.
+    pub const MAX_HEIGHT: u32 = 6;
+
+    /// Creates a monitor made of the given number of blocks across and down,
+    /// with a text scale of 1. Advanced monitors can display colors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the monitor is bigger than ComputerCraft allows.
+    pub fn new(width: u32, height: u32, is_advanced: bool) -> Self {
+        let text_scale = 2;
+        let (columns, rows) = terminal_size((width, height), text_scale);
+
+        let mut monitor = Self {
+            size: (width, height),
+            text_scale,
+            terminal: Rc::new(RefCell::new(Terminal::new(columns, rows, is_advanced))),
+        };
+        monitor.set_size(width, height);
+        monitor
+    }
+
+    pub fn is_advanced(&self) -> bool {
+        self.terminal.borrow().is_color()
+    }
+
+    /// Returns the width and height of the monitor, in blocks.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Changes how many blocks the monitor is made of, as if blocks were added
+    /// or removed. Returns whether the terminal changed size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the monitor is bigger than ComputerCraft allows.
+    pub fn set_size(&mut self, width: u32, height: u32) -> bool {
+        assert!(
+            (1..=Self::MAX_WIDTH).contains(&width) && (1..=Self::MAX_HEIGHT).contains(&height),
+            "monitors can be at most {}x{} blocks",
+            Self::MAX_WIDTH,
+            Self::MAX_HEIGHT
+        );
+
+        self.size = (width, height);
+        self.update_terminal_size()
+    }
+
+    pub fn text_scale(&self) -> f64 {
+        f64::from(self.text_scale) / 2.0
+    }
+
+    /// Sets the text scale, which must be a multiple of 0.5 from 0.5 to 5.
+    /// Returns whether the terminal changed size.
+    pub fn set_text_scale(&mut self, scale: f64) -> bool {
+        self.text_scale = (scale * 2.0).clamp(1.0, 10.0) as u32;
+        self.update_terminal_size()
+    }
+
+    /// Returns the terminal the monitor displays.
+    pub fn terminal(&self) -> Ref<'_, Terminal> {
+        self.terminal.borrow()
+    }
+
+    pub(crate) fn shared_terminal(&self) -> Rc<RefCell<Terminal>> {
+        self.terminal.clone()
+    }
+
+    fn update_terminal_size(&mut self) -> bool {
+        let (width, height) = terminal_size(self.size, self.text_scale);
+        let mut terminal = self.terminal.borrow_mut();
+        if (terminal.width(), terminal.height()) == (width, height) {
+            return false;
+        }
+
+        terminal.resize(width, height);
+        true
+    }
+}
+
+/// Returns how many characters fit across and down a monitor of the given
+/// size, using the same sums as ComputerCraft.
+fn terminal_size((width, height): (u32, u32), text_scale: u32) -> (usize, usize) {
+    // The border around the screen, and the margin inside it.
+    const BORDER: f64 = 2.5 / 16.0;
+    // The size of a pixel of a character, at a text scale of 1.
+    const PIXEL: f64 = 1.0 / 64.0;
+
+    let scale = f64::from(text_scale) / 2.0;
+    let fit = |blocks: u32, pixels: f64| {
+        ((f64::from(blocks) - 2.0 * BORDER) / (scale * pixels * PIXEL))
+            .round()
+            .max(1.0) as usize
+    };
+
+    (fit(width, 6.0), fit(height, 9.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_terminal_size() {
+        let mut monitor = Monitor::new(1, 1, true);
+        assert_eq!(
+            (monitor.terminal().width(), monitor.terminal().height()),
+            (7, 5)
+        );
+
+        assert!(monitor.set_size(3, 2));
+        assert_eq!(
+            (monitor.terminal().width(), monitor.terminal().height()),
+            (29, 12)
+        );
+
+        assert!(monitor.set_text_scale(0.5));
+        assert_eq!(
+            (monitor.terminal().width(), monitor.terminal().height()),
+            (57, 24)
+        );
+        assert!(!monitor.set_text_scale(0.5));
+
+        assert!(monitor.set_size(8, 6));
+        assert_eq!(
+            (monitor.terminal().width(), monitor.terminal().height()),
+            (164, 81)
+        );
+    }
+}
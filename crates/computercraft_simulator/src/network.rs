@@ -0,0 +1,150 @@
+use std::collections::{HashMap, VecDeque};
+
+use minecraft::BlockId;
+use minecraft::world::{Direction, Position, World};
+
+/// The block of a networking cable, which connects wired modems.
+pub const CABLE: BlockId = BlockId::new_static("computercraft:cable");
+
+/// The block of a full-block wired modem, which connects the peripherals
+/// next to it to a wired network.
+pub const WIRED_MODEM_FULL: BlockId = BlockId::new_static("computercraft:wired_modem_full");
+
+/// Returns the position of the block on the given side of a computer at the
+/// position, facing the direction.
+pub fn side_position(position: Position, facing: Direction, side: &str) -> Option<Position> {
+    Some(match side {
+        "top" => position.up(),
+        "bottom" => position.down(),
+        "front" => position.forward(facing),
+        "back" => position.back(facing),
+        "left" => position.forward(facing.turn_left()),
+        "right" => position.forward(facing.turn_right()),
+        _ => return None,
+    })
+}
+
+/// Returns the positions of the six blocks next to the position.
+fn neighbours(position: Position) -> [Position; 6] {
+    let Position { x, y, z } = position;
+
+    [
+        Position::new(x + 1, y, z),
+        Position::new(x - 1, y, z),
+        position.up(),
+        position.down(),
+        Position::new(x, y, z + 1),
+        Position::new(x, y, z - 1),
+    ]
+}
+
+fn is_network_block(id: &BlockId) -> bool {
+    *id == CABLE || *id == WIRED_MODEM_FULL
+}
+
+/// The cables and wired modems connected to each other, like a wired network
+/// in ComputerCraft.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WiredNetwork {
+    /// The distance of each block in the network from the one the network
+    /// was found from, following the cables.
+    distances: HashMap<Position, u32>,
+}
+
+impl WiredNetwork {
+    /// Finds the network the cable or wired modem at the position is part of,
+    /// if there is one.
+    pub fn find(world: &World, position: Position) -> Option<Self> {
+        if !is_network_block(&world.get_block(position).id) {
+            return None;
+        }
+
+        let mut distances = HashMap::from([(position, 0)]);
+        let mut queue = VecDeque::from([position]);
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[&position];
+            for neighbour in neighbours(position) {
+                if !distances.contains_key(&neighbour)
+                    && is_network_block(&world.get_block(neighbour).id)
+                {
+                    distances.insert(neighbour, distance + 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        Some(Self { distances })
+    }
+
+    /// Returns the distance to the cable or wired modem at the position, if it
+    /// is part of the network.
+    pub fn distance_to(&self, position: Position) -> Option<u32> {
+        self.distances.get(&position).copied()
+    }
+
+    /// Returns the distance to a peripheral at the position, if it is next to
+    /// one of the network's full-block wired modems.
+    pub fn peripheral_distance(&self, world: &World, position: Position) -> Option<u32> {
+        neighbours(position)
+            .into_iter()
+            .filter(|&neighbour| world.get_block(neighbour).id == WIRED_MODEM_FULL)
+            .filter_map(|neighbour| self.distance_to(neighbour))
+            .min()
+            .map(|distance| distance + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::Block;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_find_network() {
+        let mut world = World::new();
+        for z in 0..3 {
+            world.set_block(Position::new(0, 0, z), Block { id: CABLE });
+        }
+        world.set_block(
+            Position::new(0, 0, 3),
+            Block {
+                id: WIRED_MODEM_FULL,
+            },
+        );
+        world.set_block(Position::new(0, 0, 5), Block { id: CABLE });
+
+        let network = WiredNetwork::find(&world, Position::new(0, 0, 0)).unwrap();
+        assert_eq!(network.distance_to(Position::new(0, 0, 3)), Some(3));
+        assert_eq!(network.distance_to(Position::new(0, 0, 5)), None);
+        assert_eq!(
+            network.peripheral_distance(&world, Position::new(1, 0, 3)),
+            Some(4)
+        );
+        assert_eq!(
+            network.peripheral_distance(&world, Position::new(1, 0, 2)),
+            None
+        );
+
+        assert_eq!(WiredNetwork::find(&world, Position::new(0, 0, 4)), None);
+    }
+
+    #[test]
+    fn test_side_position() {
+        let position = Position::new(0, 0, 0);
+        assert_eq!(
+            side_position(position, Direction::North, "left"),
+            Some(Position::new(-1, 0, 0))
+        );
+        assert_eq!(
+            side_position(position, Direction::East, "back"),
+            Some(Position::new(-1, 0, 0))
+        );
+        assert_eq!(
+            side_position(position, Direction::North, "top"),
+            Some(Position::new(0, 1, 0))
+        );
+        assert_eq!(side_position(position, Direction::North, "middle"), None);
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeSet;
+
+use minecraft::ItemStack;
+
+use crate::{DiskDrive, Inventory, Monitor, Printer, Speaker};
+
+/// The sides of a computer peripherals can be attached to, in the order
+/// ComputerCraft lists them.
+pub const SIDES: [&str; 6] = ["bottom", "top", "back", "front", "right", "left"];
+
+/// A peripheral attached to a computer, or placed in the world.
+#[derive(Debug)]
+pub enum Peripheral {
+    Modem(Modem),
+    Inventory(Inventory),
+    Monitor(Monitor),
+    DiskDrive(DiskDrive),
+    Printer(Printer),
+    Speaker(Speaker),
+}
+
+impl Peripheral {
+    /// Returns the types of the peripheral, as returned by
+    /// `peripheral.getType`. The first is its main type.
+    pub fn types(&self) -> Vec<&str> {
+        match self {
+            Self::Modem(modem) if modem.kind == ModemKind::Wired => vec!["modem", "peripheral_hub"],
+            Self::Modem(_) => vec!["modem"],
+            Self::Inventory(inventory) => vec![inventory.block.as_str(), "inventory"],
+            Self::Monitor(_) => vec!["monitor"],
+            Self::DiskDrive(_) => vec!["drive"],
+            Self::Printer(_) => vec!["printer", "inventory"],
+            Self::Speaker(_) => vec!["speaker"],
+        }
+    }
+
+    /// Returns whether the peripheral has the type.
+    pub fn has_type(&self, kind: &str) -> bool {
+        self.types().contains(&kind)
+    }
+
+    /// Returns the items the peripheral holds, if it is an inventory.
+    pub fn inventory_mut(&mut self) -> Option<&mut Inventory> {
+        match self {
+            Self::Inventory(inventory) => Some(inventory),
+            Self::Printer(printer) => Some(printer.inventory_mut()),
+            Self::Modem(_) | Self::Monitor(_) | Self::DiskDrive(_) | Self::Speaker(_) => None,
+        }
+    }
+
+    /// Resets the state the computer set up on the peripheral, for when the
+    /// computer turns off.
+    pub fn reset(&mut self) {
+        match self {
+            Self::Modem(modem) => modem.close_all(),
+            Self::Speaker(speaker) => speaker.stop(),
+            Self::Inventory(_) | Self::Monitor(_) | Self::DiskDrive(_) | Self::Printer(_) => {}
+        }
+    }
+
+    /// Puts the items into the peripheral, if it holds items, returning the
+    /// ones which didn't fit.
+    pub fn insert_items(&mut self, stack: ItemStack) -> Option<ItemStack> {
+        match self {
+            Self::Inventory(inventory) => inventory.insert(stack, None),
+            Self::DiskDrive(drive) => drive.insert(stack),
+            Self::Printer(printer) => printer.insert(stack),
+            Self::Modem(_) | Self::Monitor(_) | Self::Speaker(_) => Some(stack),
+        }
+    }
+
+    /// Takes up to `limit` of the first items out of the peripheral, if it
+    /// holds items.
+    pub fn take_items(&mut self, limit: u32) -> Option<ItemStack> {
+        match self {
+            Self::Inventory(inventory) => {
+                let (slot, _) = inventory.items().next()?;
+                inventory.take(slot, limit)
+            }
+            Self::DiskDrive(drive) if limit > 0 => drive.eject(),
+            Self::Printer(printer) => printer.take(limit),
+            Self::DiskDrive(_) | Self::Modem(_) | Self::Monitor(_) | Self::Speaker(_) => None,
+        }
+    }
+
+    /// Returns the names of the peripheral's methods, in sorted order.
+    pub fn methods(&self) -> &'static [&'static str] {
+        match self {
+            Self::Modem(modem) if modem.kind == ModemKind::Wired => &[
+                "callRemote",
+                "close",
+                "closeAll",
+                "getMethodsRemote",
+                "getNameLocal",
+                "getNamesRemote",
+                "getTypeRemote",
+                "hasTypeRemote",
+                "isOpen",
+                "isPresentRemote",
+                "isWireless",
+                "open",
+                "transmit",
+            ],
+            Self::Modem(_) => &[
+                "close",
+                "closeAll",
+                "isOpen",
+                "isWireless",
+                "open",
+                "transmit",
+            ],
+            Self::Inventory(_) => &[
+                "getItemDetail",
+                "getItemLimit",
+                "list",
+                "pullItems",
+                "pushItems",
+                "size",
+            ],
+            Self::Monitor(_) => &[
+                "blit",
+                "clear",
+                "clearLine",
+                "getBackgroundColor",
+                "getBackgroundColour",
+                "getCursorBlink",
+                "getCursorPos",
+                "getPaletteColor",
+                "getPaletteColour",
+                "getSize",
+                "getTextColor",
+                "getTextColour",
+                "getTextScale",
+                "isColor",
+                "isColour",
+                "scroll",
+                "setBackgroundColor",
+                "setBackgroundColour",
+                "setCursorBlink",
+                "setCursorPos",
+                "setPaletteColor",
+                "setPaletteColour",
+                "setTextColor",
+                "setTextColour",
+                "setTextScale",
+                "write",
+            ],
+            Self::DiskDrive(_) => &[
+                "ejectDisk",
+                "getAudioTitle",
+                "getDiskID",
+                "getDiskLabel",
+                "getMountPath",
+                "hasAudio",
+                "hasData",
+                "isDiskPresent",
+                "playAudio",
+                "setDiskLabel",
+                "stopAudio",
+            ],
+            // Printers are inventories too, so their pages can be inspected.
+            Self::Printer(_) => &[
+                "endPage",
+                "getCursorPos",
+                "getInkLevel",
+                "getItemDetail",
+                "getItemLimit",
+                "getPageSize",
+                "getPaperLevel",
+                "list",
+                "newPage",
+                "pullItems",
+                "pushItems",
+                "setCursorPos",
+                "setPageTitle",
+                "size",
+                "write",
+            ],
+            Self::Speaker(_) => &["playAudio", "playNote", "playSound", "stop"],
+        }
+    }
+}
+
+/// The kind of a modem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModemKind {
+    /// A wireless modem, whose range grows with its height.
+    Wireless,
+    /// An ender modem, which has unlimited range.
+    Ender,
+    /// A wired modem, which reaches the computers and peripherals on its
+    /// network of cables instead.
+    Wired,
+}
+
+impl ModemKind {
+    /// Returns how far modems of this kind can reach at the given height,
+    /// like ComputerCraft's modems in clear weather.
+    pub fn range(self, y: i32) -> f64 {
+        const MAX_Y: f64 = 319.0;
+        const HIGH_ALTITUDE_Y: f64 = 96.0;
+
+        match self {
+            Self::Wired => 0.0,
+            Self::Ender => f64::INFINITY,
+            Self::Wireless if f64::from(y) > HIGH_ALTITUDE_Y => {
+                Modem::RANGE
+                    + (f64::from(y) - HIGH_ALTITUDE_Y) * (Modem::HIGH_ALTITUDE_RANGE - Modem::RANGE)
+                        / (MAX_Y - HIGH_ALTITUDE_Y)
+            }
+            Self::Wireless => Modem::RANGE,
+        }
+    }
+}
+
+/// A modem, which sends and receives messages on the channels it has open.
+#[derive(Debug)]
+pub struct Modem {
+    pub kind: ModemKind,
+    open_channels: BTreeSet<u16>,
+}
+
+impl Modem {
+    /// The most channels a modem can have open at once.
+    pub const MAX_OPEN_CHANNELS: usize = 128;
+
+    /// The range of wireless modems, in blocks, at or below Y=96.
+    pub const RANGE: f64 = 64.0;
+
+    /// The range of wireless modems, in blocks, at the top of the world.
+    pub const HIGH_ALTITUDE_RANGE: f64 = 384.0;
+
+    pub fn new(kind: ModemKind) -> Self {
+        Self {
+            kind,
+            open_channels: BTreeSet::new(),
+        }
+    }
+
+    pub fn is_open(&self, channel: u16) -> bool {
+        self.open_channels.contains(&channel)
+    }
+
+    /// Opens the channel, returning `false` if too many channels are open.
+    pub fn open(&mut self, channel: u16) -> bool {
+        if !self.is_open(channel) && self.open_channels.len() >= Self::MAX_OPEN_CHANNELS {
+            return false;
+        }
+
+        self.open_channels.insert(channel);
+        true
+    }
+
+    pub fn close(&mut self, channel: u16) {
+        self.open_channels.remove(&channel);
+    }
+
+    pub fn close_all(&mut self) {
+        self.open_channels.clear();
+    }
+
+    /// Returns how far the modem can reach at the given height.
+    pub fn range(&self, y: i32) -> f64 {
+        self.kind.range(y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_modem_range() {
+        let modem = Modem::new(ModemKind::Wireless);
+        assert_eq!(modem.range(64), 64.0);
+        assert_eq!(modem.range(96), 64.0);
+        assert_eq!(modem.range(319), 384.0);
+
+        assert_eq!(Modem::new(ModemKind::Ender).range(0), f64::INFINITY);
+    }
+}
@@ -0,0 +1,306 @@
+use minecraft::{BlockId, ItemId, ItemStack, Nbt, NbtValue};
+
+use crate::Inventory;
+
+/// The item of a sheet of paper.
+pub const PAPER: ItemId = ItemId::new_static("minecraft:paper");
+
+/// The item of a page printed by a printer.
+pub const PRINTED_PAGE: ItemId = ItemId::new_static("computercraft:printed_page");
+
+/// Returns the title of the printed page, if the item is one.
+pub fn page_title(stack: &ItemStack) -> Option<&str> {
+    if stack.name != PRINTED_PAGE {
+        return None;
+    }
+
+    stack.nbt.as_ref()?.get("Title")?.as_str()
+}
+
+/// Returns the lines of text on the printed page, if the item is one.
+pub fn page_lines(stack: &ItemStack) -> Option<Vec<&str>> {
+    if stack.name != PRINTED_PAGE {
+        return None;
+    }
+
+    stack
+        .nbt
+        .as_ref()?
+        .get("Text")?
+        .as_list()?
+        .iter()
+        .map(NbtValue::as_str)
+        .collect()
+}
+
+/// Returns whether the item can be used as ink, which any dye can.
+fn is_ink(stack: &ItemStack) -> bool {
+    stack.name.as_str().ends_with("_dye")
+}
+
+/// A page being printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Page {
+    title: String,
+    lines: Vec<Vec<char>>,
+    cursor_x: i32,
+    cursor_y: i32,
+}
+
+impl Page {
+    fn new() -> Self {
+        Self {
+            title: String::new(),
+            lines: vec![vec![' '; Printer::PAGE_WIDTH]; Printer::PAGE_HEIGHT],
+            cursor_x: 0,
+            cursor_y: 0,
+        }
+    }
+
+    /// Turns the page into the item of a printed page.
+    fn into_item(self) -> ItemStack {
+        let text = self
+            .lines
+            .into_iter()
+            .map(|line| NbtValue::String(line.into_iter().collect()))
+            .collect();
+
+        let nbt = Nbt::from([
+            ("Title".to_string(), NbtValue::String(self.title)),
+            ("Pages".to_string(), NbtValue::Int(1)),
+            ("Text".to_string(), NbtValue::List(text)),
+        ]);
+
+        ItemStack::new(PRINTED_PAGE, 1).with_nbt(nbt)
+    }
+}
+
+/// A printer, which prints pages using the paper and ink in its inventory.
+///
+/// Like in ComputerCraft, the first slot holds the ink, the next six the
+/// paper, and the last six the printed pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Printer {
+    inventory: Inventory,
+    page: Option<Page>,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Printer {
+    /// The width of a page, in characters.
+    pub const PAGE_WIDTH: usize = 25;
+
+    /// The height of a page, in lines.
+    pub const PAGE_HEIGHT: usize = 21;
+
+    const INK_SLOT: usize = 0;
+    const PAPER_SLOTS: [usize; 6] = [1, 2, 3, 4, 5, 6];
+    const OUTPUT_SLOTS: [usize; 6] = [7, 8, 9, 10, 11, 12];
+
+    pub fn new() -> Self {
+        Self {
+            inventory: Inventory::new(BlockId::new_static("computercraft:printer"), 13),
+            page: None,
+        }
+    }
+
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    pub fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+
+    /// Returns how much ink is left, which is how many pages can be printed
+    /// with it.
+    pub fn ink_level(&self) -> u32 {
+        self.inventory
+            .slot(Self::INK_SLOT)
+            .filter(|stack| is_ink(stack))
+            .map_or(0, |stack| stack.count)
+    }
+
+    /// Returns how much paper is left.
+    pub fn paper_level(&self) -> u32 {
+        Self::PAPER_SLOTS
+            .into_iter()
+            .filter_map(|slot| self.inventory.slot(slot))
+            .filter(|stack| stack.name == PAPER)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    /// Returns whether a page is being printed.
+    pub fn is_printing(&self) -> bool {
+        self.page.is_some()
+    }
+
+    /// Starts a new page, using up a sheet of paper and some ink, after
+    /// finishing the current one. Returns `false` if the printer is out of
+    /// paper or ink, or there is no space for the finished page.
+    pub fn new_page(&mut self) -> bool {
+        if self.ink_level() == 0 || self.paper_level() == 0 {
+            return false;
+        }
+        if self.page.is_some() && !self.end_page() {
+            return false;
+        }
+
+        let paper_slot = Self::PAPER_SLOTS
+            .into_iter()
+            .find(|&slot| {
+                self.inventory
+                    .slot(slot)
+                    .is_some_and(|stack| stack.name == PAPER)
+            })
+            .expect("there should be paper");
+        self.inventory.take(paper_slot, 1);
+        self.inventory.take(Self::INK_SLOT, 1);
+        self.page = Some(Page::new());
+
+        true
+    }
+
+    /// Finishes the current page, moving it to the output slots. Returns
+    /// `false` if there is no page or no space for it.
+    pub fn end_page(&mut self) -> bool {
+        let Some(output_slot) = Self::OUTPUT_SLOTS
+            .into_iter()
+            .find(|&slot| self.inventory.slot(slot).is_none())
+        else {
+            return false;
+        };
+        let Some(page) = self.page.take() else {
+            return false;
+        };
+
+        self.inventory.set_slot(output_slot, Some(page.into_item()));
+        true
+    }
+
+    /// Writes the text at the cursor on the current page, cutting off what
+    /// doesn't fit, and moves the cursor past it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no page is being printed.
+    pub fn write(&mut self, text: &str) {
+        let page = self.page.as_mut().expect("a page should be started");
+
+        if let Ok(y) = usize::try_from(page.cursor_y)
+            && let Some(line) = page.lines.get_mut(y)
+        {
+            for (x, char) in (page.cursor_x..).zip(text.chars()) {
+                if let Ok(x) = usize::try_from(x)
+                    && let Some(cell) = line.get_mut(x)
+                {
+                    *cell = char;
+                }
+            }
+        }
+
+        page.cursor_x += text.chars().count() as i32;
+    }
+
+    /// Returns the position of the cursor on the current page, counting from
+    /// 0, if a page is being printed.
+    pub fn cursor_pos(&self) -> Option<(i32, i32)> {
+        let page = self.page.as_ref()?;
+        Some((page.cursor_x, page.cursor_y))
+    }
+
+    /// Moves the cursor on the current page, counting from 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no page is being printed.
+    pub fn set_cursor_pos(&mut self, x: i32, y: i32) {
+        let page = self.page.as_mut().expect("a page should be started");
+        page.cursor_x = x;
+        page.cursor_y = y;
+    }
+
+    /// Sets the title of the current page.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no page is being printed.
+    pub fn set_page_title(&mut self, title: &str) {
+        let page = self.page.as_mut().expect("a page should be started");
+        page.title = title.to_string();
+    }
+
+    /// Puts the ink or paper into the slots for it, returning the items which
+    /// didn't fit, or weren't either.
+    pub fn insert(&mut self, stack: ItemStack) -> Option<ItemStack> {
+        if is_ink(&stack) {
+            return self.inventory.insert(stack, Some(Self::INK_SLOT));
+        }
+        if stack.name != PAPER {
+            return Some(stack);
+        }
+
+        Self::PAPER_SLOTS
+            .into_iter()
+            .try_fold(stack, |stack, slot| {
+                self.inventory.insert(stack, Some(slot))
+            })
+    }
+
+    /// Takes up to `limit` of the first printed pages out of the printer.
+    pub fn take(&mut self, limit: u32) -> Option<ItemStack> {
+        let slot = Self::OUTPUT_SLOTS
+            .into_iter()
+            .find(|&slot| self.inventory.slot(slot).is_some())?;
+        self.inventory.take(slot, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_print_page() {
+        let mut printer = Printer::new();
+        assert!(!printer.new_page());
+
+        let ink = ItemStack::new(ItemId::new_static("minecraft:black_dye"), 2);
+        assert_eq!(printer.insert(ink), None);
+        assert_eq!(printer.insert(ItemStack::new(PAPER, 100)), None);
+        assert_eq!((printer.ink_level(), printer.paper_level()), (2, 100));
+
+        assert!(printer.new_page());
+        printer.set_page_title("Notes");
+        printer.write("Hello");
+        printer.set_cursor_pos(22, 1);
+        printer.write("world");
+        assert_eq!(printer.cursor_pos(), Some((27, 1)));
+
+        // Starting another page finishes this one.
+        assert!(printer.new_page());
+        assert!(printer.end_page());
+        assert!(!printer.end_page());
+        assert!(!printer.new_page());
+        assert_eq!((printer.ink_level(), printer.paper_level()), (0, 98));
+
+        let page = printer.take(64).unwrap();
+        assert_eq!(page_title(&page), Some("Notes"));
+        let lines = page_lines(&page).unwrap();
+        assert_eq!(lines.len(), Printer::PAGE_HEIGHT);
+        assert_eq!(lines[0].trim_end(), "Hello");
+        assert_eq!(lines[1].trim_start(), "wor");
+
+        let blank = printer.take(64).unwrap();
+        assert_eq!(page_title(&blank), Some(""));
+        assert_eq!(printer.take(64), None);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::SIDES;
+
+/// The redstone signals on each side of a computer, in the order of
+/// [`SIDES`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedstoneSignals {
+    /// The strength of the signal on each side, from 0 to 15.
+    pub analog: [u8; 6],
+    /// The colors of the bundled cable on each side which are on.
+    pub bundled: [u16; 6],
+}
+
+impl RedstoneSignals {
+    /// Returns the strength of the signal on the side, or `None` if it isn't
+    /// one of [`SIDES`].
+    pub fn analog(&self, side: &str) -> Option<u8> {
+        side_index(side).map(|ix| self.analog[ix])
+    }
+
+    /// Returns the colors of the bundled cable on the side which are on, or
+    /// `None` if it isn't one of [`SIDES`].
+    pub fn bundled(&self, side: &str) -> Option<u16> {
+        side_index(side).map(|ix| self.bundled[ix])
+    }
+}
+
+/// Returns the index of the side in [`SIDES`], if it is one.
+pub fn side_index(side: &str) -> Option<usize> {
+    SIDES.iter().position(|&other| other == side)
+}
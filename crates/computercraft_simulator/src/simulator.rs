@@ -1,24 +1,78 @@
-use std::cell::RefCell;
-use std::path::{Path, PathBuf};
-use std::rc::Rc;
+mod colors_api;
+mod disk_api;
+mod events;
+mod expect;
+mod fs_api;
+mod gps_api;
+mod http_api;
+mod interruption;
+mod inventory;
+mod io_api;
+mod keys_api;
+mod modem;
+mod monitor;
+mod native;
+mod os_api;
+mod peripheral_api;
+mod printer;
+mod rednet_api;
+mod redstone_api;
+mod require;
+mod settings_api;
+mod shell_api;
+mod speaker;
+mod term_api;
+mod textutils_api;
+mod tick;
+mod traceback;
+mod turtle_items;
+mod websocket;
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+use std::rc::{Rc, Weak};
 
-use minecraft::Block;
 use minecraft::world::{Direction, Position, World};
-use mlua::{Lua, LuaOptions, LuaSerdeExt, StdLib};
+use minecraft::{Block, ItemStack};
+use mlua::{
+    IntoLuaMulti, Lua, LuaOptions, LuaSerdeExt, MultiValue, RegistryKey, StdLib, Thread,
+    ThreadStatus, Value,
+};
 use serde::Serialize;
 use thiserror::Error;
 
+pub use self::interruption::Interruption;
+pub(crate) use self::native::{CreateNativeFunction, native_error_message};
+use self::shell_api::RUN_PROGRAM;
+pub use self::traceback::*;
+pub use self::websocket::{WebsocketConnection, WebsocketMessage, WebsocketServer};
+
 use crate::{
-    InspectData, Turtle, TurtleDigError, TurtleInspectError, TurtleKind, TurtleMoveError,
-    TurtlePlaceError, TurtleSide,
+    Computer, ComputerFamily, ComputerKind, DenyAll, Event, EventValue, FileSystem,
+    FileSystemError, GpsHost, HttpHandler, InspectData, Key, Mount, Peripheral, RedstoneSignals,
+    TERMINAL_HEIGHT, TERMINAL_WIDTH, Terminal, Turtle, TurtleDigError, TurtleInspectError,
+    TurtleMoveError, TurtlePlaceError, TurtleSide, sanitize_path, side_position,
 };
 
 #[derive(Error, Debug)]
 pub enum SimulatorError {
     #[error("Lua error: {0}")]
     LuaError(#[from] mlua::Error),
+    #[error("Lua runtime error: {0}")]
+    LuaRuntime(LuaRuntimeError),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Filesystem error: {0}")]
+    FileSystem(#[from] FileSystemError),
+    #[error("Program is waiting for an event, but the event queue is empty")]
+    EventQueueEmpty,
+    #[error("The computer was shut down with os.shutdown()")]
+    ShutDown,
+    #[error("The computer is rebooting after os.reboot()")]
+    Rebooting,
+    #[error("The program was interrupted, as if the turtle's chunk was unloaded")]
+    Interrupted,
 }
 
 pub type SimulatorResult<T, E = SimulatorError> = Result<T, E>;
@@ -29,146 +83,336 @@ pub struct Simulator {
 }
 
 impl Simulator {
+    /// Creates an advanced turtle, with ID 0, in a new world.
     pub fn new() -> SimulatorResult<Self> {
-        let lua = {
-            let stdlib = StdLib::ALL_SAFE | StdLib::DEBUG;
-            let options = LuaOptions::default();
+        Self::with_state(SimulatorState::default())
+    }
 
-            unsafe { Lua::unsafe_new_with(stdlib, options) }
-        };
+    /// Creates another computer in the same world as this one, with the next
+    /// free ID.
+    ///
+    /// The new computer has its own filesystem, Lua VM and terminal. Turtles
+    /// start at the origin, facing north.
+    pub fn new_computer(
+        &self,
+        kind: ComputerKind,
+        family: ComputerFamily,
+    ) -> SimulatorResult<Self> {
+        let id = self.state.shared.next_computer_id.get();
+        self.state.shared.next_computer_id.set(id + 1);
+
+        Self::with_state(SimulatorState::new(
+            Computer::new(id, kind, family),
+            self.state.shared.clone(),
+        ))
+    }
+
+    fn with_state(state: SimulatorState) -> SimulatorResult<Self> {
+        let state = Rc::new(state);
+        {
+            let mut computers = state.shared.computers.borrow_mut();
+            computers.retain(|computer| computer.strong_count() > 0);
+            computers.push(Rc::downgrade(&state));
+        }
+        state.redstone_inputs.set(state.read_redstone_inputs());
 
         let mut this = Self {
-            lua,
-            state: Rc::new(SimulatorState::new()),
+            lua: create_lua(),
+            state,
         };
-
-        this.init_require()?;
-        this.init_turtle_api()?;
+        this.init_apis()?;
 
         Ok(this)
     }
 
+    fn init_apis(&mut self) -> SimulatorResult<()> {
+        self.init_require()?;
+        self.init_colors_api()?;
+        self.init_term_api()?;
+        self.init_keys_api()?;
+        self.init_fs_api()?;
+        self.init_io_api()?;
+        self.init_textutils_api()?;
+        self.init_os_api()?;
+        self.init_peripheral_api()?;
+        self.init_rednet_api()?;
+        self.init_gps_api()?;
+        self.init_http_api()?;
+        self.init_redstone_api()?;
+        self.init_disk_api()?;
+        self.init_settings_api()?;
+        self.init_shell_api()?;
+        self.init_turtle_api()?;
+        self.init_bios()?;
+
+        Ok(())
+    }
+
+    /// Turns the computer on, running `startup.lua` and the programs in
+    /// `startup/` like ComputerCraft does.
+    ///
+    /// If a startup program reboots the computer, it is turned off and on
+    /// again, and the startup programs run again. This returns once they
+    /// finish, or the computer shuts down.
+    pub fn boot(&mut self) -> SimulatorResult<()> {
+        loop {
+            match self.run_startup() {
+                Err(SimulatorError::Rebooting) => self.power_off()?,
+                Err(SimulatorError::ShutDown) => return self.power_off(),
+                result => return result,
+            }
+        }
+    }
+
+    /// Turns the computer off and on again, like `os.reboot()`.
+    pub fn reboot(&mut self) -> SimulatorResult<()> {
+        self.power_off()?;
+        self.boot()
+    }
+
+    /// Throws away everything the computer had in memory: the Lua VM, the
+    /// event queue, the timers and the terminal's contents.
+    ///
+    /// The world, the turtle, the filesystem and the label are kept, like a computer in
+    /// Minecraft keeps them when it is turned off.
+    fn power_off(&mut self) -> SimulatorResult<()> {
+        self.lua = create_lua();
+        self.state.events.borrow_mut().clear();
+        self.state.timers.borrow_mut().clear();
+        self.state.speakers_playing.borrow_mut().clear();
+        self.state.close_websockets();
+        for peripheral in self.state.peripherals.borrow_mut().values_mut() {
+            peripheral.reset();
+        }
+        self.state.started_at.set(self.state.shared.ticks.get());
+        self.state.terminal.borrow_mut().reset();
+        self.state.power_request.set(None);
+        if self.state.redstone_outputs.take() != RedstoneSignals::default() {
+            self.state.shared.update_redstone();
+        }
+
+        self.init_apis()
+    }
+
     pub fn world(&self) -> std::cell::Ref<'_, World> {
-        self.state.world.borrow()
+        self.state.shared.world.borrow()
+    }
+
+    /// Returns the computer's identity.
+    pub fn computer(&self) -> std::cell::Ref<'_, Computer> {
+        self.state.computer.borrow()
     }
 
+    pub fn computer_mut(&self) -> std::cell::RefMut<'_, Computer> {
+        self.state.computer.borrow_mut()
+    }
+
+    /// Returns the turtle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the computer isn't a turtle.
     pub fn turtle(&self) -> std::cell::Ref<'_, Turtle> {
-        self.state.turtle.borrow()
+        self.state.turtle()
     }
 
+    /// Returns the turtle, mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the computer isn't a turtle.
     pub fn turtle_mut(&self) -> std::cell::RefMut<'_, Turtle> {
-        self.state.turtle.borrow_mut()
+        self.state.turtle_mut()
+    }
+
+    pub fn terminal(&self) -> std::cell::Ref<'_, Terminal> {
+        self.state.terminal.borrow()
     }
 
+    /// Returns the computer's filesystem.
+    pub fn filesystem(&self) -> std::cell::Ref<'_, FileSystem> {
+        self.state.filesystem.borrow()
+    }
+
+    pub fn filesystem_mut(&self) -> std::cell::RefMut<'_, FileSystem> {
+        self.state.filesystem.borrow_mut()
+    }
+
+    /// Resizes the terminal of the computer.
+    pub fn set_terminal_size(&self, width: usize, height: usize) {
+        self.state.terminal.borrow_mut().resize(width, height);
+    }
+
+    /// Returns everything that has been written with `write`, `print`, and `printError`.
+    pub fn output(&self) -> String {
+        self.state.output.borrow().clone()
+    }
+
+    /// Adds an event to the end of the computer's event queue.
+    pub fn queue_event(&self, event: Event) {
+        self.state.events.borrow_mut().push_back(event);
+    }
+
+    /// Queues a `key` event, as if the key was pressed.
+    pub fn queue_key(&self, key: Key, is_held: bool) {
+        self.queue_event(Event::new("key", vec![key.code().into(), is_held.into()]));
+    }
+
+    /// Queues a `key_up` event, as if the key was released.
+    pub fn queue_key_up(&self, key: Key) {
+        self.queue_event(Event::new("key_up", vec![key.code().into()]));
+    }
+
+    /// Queues a `char` event, as if the character was typed.
+    ///
+    /// Characters outside of ComputerCraft's character set are replaced with `?`.
+    pub fn queue_char(&self, char: char) {
+        let byte = u8::try_from(char).unwrap_or(b'?');
+
+        self.queue_event(Event::new("char", vec![EventValue::String(vec![byte])]));
+    }
+
+    /// Queues a `paste` event, as if the text was pasted from the clipboard.
+    pub fn queue_paste(&self, text: &str) {
+        self.queue_event(Event::new("paste", vec![text.into()]));
+    }
+
+    /// Queues the events for pressing and releasing the key.
+    pub fn press_key(&self, key: Key) {
+        self.queue_key(key, false);
+        self.queue_key_up(key);
+    }
+
+    /// Queues a `char` event for each character in the text.
+    pub fn type_text(&self, text: &str) {
+        for char in text.chars() {
+            self.queue_char(char);
+        }
+    }
+
+    /// Backs the computer's hard drive with the directory on the host, so
+    /// programs and modules can be loaded from it.
+    ///
+    /// Files written by programs are kept in memory rather than written to the host.
     pub fn set_current_dir(&mut self, root_dir: impl AsRef<Path>) {
-        *self.state.current_dir.borrow_mut() = root_dir.as_ref().to_path_buf();
+        self.state
+            .filesystem
+            .borrow_mut()
+            .mount_at_mut("")
+            .expect("the root mount should always exist")
+            .set_host_dir(root_dir.as_ref());
     }
 
     pub fn block_at(&self, position: Position) -> Block {
-        let world = self.state.world.borrow();
+        let world = self.state.shared.world.borrow();
         world.get_block(position)
     }
 
     /// Sets the block at the given position.
     pub fn set_block_at(&self, position: Position, block: Block) {
-        let mut world = self.state.world.borrow_mut();
+        let mut world = self.state.shared.world.borrow_mut();
         world.set_block(position, block);
     }
 
+    /// Returns the position of the computer, or of the turtle if it is one.
+    pub fn position(&self) -> Position {
+        self.state.position()
+    }
+
     /// Moves the turtle to the given position.
     pub fn move_turtle_to(&self, position: Position) {
-        let mut turtle = self.state.turtle.borrow_mut();
-        turtle.position = position;
+        self.state.turtle_mut().position = position;
+        self.state.shared.update_redstone();
     }
 
-    fn init_require(&mut self) -> SimulatorResult<()> {
-        let state = self.state.clone();
-        let globals = self.lua.globals();
+    /// Moves the computer to the given position.
+    pub fn move_computer_to(&self, position: Position) {
+        self.state.computer.borrow_mut().position = position;
+        self.state.shared.update_redstone();
+    }
 
-        globals.set(
-            "require",
-            self.lua.create_function(move |lua, module_name: String| {
-                let module_name_normalized = module_name.replace('.', "/");
-                let file_path = state
-                    .current_dir
-                    .borrow()
-                    .join(format!("{module_name_normalized}.lua"));
-                match std::fs::read_to_string(&file_path) {
-                    Ok(content) => {
-                        let result: mlua::Value<'_> = lua.load(content.as_bytes()).eval()?;
-                        Ok(result)
-                    }
-                    Err(err) => Err(mlua::Error::RuntimeError(format!(
-                        "Module '{module_name}' not found: {err}",
-                    ))),
-                }
-            })?,
-        )?;
+    fn init_bios(&mut self) -> SimulatorResult<()> {
+        let error_message = self.lua.create_native_function(|lua, err: Value| {
+            Ok(match err {
+                Value::Error(err) => Value::String(lua.create_string(native_error_message(&err))?),
+                err => err,
+            })
+        })?;
+
+        let is_interrupted = self.lua.create_native_function({
+            let state = self.state.clone();
+            move |_lua, ()| Ok(state.power_request.get() == Some(PowerRequest::Interrupt))
+        })?;
+
+        self.lua
+            .load(include_str!("lua/bios.lua"))
+            .set_name("@bios.lua")
+            .call::<_, ()>((error_message, is_interrupted, self.turtle_tick()?))?;
 
         Ok(())
     }
 
     fn init_turtle_api(&mut self) -> SimulatorResult<()> {
+        if self.state.computer.borrow().kind != ComputerKind::Turtle {
+            return Ok(());
+        }
+
         let globals = self.lua.globals();
 
         let turtle_table = self.lua.create_table()?;
 
         turtle_table.set(
             "forward",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, ()| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let world = state.world.borrow();
-
-                    Ok(turtle.forward(&world).to_lua_result())
+                    Ok(state
+                        .move_turtle(|turtle| turtle.position.forward(turtle.facing))
+                        .to_lua_result())
                 }
             })?,
         )?;
         turtle_table.set(
             "back",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, ()| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let world = state.world.borrow();
-
-                    Ok(turtle.back(&world).to_lua_result())
+                    Ok(state
+                        .move_turtle(|turtle| turtle.position.back(turtle.facing))
+                        .to_lua_result())
                 }
             })?,
         )?;
         turtle_table.set(
             "up",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, ()| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let world = state.world.borrow();
-
-                    Ok(turtle.up(&world).to_lua_result())
+                    Ok(state
+                        .move_turtle(|turtle| turtle.position.up())
+                        .to_lua_result())
                 }
             })?,
         )?;
         turtle_table.set(
             "down",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, ()| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let world = state.world.borrow();
-
-                    Ok(turtle.down(&world).to_lua_result())
+                    Ok(state
+                        .move_turtle(|turtle| turtle.position.down())
+                        .to_lua_result())
                 }
             })?,
         )?;
         turtle_table.set(
             "turnLeft",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, ()| {
-                    let mut turtle = state.turtle.borrow_mut();
-
-                    turtle.turn_left();
+                    state.turtle_mut().turn_left();
+                    // The computer's sides now face different blocks.
+                    state.shared.update_redstone();
 
                     Ok((true, None::<String>))
                 }
@@ -176,12 +420,12 @@ impl Simulator {
         )?;
         turtle_table.set(
             "turnRight",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, ()| {
-                    let mut turtle = state.turtle.borrow_mut();
-
-                    turtle.turn_right();
+                    state.turtle_mut().turn_right();
+                    // The computer's sides now face different blocks.
+                    state.shared.update_redstone();
 
                     Ok((true, None::<String>))
                 }
@@ -189,11 +433,11 @@ impl Simulator {
         )?;
         turtle_table.set(
             "dig",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, _side: Option<String>| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let mut world = state.world.borrow_mut();
+                    let mut turtle = state.turtle_mut();
+                    let mut world = state.shared.world.borrow_mut();
 
                     Ok(turtle
                         .dig_forward(TurtleSide::Right, &mut world)
@@ -203,11 +447,11 @@ impl Simulator {
         )?;
         turtle_table.set(
             "digUp",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, _side: Option<String>| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let mut world = state.world.borrow_mut();
+                    let mut turtle = state.turtle_mut();
+                    let mut world = state.shared.world.borrow_mut();
 
                     Ok(turtle.dig_up(TurtleSide::Right, &mut world).to_lua_result())
                 }
@@ -215,10 +459,10 @@ impl Simulator {
         )?;
         turtle_table.set(
             "select",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, slot: i32| {
-                    let mut turtle = state.turtle.borrow_mut();
+                    let mut turtle = state.turtle_mut();
 
                     Ok(turtle.select((slot - 1) as usize))
                 }
@@ -226,11 +470,11 @@ impl Simulator {
         )?;
         turtle_table.set(
             "digDown",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, _side: Option<String>| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let mut world = state.world.borrow_mut();
+                    let mut turtle = state.turtle_mut();
+                    let mut world = state.shared.world.borrow_mut();
 
                     Ok(turtle
                         .dig_down(TurtleSide::Right, &mut world)
@@ -240,11 +484,11 @@ impl Simulator {
         )?;
         turtle_table.set(
             "place",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, text: Option<String>| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let mut world = state.world.borrow_mut();
+                    let mut turtle = state.turtle_mut();
+                    let mut world = state.shared.world.borrow_mut();
 
                     Ok(turtle.place_forward(text, &mut world).to_lua_result())
                 }
@@ -252,11 +496,11 @@ impl Simulator {
         )?;
         turtle_table.set(
             "placeUp",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, text: Option<String>| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let mut world = state.world.borrow_mut();
+                    let mut turtle = state.turtle_mut();
+                    let mut world = state.shared.world.borrow_mut();
 
                     Ok(turtle.place_up(text, &mut world).to_lua_result())
                 }
@@ -264,11 +508,11 @@ impl Simulator {
         )?;
         turtle_table.set(
             "placeDown",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |_lua, text: Option<String>| {
-                    let mut turtle = state.turtle.borrow_mut();
-                    let mut world = state.world.borrow_mut();
+                    let mut turtle = state.turtle_mut();
+                    let mut world = state.shared.world.borrow_mut();
 
                     Ok(turtle.place_down(text, &mut world).to_lua_result())
                 }
@@ -276,11 +520,11 @@ impl Simulator {
         )?;
         turtle_table.set(
             "inspect",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |lua, ()| {
-                    let turtle = state.turtle.borrow();
-                    let world = state.world.borrow();
+                    let turtle = state.turtle();
+                    let world = state.shared.world.borrow();
 
                     let (has_block, data) = turtle.inspect_forward(&world).to_lua_result();
 
@@ -290,11 +534,11 @@ impl Simulator {
         )?;
         turtle_table.set(
             "inspectUp",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |lua, ()| {
-                    let turtle = state.turtle.borrow();
-                    let world = state.world.borrow();
+                    let turtle = state.turtle();
+                    let world = state.shared.world.borrow();
 
                     let (has_block, data) = turtle.inspect_up(&world).to_lua_result();
 
@@ -304,11 +548,11 @@ impl Simulator {
         )?;
         turtle_table.set(
             "inspectDown",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |lua, ()| {
-                    let turtle = state.turtle.borrow();
-                    let world = state.world.borrow();
+                    let turtle = state.turtle();
+                    let world = state.shared.world.borrow();
 
                     let (has_block, data) = turtle.inspect_down(&world).to_lua_result();
 
@@ -318,10 +562,10 @@ impl Simulator {
         )?;
         turtle_table.set(
             "getItemDetail",
-            self.lua.create_function({
+            self.lua.create_native_function({
                 let state = self.state.clone();
                 move |lua, (slot, detailed): (Option<i32>, bool)| {
-                    let turtle = state.turtle.borrow();
+                    let turtle = state.turtle();
 
                     let slot = slot
                         .map(|slot| (slot - 1) as usize)
@@ -334,40 +578,206 @@ impl Simulator {
             })?,
         )?;
 
+        self.init_turtle_drop_and_suck(&turtle_table)?;
+        self.wrap_turtle_actions(&turtle_table)?;
+        self.yield_after_turtle_actions(&turtle_table)?;
         globals.set("turtle", turtle_table)?;
 
         Ok(())
     }
 
-    fn read_lua_file(&self, path: impl AsRef<Path>) -> SimulatorResult<String> {
-        let path = self.state.current_dir.borrow().join(path);
-        let content = std::fs::read_to_string(path)?;
+    /// Reads the file from the computer's filesystem, returning its code and
+    /// the chunk name to load it with, so errors point to the file's path.
+    fn read_lua_file(&self, path: impl AsRef<Path>) -> SimulatorResult<(String, String)> {
+        let path = sanitize_path(&path.as_ref().to_string_lossy(), false);
+        let content = self.state.filesystem.borrow().read_file(&path)?;
+
+        Ok((
+            String::from_utf8_lossy(&content).into_owned(),
+            format!("@{path}"),
+        ))
+    }
+
+    /// Loads the code, trying it as an expression first like `mlua::Chunk::eval`.
+    fn load_expression(&self, code: &str, name: Option<&str>) -> mlua::Result<mlua::Function> {
+        let load = |code: &str| {
+            let chunk = self.lua.load(code.to_string());
+            match name {
+                Some(name) => chunk.set_name(name),
+                None => chunk,
+            }
+            .into_function()
+        };
 
-        Ok(content)
+        load(&format!("return {code}")).or_else(|_| load(code))
+    }
+
+    /// Runs the function as the computer's main coroutine, feeding it events
+    /// from the event queue whenever it yields, until it finishes.
+    fn run_function<'a, A, R>(&'a self, function: mlua::Function<'a>, args: A) -> SimulatorResult<R>
+    where
+        A: mlua::IntoLuaMulti<'a>,
+        R: mlua::FromLuaMulti<'a>,
+    {
+        let thread = self.lua.create_thread(function)?;
+
+        let mut resume_args = args.into_lua_multi(&self.lua)?;
+        loop {
+            match self.resume_until_tick(&thread, resume_args)? {
+                Resumed::Finished(values) => return Ok(R::from_lua_multi(values, &self.lua)?),
+                Resumed::Tick => {
+                    self.advance_tick();
+                    resume_args = MultiValue::new();
+                }
+                // Nothing else can happen while this computer waits, so skip
+                // ahead to its next timer.
+                Resumed::Waiting(filter) => {
+                    let event = loop {
+                        if !self.state.skip_to_next_timer() {
+                            return Err(SimulatorError::EventQueueEmpty);
+                        }
+
+                        if let Some(event) = self.poll_event(filter.as_deref())? {
+                            break event;
+                        }
+                    };
+
+                    resume_args = event.into_lua_multi(&self.lua)?;
+                }
+            }
+        }
+    }
+
+    /// Resumes the thread, handing it events as it asks for them, until it
+    /// finishes, a turtle action ends its turn for the tick, or it waits for
+    /// an event which hasn't happened yet.
+    fn resume_until_tick<'a>(
+        &'a self,
+        thread: &Thread<'a>,
+        mut resume_args: MultiValue<'a>,
+    ) -> SimulatorResult<Resumed<'a>> {
+        let tick = self.turtle_tick()?;
+
+        loop {
+            let values: MultiValue = thread.resume(resume_args).map_err(|err| {
+                if self.state.power_request.get() == Some(PowerRequest::Interrupt) {
+                    self.state.power_request.set(None);
+                    return SimulatorError::Interrupted;
+                }
+
+                match LuaRuntimeError::from_lua_error(&err) {
+                    Some(err) => SimulatorError::LuaRuntime(err),
+                    None => SimulatorError::LuaError(err),
+                }
+            })?;
+            if thread.status() != ThreadStatus::Resumable {
+                return Ok(Resumed::Finished(values));
+            }
+
+            match self.state.power_request.take() {
+                Some(PowerRequest::Shutdown) => return Err(SimulatorError::ShutDown),
+                Some(PowerRequest::Reboot) => return Err(SimulatorError::Rebooting),
+                Some(PowerRequest::Interrupt) => return Err(SimulatorError::Interrupted),
+                None => {}
+            }
+
+            let filter = match values.into_iter().next() {
+                Some(Value::Table(value)) if value.to_pointer() == tick.to_pointer() => {
+                    return Ok(Resumed::Tick);
+                }
+                Some(Value::String(filter)) => Some(filter.to_string_lossy().into_owned()),
+                _ => None,
+            };
+            let Some(event) = self.poll_event(filter.as_deref())? else {
+                return Ok(Resumed::Waiting(filter));
+            };
+
+            resume_args = event.into_lua_multi(&self.lua)?;
+        }
+    }
+
+    /// Runs a program started with [`Simulator::start_program`] until its next
+    /// turtle action completes, or it has to wait for an event.
+    pub(crate) fn resume_program(&self, program: &mut Program) -> SimulatorResult<ProgramStatus> {
+        let thread: Thread = self.lua.registry_value(&program.thread)?;
+
+        let resume_args = match program.waiting_for.take() {
+            Some(filter) => match self.poll_event(filter.as_deref())? {
+                Some(event) => event.into_lua_multi(&self.lua)?,
+                None => {
+                    program.waiting_for = Some(filter);
+                    return Ok(ProgramStatus::Waiting);
+                }
+            },
+            None => MultiValue::new(),
+        };
+
+        Ok(match self.resume_until_tick(&thread, resume_args)? {
+            Resumed::Finished(_) => ProgramStatus::Finished,
+            Resumed::Tick => ProgramStatus::Running,
+            Resumed::Waiting(filter) => {
+                program.waiting_for = Some(filter);
+                ProgramStatus::Waiting
+            }
+        })
+    }
+
+    /// Removes the next event matching the filter from the event queue.
+    ///
+    /// Like ComputerCraft, events that don't match the filter are discarded,
+    /// and `terminate` events always match. Every event is handed to rednet
+    /// first, even those which don't match.
+    fn poll_event(&self, filter: Option<&str>) -> SimulatorResult<Option<Event>> {
+        while let Some(event) = self.state.next_event() {
+            self.run_rednet(&event)?;
+            if filter.is_none_or(|filter| event.name == filter || event.name == "terminate") {
+                return Ok(Some(event));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns whether the computer has events or timers which could wake a
+    /// waiting program.
+    pub(crate) fn has_pending_events(&self) -> bool {
+        !self.state.events.borrow().is_empty()
+            || !self.state.timers.borrow().is_empty()
+            || !self.state.speakers_playing.borrow().is_empty()
+    }
+
+    /// Moves the world's time on by a tick.
+    pub(crate) fn advance_tick(&self) {
+        let ticks = &self.state.shared.ticks;
+        ticks.set(ticks.get() + 1);
     }
 
     pub fn exec_lua(&self, code: &str) -> SimulatorResult<()> {
-        Ok(self.lua.load(code).exec()?)
+        let function = self.lua.load(code).into_function()?;
+        self.run_function(function, ())
     }
 
     pub fn exec_lua_file(&self, path: impl AsRef<Path>) -> SimulatorResult<()> {
-        let code = self.read_lua_file(path)?;
-        self.exec_lua(&code)
+        let (code, name) = self.read_lua_file(path)?;
+        let function = self.lua.load(code).set_name(name).into_function()?;
+        self.run_function(function, ())
     }
 
     pub fn eval_lua<'a, R>(&'a self, code: &str) -> SimulatorResult<R>
     where
         R: mlua::FromLuaMulti<'a>,
     {
-        Ok(self.lua.load(code).eval()?)
+        let function = self.load_expression(code, None)?;
+        self.run_function(function, ())
     }
 
     pub fn eval_lua_file<'a, R>(&'a self, path: impl AsRef<Path>) -> SimulatorResult<R>
     where
         R: mlua::FromLuaMulti<'a>,
     {
-        let code = self.read_lua_file(path)?;
-        self.eval_lua(&code)
+        let (code, name) = self.read_lua_file(path)?;
+        let function = self.load_expression(&code, Some(&name))?;
+        self.run_function(function, ())
     }
 
     pub fn call_lua<'a, A, R>(&'a self, code: &str, args: A) -> SimulatorResult<R>
@@ -375,7 +785,8 @@ impl Simulator {
         A: mlua::IntoLuaMulti<'a>,
         R: mlua::FromLuaMulti<'a>,
     {
-        Ok(self.lua.load(code).call(args)?)
+        let function = self.lua.load(code).into_function()?;
+        self.run_function(function, args)
     }
 
     pub fn call_lua_file<'a, A, R>(&'a self, path: impl AsRef<Path>, args: A) -> SimulatorResult<R>
@@ -383,11 +794,36 @@ impl Simulator {
         A: mlua::IntoLuaMulti<'a>,
         R: mlua::FromLuaMulti<'a>,
     {
-        let code = self.read_lua_file(path)?;
-        self.call_lua(&code, args)
+        let (code, name) = self.read_lua_file(path)?;
+        let function = self.lua.load(code).set_name(name).into_function()?;
+        self.run_function(function, args)
+    }
+
+    /// Runs the program the way ComputerCraft's shell does: in a fresh
+    /// environment with its own `require`, and with the arguments passed both
+    /// as `...` and in the `arg` table.
+    ///
+    /// The program is resolved like `shell.resolveProgram`, so programs in
+    /// `programs/` can be run by name, and `arg[0]` is the path it resolved
+    /// to. Unlike the shell, which prints errors raised by the program, they
+    /// are returned.
+    pub fn run_program(&self, path: &str, args: &[&str]) -> SimulatorResult<()> {
+        let run_program: mlua::Function = self.lua.named_registry_value(RUN_PROGRAM)?;
+
+        let mut run_args = vec![path];
+        run_args.extend_from_slice(args);
+        self.run_function(run_program, mlua::Variadic::from_iter(run_args))
     }
 }
 
+fn create_lua() -> Lua {
+    // ComputerCraft's `io` library and `require` are used instead of Lua's.
+    let stdlib = (StdLib::ALL_SAFE ^ StdLib::IO ^ StdLib::PACKAGE) | StdLib::DEBUG;
+    let options = LuaOptions::default();
+
+    unsafe { Lua::unsafe_new_with(stdlib, options) }
+}
+
 pub trait TurtleResultExt<T> {
     fn to_lua_result(self) -> (bool, T);
 }
@@ -402,6 +838,7 @@ impl TurtleResultExt<Option<String>> for Result<(), TurtleMoveError> {
 }
 
 #[derive(Serialize)]
+#[serde(untagged)]
 enum InspectDataOrReason {
     Data(InspectData),
     Reason(String),
@@ -435,23 +872,299 @@ impl TurtleResultExt<Option<String>> for Result<(), TurtlePlaceError> {
 }
 
 pub struct SimulatorState {
-    current_dir: RefCell<PathBuf>,
+    computer: RefCell<Computer>,
+    shared: Rc<SharedWorld>,
+    /// The turtle, if the computer is one.
+    turtle: RefCell<Option<Turtle>>,
+    /// The peripherals attached to the computer, by side.
+    peripherals: RefCell<BTreeMap<String, Peripheral>>,
+    terminal: Rc<RefCell<Terminal>>,
+    filesystem: RefCell<FileSystem>,
+    output: RefCell<String>,
+    events: RefCell<VecDeque<Event>>,
+    /// The tick each pending timer is due, by ID.
+    timers: RefCell<BTreeMap<u32, u64>>,
+    next_timer_id: Cell<u32>,
+    /// The tick each speaker the computer is streaming audio to runs out of
+    /// it, by name.
+    speakers_playing: RefCell<BTreeMap<String, u64>>,
+    /// The tick the computer was turned on.
+    started_at: Cell<u64>,
+    power_request: Cell<Option<PowerRequest>>,
+    /// The number of turtle actions since an interruption was scheduled.
+    turtle_actions: Cell<u64>,
+    interrupt_after: Cell<Option<u64>>,
+    /// The redstone signals the computer sends out of each side.
+    redstone_outputs: Cell<RedstoneSignals>,
+    /// The redstone signals the computer last received, to tell when they
+    /// change.
+    redstone_inputs: Cell<RedstoneSignals>,
+}
+
+/// How far a thread got when it was resumed.
+enum Resumed<'a> {
+    Finished(MultiValue<'a>),
+    /// A turtle action completed, ending the computer's turn for the tick.
+    Tick,
+    /// The thread is waiting for an event matching the filter.
+    Waiting(Option<String>),
+}
+
+/// A program started with [`Simulator::start_program`].
+pub(crate) struct Program {
+    thread: RegistryKey,
+    /// The filter of the event the program is waiting for, if it is waiting.
+    waiting_for: Option<Option<String>>,
+}
+
+impl Program {
+    pub(crate) fn new(thread: RegistryKey) -> Self {
+        Self {
+            thread,
+            waiting_for: None,
+        }
+    }
+}
+
+/// What a program did when it was resumed with [`Simulator::resume_program`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProgramStatus {
+    /// The program carried out a turtle action.
+    Running,
+    /// The program is waiting for an event.
+    Waiting,
+    Finished,
+}
+
+/// The parts of the simulation shared by every computer in a world.
+struct SharedWorld {
     world: RefCell<World>,
-    turtle: RefCell<Turtle>,
+    /// The number of ticks since the world was created.
+    ticks: Rc<Cell<u64>>,
+    /// The ID given to the next computer created in the world.
+    next_computer_id: Cell<u32>,
+    computers: RefCell<Vec<Weak<SimulatorState>>>,
+    gps_hosts: RefCell<Vec<GpsHost>>,
+    /// The peripherals placed in the world, by name.
+    peripherals: RefCell<BTreeMap<String, PlacedPeripheral>>,
+    /// The number of peripherals of each type placed so far, to name them.
+    peripheral_counts: RefCell<HashMap<String, u32>>,
+    /// The files on each floppy disk which isn't mounted, by disk ID.
+    floppy_disks: RefCell<HashMap<u32, Mount>>,
+    /// The ID given to the next floppy disk.
+    next_disk_id: Cell<u32>,
+    /// The items lying on the ground, by the block they are in.
+    ground_items: RefCell<HashMap<Position, Vec<ItemStack>>>,
+    /// Serves the requests computers make with the `http` API.
+    http_handler: RefCell<Rc<dyn HttpHandler>>,
+    /// The websocket servers computers can connect to, by URL.
+    websocket_servers: RefCell<BTreeMap<String, WebsocketServer>>,
 }
 
-impl SimulatorState {
-    pub fn new() -> Self {
+/// A peripheral placed in the world, which computers can use over a wired
+/// network.
+struct PlacedPeripheral {
+    position: Position,
+    peripheral: Peripheral,
+}
+
+impl SharedWorld {
+    fn new() -> Self {
         Self {
-            current_dir: RefCell::new(PathBuf::new()),
             world: RefCell::new(World::new()),
-            turtle: RefCell::new(Turtle::new(
+            ticks: Rc::new(Cell::new(0)),
+            next_computer_id: Cell::new(1),
+            computers: RefCell::new(Vec::new()),
+            gps_hosts: RefCell::new(Vec::new()),
+            peripherals: RefCell::new(BTreeMap::new()),
+            peripheral_counts: RefCell::new(HashMap::new()),
+            floppy_disks: RefCell::new(HashMap::new()),
+            next_disk_id: Cell::new(0),
+            ground_items: RefCell::new(HashMap::new()),
+            http_handler: RefCell::new(Rc::new(DenyAll)),
+            websocket_servers: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns whether a turtle other than the given computer is at the
+    /// position, as turtles can't move through each other.
+    fn is_turtle_at(&self, position: Position, except: &SimulatorState) -> bool {
+        self.computers
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|state| !std::ptr::eq(Rc::as_ptr(state), except))
+            .any(|state| {
+                state
+                    .turtle
+                    .borrow()
+                    .as_ref()
+                    .is_some_and(|turtle| turtle.position == position)
+            })
+    }
+}
+
+/// A request to turn the computer off, made with `os.shutdown` or `os.reboot`,
+/// or by interrupting the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PowerRequest {
+    Shutdown,
+    Reboot,
+    Interrupt,
+}
+
+impl Default for SimulatorState {
+    /// Creates the state of an advanced turtle, with ID 0, in a new world.
+    fn default() -> Self {
+        Self::new(
+            Computer::new(0, ComputerKind::Turtle, ComputerFamily::Advanced),
+            Rc::new(SharedWorld::new()),
+        )
+    }
+}
+
+impl SimulatorState {
+    fn new(computer: Computer, shared: Rc<SharedWorld>) -> Self {
+        let started_at = shared.ticks.get();
+        let mut filesystem = FileSystem::new();
+        filesystem.set_clock(shared.ticks.clone());
+        filesystem
+            .mount_at_mut("rom")
+            .expect("the ROM should always be mounted")
+            .insert_file("programs/rednet/repeat.lua", include_str!("lua/repeat.lua"));
+        let turtle = (computer.kind == ComputerKind::Turtle).then(|| {
+            Turtle::new(
                 Position::new(0, 0, 0),
                 Direction::North,
-                TurtleKind::Advanced,
-            )),
+                computer.family.into(),
+            )
+        });
+        let terminal = Terminal::new(
+            TERMINAL_WIDTH,
+            TERMINAL_HEIGHT,
+            computer.family == ComputerFamily::Advanced,
+        );
+
+        Self {
+            computer: RefCell::new(computer),
+            shared,
+            turtle: RefCell::new(turtle),
+            peripherals: RefCell::new(BTreeMap::new()),
+            terminal: Rc::new(RefCell::new(terminal)),
+            filesystem: RefCell::new(filesystem),
+            output: RefCell::new(String::new()),
+            events: RefCell::new(VecDeque::new()),
+            timers: RefCell::new(BTreeMap::new()),
+            next_timer_id: Cell::new(0),
+            speakers_playing: RefCell::new(BTreeMap::new()),
+            started_at: Cell::new(started_at),
+            power_request: Cell::new(None),
+            turtle_actions: Cell::new(0),
+            interrupt_after: Cell::new(None),
+            redstone_outputs: Cell::new(RedstoneSignals::default()),
+            redstone_inputs: Cell::new(RedstoneSignals::default()),
+        }
+    }
+
+    fn turtle(&self) -> std::cell::Ref<'_, Turtle> {
+        std::cell::Ref::map(self.turtle.borrow(), |turtle| {
+            turtle.as_ref().expect("the computer is not a turtle")
+        })
+    }
+
+    fn turtle_mut(&self) -> std::cell::RefMut<'_, Turtle> {
+        std::cell::RefMut::map(self.turtle.borrow_mut(), |turtle| {
+            turtle.as_mut().expect("the computer is not a turtle")
+        })
+    }
+
+    /// Returns the position of the computer, or of the turtle if it is one.
+    fn position(&self) -> Position {
+        match &*self.turtle.borrow() {
+            Some(turtle) => turtle.position,
+            None => self.computer.borrow().position,
         }
     }
+
+    /// Returns the position of the block on the given side of the computer.
+    fn side_position(&self, side: &str) -> Option<Position> {
+        match &*self.turtle.borrow() {
+            Some(turtle) => side_position(turtle.position, turtle.facing, side),
+            None => {
+                let computer = self.computer.borrow();
+                side_position(computer.position, computer.facing, side)
+            }
+        }
+    }
+
+    /// Moves the turtle to the position picked by the function, unless
+    /// another turtle is already there.
+    fn move_turtle(&self, target: impl FnOnce(&Turtle) -> Position) -> Result<(), TurtleMoveError> {
+        let mut turtle = self.turtle_mut();
+        let target = target(&turtle);
+        if self.shared.is_turtle_at(target, self) {
+            return Err(TurtleMoveError::Obstructed);
+        }
+
+        turtle.move_to(target, &self.shared.world.borrow())?;
+        drop(turtle);
+        self.shared.update_redstone();
+
+        Ok(())
+    }
+
+    /// Queues the due timers' `timer` events, then removes the next event
+    /// from the event queue.
+    fn next_event(&self) -> Option<Event> {
+        let now = self.shared.ticks.get();
+        let mut timers = self.timers.borrow_mut();
+        let mut due: Vec<_> = timers
+            .iter()
+            .filter(|&(_, &at)| at <= now)
+            .map(|(&id, &at)| (at, id))
+            .collect();
+        due.sort();
+
+        let mut events = self.events.borrow_mut();
+        for (_, id) in due {
+            timers.remove(&id);
+            events.push_back(Event::new("timer", vec![id.into()]));
+        }
+
+        self.speakers_playing.borrow_mut().retain(|name, &mut at| {
+            if at > now {
+                return true;
+            }
+
+            events.push_back(Event::new(
+                "speaker_audio_empty",
+                vec![name.as_str().into()],
+            ));
+            false
+        });
+
+        events.pop_front()
+    }
+
+    /// Moves the world's time on to when the computer's next timer is due, or
+    /// a speaker runs out of audio, returning whether there is one.
+    fn skip_to_next_timer(&self) -> bool {
+        let timers = self.timers.borrow();
+        let speakers = self.speakers_playing.borrow();
+        let Some(&at) = timers.values().chain(speakers.values()).min() else {
+            return false;
+        };
+
+        self.shared.ticks.set(self.shared.ticks.get().max(at));
+        true
+    }
+
+    /// Records the text in the output transcript.
+    fn record_output(&self, text: &[u8]) {
+        self.output
+            .borrow_mut()
+            .push_str(&String::from_utf8_lossy(text));
+    }
 }
 
 #[cfg(test)]
@@ -460,51 +1173,60 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::{Color, Key};
 
     #[test]
     fn test_turtle_movement() {
         let simulator = Simulator::new().unwrap();
 
         simulator.exec_lua("turtle.forward()").unwrap();
-        assert_eq!(
-            simulator.state.turtle.borrow().position,
-            Position::new(0, 0, -1)
-        );
+        assert_eq!(simulator.turtle().position, Position::new(0, 0, -1));
 
         simulator.exec_lua("turtle.back()").unwrap();
-        assert_eq!(
-            simulator.state.turtle.borrow().position,
-            Position::new(0, 0, 0)
-        );
+        assert_eq!(simulator.turtle().position, Position::new(0, 0, 0));
 
         simulator.exec_lua("turtle.up()").unwrap();
-        assert_eq!(
-            simulator.state.turtle.borrow().position,
-            Position::new(0, 1, 0)
-        );
+        assert_eq!(simulator.turtle().position, Position::new(0, 1, 0));
 
         simulator.exec_lua("turtle.down()").unwrap();
-        assert_eq!(
-            simulator.state.turtle.borrow().position,
-            Position::new(0, 0, 0)
-        );
+        assert_eq!(simulator.turtle().position, Position::new(0, 0, 0));
 
         simulator.exec_lua("turtle.turnRight()").unwrap();
-        assert_eq!(simulator.state.turtle.borrow().facing, Direction::East);
+        assert_eq!(simulator.turtle().facing, Direction::East);
 
         simulator.exec_lua("turtle.forward()").unwrap();
-        assert_eq!(
-            simulator.state.turtle.borrow().position,
-            Position::new(1, 0, 0)
-        );
+        assert_eq!(simulator.turtle().position, Position::new(1, 0, 0));
 
         simulator.exec_lua("turtle.turnLeft()").unwrap();
-        assert_eq!(simulator.state.turtle.borrow().facing, Direction::North);
+        assert_eq!(simulator.turtle().facing, Direction::North);
 
         simulator.exec_lua("turtle.forward()").unwrap();
+        assert_eq!(simulator.turtle().position, Position::new(1, 0, -1));
+    }
+
+    #[test]
+    fn test_turtle_inspect() {
+        let simulator = Simulator::new().unwrap();
+        simulator.set_block_at(Position::new(0, 0, -1), blocks::STONE.clone());
+
+        let result: (bool, String, bool, String) = simulator
+            .eval_lua(
+                r#"
+                local has_block, data = turtle.inspect()
+                local has_block_up, reason = turtle.inspectUp()
+                return has_block, data.name, has_block_up, reason
+                "#,
+            )
+            .unwrap();
+
         assert_eq!(
-            simulator.state.turtle.borrow().position,
-            Position::new(1, 0, -1)
+            result,
+            (
+                true,
+                "minecraft:stone".to_string(),
+                false,
+                "No block to inspect".to_string()
+            )
         );
     }
 
@@ -532,4 +1254,411 @@ mod tests {
         let result: (bool, Option<String>) = simulator.eval_lua("turtle.dig()").unwrap();
         assert_eq!(result, (true, None));
     }
+
+    #[test]
+    fn test_turtle_drop_and_suck_ground_items() {
+        let simulator = Simulator::new().unwrap();
+        simulator.turtle_mut().inventory[0] = Some(ItemStack::new(
+            ItemId::new_static("minecraft:cobblestone"),
+            10,
+        ));
+
+        simulator
+            .exec_lua(
+                r#"
+                print(turtle.drop(4))
+                print(turtle.suck(1))
+                print(turtle.suck())
+                print(turtle.suck())
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            "true\tnil\ntrue\tnil\ntrue\tnil\nfalse\tNo items to take\n"
+        );
+        assert_eq!(simulator.turtle().inventory[0].as_ref().unwrap().count, 10);
+        assert!(simulator.items_at(Position::new(0, 0, -1)).is_empty());
+    }
+
+    #[test]
+    fn test_print_output() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                print("Hello", 42, nil)
+                write("No newline")
+                printError("Out of torches")
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            simulator.output(),
+            "Hello\t42\tnil\nNo newlineOut of torches\n"
+        );
+        assert_eq!(
+            simulator.terminal().line(0).unwrap().trim_end(),
+            "Hello\t42\tnil"
+        );
+        assert_eq!(
+            simulator.terminal().line(1).unwrap().trim_end(),
+            "No newlineOut of torches"
+        );
+
+        let result: Result<(), _> = simulator.exec_lua("write({})");
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("bad argument #1 (string expected, got table)")
+        );
+    }
+
+    #[test]
+    fn test_print_wraps_and_scrolls() {
+        let simulator = Simulator::new().unwrap();
+        simulator.set_terminal_size(10, 3);
+
+        let lines: usize = simulator
+            .eval_lua(r#"return print("Hello there abcdefghijklmno")"#)
+            .unwrap();
+        assert_eq!(lines, 4);
+        assert_eq!(simulator.terminal().contents(), "efghijklmn\no\n\n");
+    }
+
+    #[test]
+    fn test_print_to_terminal_with_no_width() {
+        let simulator = Simulator::new().unwrap();
+        simulator.set_terminal_size(0, 3);
+
+        let lines: usize = simulator.eval_lua(r#"return print("Hello")"#).unwrap();
+        assert_eq!(lines, 0);
+        assert_eq!(simulator.output(), "Hello\n");
+    }
+
+    #[test]
+    fn test_term_colors() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                term.setBackgroundColor(colors.blue)
+                term.clear()
+                term.setCursorPos(2, 1)
+                term.setTextColour(colours.red)
+                term.write("Hi")
+                term.blit("ok", "0f", "e1")
+                printError("Oops")
+                "#,
+            )
+            .unwrap();
+
+        let terminal = simulator.terminal();
+        assert!(terminal.is_color());
+        assert_eq!(terminal.text_color(), Color::Red);
+        assert_eq!(
+            terminal
+                .blit_line(0)
+                .map(|(text, fg, bg)| (&text[..5], &fg[..5], &bg[..5])),
+            Some((&b" Hiok"[..], &b"0ee0f"[..], &b"bbbe1"[..]))
+        );
+        assert_eq!(
+            terminal.cell(5, 0).map(|cell| (cell.char, cell.text_color)),
+            Some((b'O', Color::Red))
+        );
+
+        let result: Result<(), _> = simulator.exec_lua("term.setTextColor(0)");
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Colour out of range")
+        );
+    }
+
+    #[test]
+    fn test_term_redirect() {
+        let simulator = Simulator::new().unwrap();
+
+        let written: Vec<String> = simulator
+            .eval_lua(
+                r#"
+                local written = {}
+                local target = {}
+                for name, fn in pairs(term.native()) do
+                    target[name] = fn
+                end
+                target.write = function(text)
+                    table.insert(written, text)
+                end
+
+                local previous = term.redirect(target)
+                assert(previous == term.native())
+                assert(term.current() == target)
+                print("Hello world")
+                term.redirect(previous)
+
+                return written
+                "#,
+            )
+            .unwrap();
+        assert_eq!(written, vec!["Hello", " ", "world"]);
+        assert_eq!(simulator.terminal().contents().trim(), "");
+    }
+
+    #[test]
+    fn test_term_redirect_missing_methods() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                local previous = term.redirect({ write = function() end })
+                local has_stub = term.current().getSize ~= nil
+                local ok, err = pcall(term.getSize)
+                term.redirect(previous)
+                print(has_stub, ok, err)
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            "true\tfalse\tRedirect object is missing method getSize.\n"
+        );
+    }
+
+    #[test]
+    fn test_pull_key_events() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator.queue_char('x');
+        simulator.press_key(Key::ENTER);
+
+        let result: (String, u32, bool) = simulator
+            .eval_lua(
+                r#"
+                local event, key, is_held = os.pullEvent("key")
+                return event, key, is_held
+                "#,
+            )
+            .unwrap();
+        assert_eq!(result, ("key".to_string(), Key::ENTER.code(), false));
+
+        let result: (String, String) = simulator
+            .eval_lua(r#"return os.pullEvent(), keys.getName(keys.enter)"#)
+            .unwrap();
+        assert_eq!(result, ("key_up".to_string(), "enter".to_string()));
+
+        let result: Result<(), _> = simulator.exec_lua("os.pullEvent()");
+        assert!(matches!(result, Err(SimulatorError::EventQueueEmpty)));
+    }
+
+    #[test]
+    fn test_queue_event_from_lua() {
+        let simulator = Simulator::new().unwrap();
+
+        let result: (String, i64, String) = simulator
+            .eval_lua(
+                r#"
+                os.queueEvent("custom", { count = 3 }, "hello")
+                local _, data, text = os.pullEvent("custom")
+                return "custom", data.count, text
+                "#,
+            )
+            .unwrap();
+        assert_eq!(result, ("custom".to_string(), 3, "hello".to_string()));
+    }
+
+    #[test]
+    fn test_read() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator.type_text("helo");
+        simulator.press_key(Key::LEFT);
+        simulator.type_text("l");
+        simulator.press_key(Key::END);
+        simulator.queue_paste(" world");
+        simulator.press_key(Key::ENTER);
+
+        let line: String = simulator
+            .eval_lua(
+                r#"
+                write("> ")
+                return read()
+                "#,
+            )
+            .unwrap();
+        assert_eq!(line, "hello world");
+        assert_eq!(
+            simulator.terminal().line(0).unwrap().trim_end(),
+            "> hello world"
+        );
+        assert_eq!(simulator.terminal().cursor_pos(), (0, 1));
+    }
+
+    #[test]
+    fn test_read_history_and_completion() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator.press_key(Key::UP);
+        simulator.press_key(Key::UP);
+        simulator.press_key(Key::ENTER);
+        simulator.type_text("ba");
+        simulator.press_key(Key::TAB);
+        simulator.press_key(Key::ENTER);
+        simulator.type_text("secret");
+        simulator.press_key(Key::ENTER);
+
+        let lines: (String, String, String) = simulator
+            .eval_lua(
+                r#"
+                local history = { "first", "second" }
+                local function complete(text)
+                    local choices = { "banana", "apple" }
+                    local results = {}
+                    for _, choice in ipairs(choices) do
+                        if #text > 0 and choice:sub(1, #text) == text then
+                            table.insert(results, choice:sub(#text + 1))
+                        end
+                    end
+                    return results
+                end
+
+                return read(nil, history), read(nil, nil, complete), read("*")
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            lines,
+            (
+                "first".to_string(),
+                "banana".to_string(),
+                "secret".to_string()
+            )
+        );
+        assert_eq!(simulator.terminal().line(2).unwrap().trim_end(), "******");
+    }
+
+    #[test]
+    fn test_run_program() {
+        let simulator = Simulator::new().unwrap();
+        simulator
+            .filesystem_mut()
+            .write_file(
+                "programs/echo.lua",
+                r#"
+                    leaked = true
+                    print(select('#', ...), ..., arg[0], arg[2], shell.getRunningProgram())
+                "#,
+            )
+            .unwrap();
+
+        simulator
+            .run_program("/programs/echo.lua", &["one", "two"])
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            "2\tone\tprograms/echo.lua\ttwo\tprograms/echo.lua\n"
+        );
+        assert!(!simulator.eval_lua::<bool>("leaked == true").unwrap());
+    }
+
+    #[test]
+    fn test_boot_runs_startup_programs() {
+        let mut simulator = Simulator::new().unwrap();
+        {
+            let mut filesystem = simulator.filesystem_mut();
+            filesystem
+                .write_file("startup.lua", "print('startup.lua')")
+                .unwrap();
+            filesystem
+                .write_file("startup/2_second.lua", "print('second')")
+                .unwrap();
+            filesystem
+                .write_file("startup/1_first.lua", "print('first')")
+                .unwrap();
+        }
+
+        simulator.boot().unwrap();
+
+        assert_eq!(simulator.output(), "startup.lua\nfirst\nsecond\n");
+    }
+
+    #[test]
+    fn test_reboot_keeps_world_but_resets_lua() {
+        let mut simulator = Simulator::new().unwrap();
+        simulator
+            .filesystem_mut()
+            .write_file(
+                "startup.lua",
+                r#"
+                    boots = (boots or 0) + 1
+                    local file = fs.open("progress.txt", "r")
+                    local progress = file and tonumber(file.readAll()) or 0
+                    if file then file.close() end
+
+                    turtle.forward()
+                    print("Progress " .. progress .. ", boots " .. boots)
+
+                    file = fs.open("progress.txt", "w")
+                    file.write(tostring(progress + 1))
+                    file.close()
+
+                    if progress < 2 then
+                        os.reboot()
+                    end
+                    os.shutdown()
+                    print("unreachable")
+                "#,
+            )
+            .unwrap();
+
+        simulator.boot().unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            "Progress 0, boots 1\nProgress 1, boots 1\nProgress 2, boots 1\n"
+        );
+        assert_eq!(simulator.turtle().position, Position::new(0, 0, -3));
+        assert_eq!(simulator.terminal().line(0).unwrap().trim_end(), "");
+
+        let result = simulator.exec_lua("os.reboot()");
+        assert!(matches!(result, Err(SimulatorError::Rebooting)));
+    }
+
+    #[test]
+    fn test_computers_share_world() {
+        let turtle = Simulator::new().unwrap();
+        let computer = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        let pocket = computer
+            .new_computer(ComputerKind::Pocket, ComputerFamily::Advanced)
+            .unwrap();
+
+        turtle.exec_lua(r#"os.setComputerLabel("Miner")"#).unwrap();
+        turtle.filesystem_mut().write_file("notes.txt", "").unwrap();
+        computer
+            .exec_lua(
+                r#"print(os.getComputerID(), os.getComputerLabel(), turtle, term.isColour(), fs.exists("notes.txt"))"#,
+            )
+            .unwrap();
+
+        assert_eq!(computer.output(), "1\tnil\tnil\tfalse\tfalse\n");
+        assert_eq!(pocket.eval_lua::<u32>("os.computerID()").unwrap(), 2);
+        assert_eq!(
+            turtle.eval_lua::<String>("os.computerLabel()").unwrap(),
+            "Miner"
+        );
+        assert_eq!(turtle.computer().label.as_deref(), Some("Miner"));
+
+        computer.set_block_at(Position::new(0, 0, -1), blocks::STONE.clone());
+        assert!(turtle.eval_lua::<bool>("turtle.inspect()").unwrap());
+    }
 }
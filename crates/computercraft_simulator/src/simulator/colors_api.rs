@@ -0,0 +1,130 @@
+use mlua::{Value, Variadic};
+
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::expect::{bad_argument, expect_number};
+use crate::{Color, Simulator, SimulatorResult, pack_rgb, unpack_rgb};
+
+impl Simulator {
+    pub(super) fn init_colors_api(&mut self) -> SimulatorResult<()> {
+        let globals = self.lua.globals();
+
+        let colors_table = self.lua.create_table()?;
+
+        for color in Color::ALL {
+            colors_table.set(color.name(), color.value())?;
+        }
+
+        colors_table.set(
+            "combine",
+            self.lua
+                .create_native_function(|_lua, colors: Variadic<Value>| {
+                    let mut set = 0;
+                    for (ix, color) in colors.into_iter().enumerate() {
+                        set |= expect_number(ix + 1, color)? as u32;
+                    }
+
+                    Ok(set)
+                })?,
+        )?;
+        colors_table.set(
+            "subtract",
+            self.lua
+                .create_native_function(|_lua, (set, colors): (Value, Variadic<Value>)| {
+                    let mut set = expect_number(1, set)? as u32;
+                    for (ix, color) in colors.into_iter().enumerate() {
+                        set &= !(expect_number(ix + 2, color)? as u32);
+                    }
+
+                    Ok(set)
+                })?,
+        )?;
+        colors_table.set(
+            "test",
+            self.lua
+                .create_native_function(|_lua, (set, color): (Value, Value)| {
+                    let set = expect_number(1, set)? as u32;
+                    let color = expect_number(2, color)? as u32;
+
+                    Ok(set & color == color)
+                })?,
+        )?;
+
+        colors_table.set(
+            "packRGB",
+            self.lua
+                .create_native_function(|_lua, (r, g, b): (Value, Value, Value)| {
+                    Ok(pack_rgb([
+                        expect_number(1, r)?,
+                        expect_number(2, g)?,
+                        expect_number(3, b)?,
+                    ]))
+                })?,
+        )?;
+        colors_table.set(
+            "unpackRGB",
+            self.lua.create_native_function(|_lua, rgb: Value| {
+                let [r, g, b] = unpack_rgb(expect_number(1, rgb)? as u32);
+
+                Ok((r, g, b))
+            })?,
+        )?;
+        colors_table.set(
+            "rgb8",
+            self.lua
+                .create_native_function(|lua, (r, g, b): (Value, Value, Value)| {
+                    let colors: mlua::Table = lua.globals().get("colors")?;
+                    if g.is_nil() && b.is_nil() {
+                        colors
+                            .get::<_, mlua::Function>("unpackRGB")?
+                            .call::<_, mlua::MultiValue>(r)
+                    } else {
+                        colors
+                            .get::<_, mlua::Function>("packRGB")?
+                            .call::<_, mlua::MultiValue>((r, g, b))
+                    }
+                })?,
+        )?;
+
+        colors_table.set(
+            "toBlit",
+            self.lua.create_native_function(|_lua, color: Value| {
+                let value = expect_number(1, color)?;
+                let blit = Color::from_value(value as u32)
+                    .map(|color| (color.to_blit() as char).to_string())
+                    .unwrap_or_else(|| format!("{:x}", value.log2().floor() as i64));
+
+                Ok(blit)
+            })?,
+        )?;
+        colors_table.set(
+            "fromBlit",
+            self.lua.create_native_function(|_lua, hex: Value| {
+                let Value::String(hex) = hex else {
+                    return Err(bad_argument(1, "string", &hex));
+                };
+
+                Ok(match hex.as_bytes() {
+                    [blit] => Color::from_blit(*blit).map(Color::value),
+                    _ => None,
+                })
+            })?,
+        )?;
+
+        let colours_table = self.lua.create_table()?;
+        for pair in colors_table.clone().pairs::<String, Value>() {
+            let (key, value) = pair?;
+            let key = match key.as_str() {
+                "gray" => "grey",
+                "lightGray" => "lightGrey",
+                key => key,
+            };
+
+            colours_table.set(key, value)?;
+        }
+
+        globals.set("colors", colors_table)?;
+        globals.set("colours", colours_table)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,399 @@
+use std::cell::RefMut;
+
+use minecraft::world::Position;
+use minecraft::{ItemStack, NbtValue};
+use mlua::{IntoLuaMulti, Lua, MultiValue, Table, Value};
+
+use crate::simulator::SimulatorState;
+use crate::simulator::expect::bad_argument;
+use crate::simulator::peripheral_api::PeripheralLocation;
+use crate::{
+    DiskDrive, Event, FLOPPY_DISK_CAPACITY, Mount, Peripheral, Simulator, SimulatorResult, disk_id,
+    disk_label, floppy_disk,
+};
+
+impl Simulator {
+    /// Sets up the `disk` API.
+    pub(super) fn init_disk_api(&mut self) -> SimulatorResult<()> {
+        let disk_table: Table = self
+            .lua
+            .load(include_str!("../lua/disk.lua"))
+            .set_name("@disk.lua")
+            .call(())?;
+
+        self.lua.globals().set("disk", disk_table)?;
+
+        Ok(())
+    }
+
+    /// Creates a blank floppy disk with the next free disk ID, returning its
+    /// item.
+    pub fn new_floppy_disk(&self, label: Option<&str>) -> ItemStack {
+        let id = self.state.shared.next_disk_id.get();
+        self.state.shared.next_disk_id.set(id + 1);
+
+        self.state
+            .shared
+            .floppy_disks
+            .borrow_mut()
+            .insert(id, Mount::new("disk", FLOPPY_DISK_CAPACITY));
+
+        floppy_disk(id, label)
+    }
+
+    /// Returns the files on the floppy disk with the ID, unless they are
+    /// mounted on a computer, where they can be found in its filesystem.
+    pub fn floppy_disk_mut(&self, id: u32) -> Option<RefMut<'_, Mount>> {
+        RefMut::filter_map(self.state.shared.floppy_disks.borrow_mut(), |disks| {
+            disks.get_mut(&id)
+        })
+        .ok()
+    }
+}
+
+impl SimulatorState {
+    /// Calls a method of the disk drive at the location.
+    pub(super) fn call_drive<'lua>(
+        &self,
+        lua: &'lua Lua,
+        location: &PeripheralLocation,
+        method: &str,
+        args: MultiValue<'lua>,
+    ) -> mlua::Result<MultiValue<'lua>> {
+        let mut args = args.into_iter();
+        let mut arg = || args.next().unwrap_or(Value::Nil);
+
+        match method {
+            "isDiskPresent" => self
+                .with_drive(location, |drive| drive.disk().is_some())
+                .into_lua_multi(lua),
+            "getDiskLabel" => self
+                .with_drive(location, |drive| {
+                    drive.disk().and_then(disk_label).map(str::to_string)
+                })
+                .into_lua_multi(lua),
+            "setDiskLabel" => {
+                // Like in ComputerCraft, labels are at most 32 characters.
+                let label = match arg() {
+                    Value::Nil => None,
+                    Value::String(label) => {
+                        Some(label.to_string_lossy().chars().take(32).collect())
+                    }
+                    label => return Err(bad_argument(1, "string or nil", &label)),
+                };
+
+                self.with_drive(location, |drive| {
+                    let Some(stack) = drive.disk_mut().filter(|stack| disk_id(stack).is_some())
+                    else {
+                        return Err(mlua::Error::RuntimeError(
+                            "Disk label cannot be changed".to_string(),
+                        ));
+                    };
+
+                    let nbt = stack.nbt.get_or_insert_default();
+                    match label {
+                        Some(label) => nbt.insert("Label".to_string(), NbtValue::String(label)),
+                        None => nbt.remove("Label"),
+                    };
+
+                    Ok(())
+                })?;
+
+                ().into_lua_multi(lua)
+            }
+            "hasData" => self
+                .with_drive(location, |drive| drive.disk().and_then(disk_id).is_some())
+                .into_lua_multi(lua),
+            "getMountPath" => self
+                .with_drive(location, |drive| drive.mount_path.clone())
+                .into_lua_multi(lua),
+            "getDiskID" => self
+                .with_drive(location, |drive| drive.disk().and_then(disk_id))
+                .into_lua_multi(lua),
+            // Records aren't simulated, so there is never any audio to play.
+            "hasAudio" => false.into_lua_multi(lua),
+            "getAudioTitle" => self
+                .with_drive(location, |drive| drive.disk().map(|_| false))
+                .into_lua_multi(lua),
+            "playAudio" | "stopAudio" => ().into_lua_multi(lua),
+            "ejectDisk" => {
+                if let PeripheralLocation::Side(side) = location {
+                    self.unmount_disk(side);
+                }
+
+                // The disk pops out onto the block above the drive, where a
+                // turtle can pick it up.
+                let disk = self.with_drive(location, |drive| drive.eject());
+                if let Some(disk) = disk
+                    && let Some(position) = self.peripheral_position(location)
+                {
+                    self.shared.drop_item(position.up(), disk);
+                }
+
+                ().into_lua_multi(lua)
+            }
+            _ => Err(mlua::Error::RuntimeError(format!(
+                "No such method {method}"
+            ))),
+        }
+    }
+
+    /// Mounts the files of the floppy disk in the drive on the side, if there
+    /// is one which isn't mounted yet, at `disk`, or `disk2` and so on if that
+    /// is taken, queueing a `disk` event.
+    ///
+    /// Only drives attached to the computer are mounted, not ones on its
+    /// wired network.
+    pub(super) fn mount_disk(&self, side: &str) {
+        let mut peripherals = self.peripherals.borrow_mut();
+        let Some(Peripheral::DiskDrive(drive)) = peripherals.get_mut(side) else {
+            return;
+        };
+        if drive.mount_path.is_some() {
+            return;
+        }
+
+        let Some(id) = drive.disk().and_then(disk_id) else {
+            return;
+        };
+        let Some(mut mount) = self.shared.floppy_disks.borrow_mut().remove(&id) else {
+            return;
+        };
+        mount.set_drive(side);
+
+        let mut filesystem = self.filesystem.borrow_mut();
+        let path = (1..)
+            .map(|n| match n {
+                1 => "disk".to_string(),
+                n => format!("disk{n}"),
+            })
+            .find(|path| !filesystem.exists(path))
+            .expect("there should be a free mount path");
+        filesystem.mount(&path, mount);
+        drive.mount_path = Some(path);
+        self.events
+            .borrow_mut()
+            .push_back(Event::new("disk", vec![side.into()]));
+    }
+
+    /// Unmounts the files of the floppy disk in the drive on the side, if they
+    /// are mounted, queueing a `disk_eject` event.
+    pub(super) fn unmount_disk(&self, side: &str) {
+        let mut peripherals = self.peripherals.borrow_mut();
+        let Some(Peripheral::DiskDrive(drive)) = peripherals.get_mut(side) else {
+            return;
+        };
+        let Some(path) = drive.mount_path.take() else {
+            return;
+        };
+
+        let mount = self.filesystem.borrow_mut().unmount(&path);
+        if let Some(mount) = mount
+            && let Some(id) = drive.disk().and_then(disk_id)
+        {
+            self.shared.floppy_disks.borrow_mut().insert(id, mount);
+        }
+        self.events
+            .borrow_mut()
+            .push_back(Event::new("disk_eject", vec![side.into()]));
+    }
+
+    /// Returns the position of the block of the peripheral at the location.
+    fn peripheral_position(&self, location: &PeripheralLocation) -> Option<Position> {
+        match location {
+            PeripheralLocation::Side(side) => self.side_position(side),
+            PeripheralLocation::Remote { name, .. } => self
+                .shared
+                .peripherals
+                .borrow()
+                .get(name)
+                .map(|placed| placed.position),
+        }
+    }
+
+    /// Runs the function on the disk drive at the location.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no disk drive at the location.
+    fn with_drive<R>(
+        &self,
+        location: &PeripheralLocation,
+        f: impl FnOnce(&mut DiskDrive) -> R,
+    ) -> R {
+        self.with_peripheral(location, |peripheral| match peripheral {
+            Peripheral::DiskDrive(drive) => f(drive),
+            _ => unreachable!("the peripheral should be a disk drive"),
+        })
+        .expect("the disk drive should exist")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::Position;
+    use pretty_assertions::assert_eq;
+
+    use crate::{ComputerFamily, ComputerKind, DiskDrive, Peripheral, Simulator, disk_id};
+
+    #[test]
+    fn test_disk_drive() {
+        let simulator = Simulator::new().unwrap();
+        let disk = simulator.new_floppy_disk(Some("Programs"));
+        let id = disk_id(&disk).unwrap();
+        simulator
+            .floppy_disk_mut(id)
+            .unwrap()
+            .insert_file("hello.lua", "print('Hello from the disk')");
+        simulator.attach_peripheral("left", Peripheral::DiskDrive(DiskDrive::with_disk(disk)));
+
+        simulator
+            .exec_lua(
+                r#"
+                print(disk.isPresent("left"), disk.hasData("left"), disk.getID("left"))
+                print(disk.getLabel("left"), disk.getMountPath("left"))
+                print(fs.getDrive("disk"))
+                dofile("disk/hello.lua")
+
+                disk.setLabel("left", "Backup")
+                print(disk.getLabel("left"))
+                disk.eject("left")
+                print(disk.isPresent("left"), fs.exists("disk"))
+                print(disk.isPresent("right"))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "true\ttrue\t0",
+                "Programs\tdisk",
+                "left",
+                "Hello from the disk",
+                "Backup",
+                "false\tfalse",
+                "false",
+                "",
+            ]
+            .join("\n")
+        );
+
+        // The ejected disk is on the ground, with its files back off the
+        // computer.
+        assert_eq!(simulator.items_at(Position::new(-1, 1, 0)).len(), 1);
+        assert!(simulator.floppy_disk_mut(id).is_some());
+    }
+
+    #[test]
+    fn test_turtle_drop_and_suck_disk() {
+        let turtle = Simulator::new().unwrap();
+        let computer = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        computer.move_computer_to(Position::new(0, -2, 0));
+        computer.attach_peripheral("top", Peripheral::DiskDrive(DiskDrive::new()));
+
+        let disk = turtle.new_floppy_disk(None);
+        let id = disk_id(&disk).unwrap();
+        turtle.turtle_mut().inventory[0] = Some(disk);
+
+        turtle.exec_lua("print(turtle.dropDown())").unwrap();
+        computer
+            .exec_lua(
+                r#"
+                local file = fs.open("disk/startup.lua", "w")
+                file.write("print('Booted from the disk')")
+                file.close()
+                print(disk.getMountPath("top"))
+                "#,
+            )
+            .unwrap();
+        turtle
+            .exec_lua(
+                r#"
+                print(turtle.suckDown())
+                print(turtle.suckDown())
+                print(turtle.getItemDetail(1).name)
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            turtle.output(),
+            "true\tnil\ntrue\tnil\nfalse\tNo items to take\ncomputercraft:disk\n"
+        );
+        assert_eq!(computer.output(), "disk\n");
+        assert!(
+            !computer
+                .eval_lua::<bool>("return fs.exists('disk')")
+                .unwrap()
+        );
+        assert_eq!(
+            turtle.turtle().inventory[0].as_ref().and_then(disk_id),
+            Some(id)
+        );
+        assert!(turtle.floppy_disk_mut(id).unwrap().used_space() > 0);
+    }
+
+    #[test]
+    fn test_turtle_sucks_up_ejected_disk() {
+        let turtle = Simulator::new().unwrap();
+        let computer = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        computer.move_computer_to(Position::new(0, -3, 0));
+        let disk = turtle.new_floppy_disk(None);
+        computer.attach_peripheral("top", Peripheral::DiskDrive(DiskDrive::with_disk(disk)));
+        computer.exec_lua("disk.eject('top')").unwrap();
+
+        // The disk lands on the block between the drive and the turtle.
+        turtle
+            .exec_lua("print(turtle.suckDown()) print(turtle.getItemDetail(1).name)")
+            .unwrap();
+
+        assert_eq!(turtle.output(), "true\tnil\ncomputercraft:disk\n");
+        assert!(turtle.items_at(Position::new(0, -1, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_disk_events() {
+        let turtle = Simulator::new().unwrap();
+        let computer = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        computer.move_computer_to(Position::new(0, -2, 0));
+        computer.attach_peripheral("top", Peripheral::DiskDrive(DiskDrive::new()));
+        turtle.turtle_mut().inventory[0] = Some(turtle.new_floppy_disk(None));
+
+        turtle.exec_lua("turtle.dropDown()").unwrap();
+        computer.exec_lua("print(os.pullEvent('disk'))").unwrap();
+        turtle.exec_lua("turtle.suckDown()").unwrap();
+        computer
+            .exec_lua("print(os.pullEvent('disk_eject'))")
+            .unwrap();
+
+        assert_eq!(computer.output(), "disk\ttop\ndisk_eject\ttop\n");
+    }
+
+    #[test]
+    fn test_turtle_suck_nothing_keeps_disk_mounted() {
+        let turtle = Simulator::new().unwrap();
+        let computer = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        computer.move_computer_to(Position::new(0, -2, 0));
+        computer.attach_peripheral("top", Peripheral::DiskDrive(DiskDrive::new()));
+        turtle.turtle_mut().inventory[0] = Some(turtle.new_floppy_disk(None));
+
+        turtle
+            .exec_lua("turtle.dropDown() print(turtle.suckDown(0))")
+            .unwrap();
+        let mounted: (bool, bool, Option<String>) = computer
+            .eval_lua("return disk.isPresent('top'), fs.exists('disk'), disk.getMountPath('top')")
+            .unwrap();
+
+        assert_eq!(turtle.output(), "false\tNo items to take\n");
+        assert_eq!(mounted, (true, true, Some("disk".to_string())));
+    }
+}
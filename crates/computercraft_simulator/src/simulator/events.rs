@@ -0,0 +1,89 @@
+//! Converts events to and from Lua values, turning HTTP responses and
+//! websocket connections into handles.
+
+use std::collections::HashSet;
+
+use mlua::{FromLua, IntoLua, IntoLuaMulti, Lua, MultiValue, Value};
+
+use crate::simulator::http_api::create_response_handle;
+use crate::simulator::websocket::create_websocket_handle;
+use crate::{Event, EventValue};
+
+impl EventValue {
+    fn from_lua_value(
+        value: Value,
+        seen: &mut HashSet<*const std::ffi::c_void>,
+    ) -> mlua::Result<Self> {
+        Ok(match value {
+            Value::Nil => Self::Nil,
+            Value::Boolean(value) => Self::Boolean(value),
+            Value::Integer(value) => Self::Integer(value),
+            Value::Number(value) => Self::Number(value),
+            Value::String(value) => Self::String(value.as_bytes().to_vec()),
+            Value::Table(table) => {
+                if !seen.insert(table.to_pointer()) {
+                    return Err(mlua::Error::RuntimeError(
+                        "Cannot serialize recursive table".to_string(),
+                    ));
+                }
+
+                let mut entries = Vec::new();
+                for pair in table.clone().pairs::<Value, Value>() {
+                    let (key, value) = pair?;
+                    let key = Self::from_lua_value(key, seen)?;
+                    if key == Self::Nil {
+                        continue;
+                    }
+
+                    entries.push((key, Self::from_lua_value(value, seen)?));
+                }
+
+                seen.remove(&table.to_pointer());
+
+                Self::Table(entries)
+            }
+            _ => Self::Nil,
+        })
+    }
+}
+
+impl<'lua> IntoLua<'lua> for EventValue {
+    fn into_lua(self, lua: &'lua Lua) -> mlua::Result<Value<'lua>> {
+        Ok(match self {
+            Self::Nil => Value::Nil,
+            Self::Boolean(value) => Value::Boolean(value),
+            Self::Integer(value) => Value::Integer(value),
+            Self::Number(value) => Value::Number(value),
+            Self::String(value) => Value::String(lua.create_string(value)?),
+            Self::Table(entries) => {
+                let table = lua.create_table()?;
+                for (key, value) in entries {
+                    table.raw_set(key, value)?;
+                }
+
+                Value::Table(table)
+            }
+            Self::HttpResponse { response, binary } => {
+                Value::Table(create_response_handle(lua, &response, binary)?)
+            }
+            Self::Websocket(connection) => Value::Table(create_websocket_handle(lua, connection)?),
+        })
+    }
+}
+
+impl<'lua> FromLua<'lua> for EventValue {
+    fn from_lua(value: Value<'lua>, _lua: &'lua Lua) -> mlua::Result<Self> {
+        Self::from_lua_value(value, &mut HashSet::new())
+    }
+}
+
+impl<'lua> IntoLuaMulti<'lua> for Event {
+    fn into_lua_multi(self, lua: &'lua Lua) -> mlua::Result<MultiValue<'lua>> {
+        let mut values = vec![self.name.into_lua(lua)?];
+        for arg in self.args {
+            values.push(arg.into_lua(lua)?);
+        }
+
+        Ok(MultiValue::from_vec(values))
+    }
+}
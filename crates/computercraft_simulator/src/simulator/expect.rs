@@ -0,0 +1,51 @@
+//! Argument validation helpers, mirroring ComputerCraft's `cc.expect` module.
+
+use mlua::{Lua, Value};
+
+/// Returns the error ComputerCraft raises when an argument has the wrong type.
+pub(crate) fn bad_argument(index: usize, expected: &str, value: &Value) -> mlua::Error {
+    let actual = match value {
+        Value::Nil => "nil",
+        Value::Boolean(_) => "boolean",
+        Value::Integer(_) | Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Table(_) => "table",
+        Value::Function(_) => "function",
+        Value::Thread(_) => "thread",
+        _ => "userdata",
+    };
+
+    mlua::Error::RuntimeError(format!(
+        "bad argument #{index} ({expected} expected, got {actual})"
+    ))
+}
+
+/// Expects the argument to be a string or a number, returning its bytes.
+pub(crate) fn expect_text(lua: &Lua, index: usize, value: Value) -> mlua::Result<Vec<u8>> {
+    match value {
+        Value::String(_) | Value::Integer(_) | Value::Number(_) => {
+            let text = lua
+                .coerce_string(value)?
+                .expect("value should be coercible");
+            Ok(text.as_bytes().to_vec())
+        }
+        value => Err(bad_argument(index, "string", &value)),
+    }
+}
+
+/// Expects the argument to be a string, returning its bytes.
+pub(crate) fn expect_string(index: usize, value: Value) -> mlua::Result<Vec<u8>> {
+    match value {
+        Value::String(text) => Ok(text.as_bytes().to_vec()),
+        value => Err(bad_argument(index, "string", &value)),
+    }
+}
+
+/// Expects the argument to be a number, returning it.
+pub(crate) fn expect_number(index: usize, value: Value) -> mlua::Result<f64> {
+    match value {
+        Value::Integer(n) => Ok(n as f64),
+        Value::Number(n) => Ok(n),
+        value => Err(bad_argument(index, "number", &value)),
+    }
+}
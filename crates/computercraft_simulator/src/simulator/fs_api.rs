@@ -0,0 +1,784 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mlua::{Function, Lua, LuaSerdeExt, Table, Value, Variadic};
+
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::SimulatorState;
+use crate::simulator::expect::{bad_argument, expect_number, expect_string, expect_text};
+use crate::{FileSystemError, Simulator, SimulatorResult, combine, get_dir, get_name};
+
+impl Simulator {
+    pub(super) fn init_fs_api(&mut self) -> SimulatorResult<()> {
+        let globals = self.lua.globals();
+
+        let fs_table = self.lua.create_table()?;
+
+        fs_table.set(
+            "list",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    state.filesystem.borrow().list(&path).map_err(fs_error)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "exists",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    Ok(state.filesystem.borrow().exists(&path))
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "isDir",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    Ok(state.filesystem.borrow().is_dir(&path))
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "isReadOnly",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    Ok(state.filesystem.borrow().is_read_only(&path))
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "getDrive",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    Ok(state.filesystem.borrow().drive(&path).map(str::to_string))
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "isDriveRoot",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+                    let dir = get_dir(&path);
+                    let filesystem = state.filesystem.borrow();
+
+                    Ok(dir == ".." || filesystem.drive(&path) != filesystem.drive(&dir))
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "makeDir",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    state
+                        .filesystem
+                        .borrow_mut()
+                        .make_dir(&path)
+                        .map_err(fs_error)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "move",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, (from, to): (Value, Value)| {
+                    let from = expect_path(1, from)?;
+                    let to = expect_path(2, to)?;
+
+                    state
+                        .filesystem
+                        .borrow_mut()
+                        .rename(&from, &to)
+                        .map_err(fs_error)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "copy",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, (from, to): (Value, Value)| {
+                    let from = expect_path(1, from)?;
+                    let to = expect_path(2, to)?;
+
+                    state
+                        .filesystem
+                        .borrow_mut()
+                        .copy(&from, &to)
+                        .map_err(fs_error)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "delete",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    state
+                        .filesystem
+                        .borrow_mut()
+                        .delete(&path)
+                        .map_err(fs_error)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "getSize",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    state.filesystem.borrow().size(&path).map_err(fs_error)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "getFreeSpace",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    state
+                        .filesystem
+                        .borrow()
+                        .free_space(&path)
+                        .map_err(fs_error)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "getCapacity",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, path: Value| {
+                    let path = expect_path(1, path)?;
+
+                    state.filesystem.borrow().capacity(&path).map_err(fs_error)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "find",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, pattern: Value| {
+                    let pattern = expect_path(1, pattern)?;
+
+                    state.filesystem.borrow().find(&pattern).map_err(fs_error)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "attributes",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |lua, path: Value| {
+                    let path = expect_path(1, path)?;
+                    let attributes = state
+                        .filesystem
+                        .borrow()
+                        .attributes(&path)
+                        .map_err(fs_error)?;
+
+                    lua.to_value(&attributes)
+                }
+            })?,
+        )?;
+        fs_table.set(
+            "combine",
+            self.lua
+                .create_native_function(|_lua, parts: Variadic<Value>| {
+                    let mut paths = Vec::new();
+                    for (ix, part) in parts.into_iter().enumerate() {
+                        paths.push(expect_path(ix + 1, part)?);
+                    }
+
+                    Ok(combine(paths.iter().map(String::as_str)))
+                })?,
+        )?;
+        fs_table.set(
+            "getName",
+            self.lua.create_native_function(|_lua, path: Value| {
+                let path = expect_path(1, path)?;
+
+                Ok(get_name(&path))
+            })?,
+        )?;
+        fs_table.set(
+            "getDir",
+            self.lua.create_native_function(|_lua, path: Value| {
+                let path = expect_path(1, path)?;
+
+                Ok(get_dir(&path))
+            })?,
+        )?;
+        fs_table.set(
+            "open",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |lua, (path, mode): (Value, Value)| {
+                    let path = expect_path(1, path)?;
+                    let mode = String::from_utf8_lossy(&expect_string(2, mode)?).into_owned();
+
+                    match open_file(lua, &state, path, &mode)? {
+                        Ok(handle) => Ok((Value::Table(handle), None)),
+                        Err(err) => Ok((Value::Nil, Some(err.to_string()))),
+                    }
+                }
+            })?,
+        )?;
+
+        globals.set("fs", fs_table)?;
+
+        Ok(())
+    }
+}
+
+/// Expects the argument to be a path.
+fn expect_path(index: usize, value: Value) -> mlua::Result<String> {
+    let path = expect_string(index, value)?;
+
+    Ok(String::from_utf8_lossy(&path).into_owned())
+}
+
+/// Converts a filesystem error into the Lua error ComputerCraft raises.
+pub(crate) fn fs_error(err: FileSystemError) -> mlua::Error {
+    mlua::Error::RuntimeError(err.to_string())
+}
+
+/// An open file, shared between the functions of its handle.
+///
+/// The whole file is kept in memory. Like in ComputerCraft, writes are kept in
+/// the handle until it is flushed or closed.
+pub(super) struct FileHandle {
+    path: String,
+    contents: Vec<u8>,
+    position: usize,
+    is_open: bool,
+    /// Whether there are writes which haven't been flushed to the file yet.
+    is_modified: bool,
+}
+
+impl FileHandle {
+    /// Opens a handle to read the contents from the start, which aren't
+    /// backed by a file.
+    pub(super) fn reading(contents: Vec<u8>) -> Self {
+        Self {
+            path: String::new(),
+            contents,
+            position: 0,
+            is_open: true,
+            is_modified: false,
+        }
+    }
+
+    fn check_open(&self) -> mlua::Result<()> {
+        if !self.is_open {
+            return Err(mlua::Error::RuntimeError(
+                "attempt to use a closed file".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn remaining(&self) -> &[u8] {
+        &self.contents[self.position.min(self.contents.len())..]
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let end = self.position + bytes.len();
+        if self.contents.len() < end {
+            self.contents.resize(end, 0);
+        }
+
+        self.contents[self.position..end].copy_from_slice(bytes);
+        self.position = end;
+        self.is_modified = true;
+    }
+
+    /// Writes the contents of the handle to its file, if they have changed
+    /// since it was last flushed.
+    fn flush(&mut self, state: &SimulatorState) -> mlua::Result<()> {
+        if !self.is_modified {
+            return Ok(());
+        }
+
+        state
+            .filesystem
+            .borrow_mut()
+            .write_file(&self.path, self.contents.clone())
+            .map_err(fs_error)?;
+        self.is_modified = false;
+
+        Ok(())
+    }
+}
+
+/// Opens the file in the given mode, returning its handle.
+///
+/// Errors with the file itself (such as it not existing) are returned as the
+/// inner error, as `fs.open` returns them instead of raising them.
+fn open_file<'lua>(
+    lua: &'lua Lua,
+    state: &Rc<SimulatorState>,
+    path: String,
+    mode: &str,
+) -> mlua::Result<Result<Table<'lua>, FileSystemError>> {
+    let (mode, is_binary) = match mode.strip_suffix('b') {
+        Some(mode) => (mode, true),
+        None => (mode, false),
+    };
+
+    let mut filesystem = state.filesystem.borrow_mut();
+    let (contents, position) = match mode {
+        "r" => match filesystem.read_file(&path) {
+            Ok(contents) => (contents, 0),
+            Err(err) => return Ok(Err(err)),
+        },
+        "w" => (Vec::new(), 0),
+        "a" => {
+            let contents = filesystem.read_file(&path).unwrap_or_default();
+            let position = contents.len();
+            (contents, position)
+        }
+        _ => return Err(mlua::Error::RuntimeError("Unsupported mode".to_string())),
+    };
+    if mode != "r"
+        && let Err(err) = filesystem.write_file(&path, contents.clone())
+    {
+        return Ok(Err(err));
+    }
+
+    let handle = Rc::new(RefCell::new(FileHandle {
+        path,
+        contents,
+        position,
+        is_open: true,
+        is_modified: false,
+    }));
+
+    let table = if mode == "r" {
+        let table = create_read_handle(lua, handle.clone(), is_binary)?;
+        table.set("close", create_close(lua, handle.clone())?)?;
+        table
+    } else {
+        create_write_handle(lua, state.clone(), handle.clone(), is_binary)?
+    };
+
+    table.set("seek", create_seek(lua, handle)?)?;
+
+    Ok(Ok(table))
+}
+
+/// Creates the `close` function of a handle.
+pub(super) fn create_close(
+    lua: &Lua,
+    handle: Rc<RefCell<FileHandle>>,
+) -> mlua::Result<Function<'_>> {
+    lua.create_native_function(move |_lua, ()| {
+        let mut handle = handle.borrow_mut();
+        handle.check_open()?;
+        handle.is_open = false;
+
+        Ok(())
+    })
+}
+
+/// Creates the `seek` function of a handle.
+pub(super) fn create_seek(
+    lua: &Lua,
+    handle: Rc<RefCell<FileHandle>>,
+) -> mlua::Result<Function<'_>> {
+    lua.create_native_function(
+        move |_lua, (whence, offset): (Option<String>, Option<i64>)| {
+            let mut handle = handle.borrow_mut();
+            handle.check_open()?;
+
+            let offset = offset.unwrap_or(0);
+            let base = match whence.as_deref().unwrap_or("cur") {
+                "set" => 0,
+                "cur" => handle.position as i64,
+                "end" => handle.contents.len() as i64,
+                whence => {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "bad argument #1 (invalid option {whence})"
+                    )));
+                }
+            };
+
+            let position = base + offset;
+            if position < 0 {
+                return Ok((None, Some("Position is negative")));
+            }
+
+            handle.position = position as usize;
+
+            Ok((Some(position), None))
+        },
+    )
+}
+
+pub(super) fn create_read_handle(
+    lua: &Lua,
+    handle: Rc<RefCell<FileHandle>>,
+    is_binary: bool,
+) -> mlua::Result<Table<'_>> {
+    let table = lua.create_table()?;
+
+    table.set(
+        "read",
+        lua.create_native_function({
+            let handle = handle.clone();
+            move |lua, count: Value| {
+                let mut handle = handle.borrow_mut();
+                handle.check_open()?;
+
+                if is_binary && count.is_nil() {
+                    let byte = handle.remaining().first().copied();
+                    if byte.is_some() {
+                        handle.position += 1;
+                    }
+
+                    return Ok(byte.map_or(Value::Nil, |byte| Value::Integer(byte as i64)));
+                }
+
+                let count = match count {
+                    Value::Nil => 1.0,
+                    count => expect_number(1, count)?,
+                };
+                if count < 0.0 {
+                    return Err(mlua::Error::RuntimeError(
+                        "Cannot read a negative number of bytes".to_string(),
+                    ));
+                }
+
+                let remaining = handle.remaining();
+                if remaining.is_empty() {
+                    return Ok(Value::Nil);
+                }
+
+                let bytes = remaining[..(count as usize).min(remaining.len())].to_vec();
+                handle.position += bytes.len();
+
+                Ok(Value::String(lua.create_string(bytes)?))
+            }
+        })?,
+    )?;
+    table.set(
+        "readLine",
+        lua.create_native_function({
+            let handle = handle.clone();
+            move |lua, with_trailing: Option<bool>| {
+                let mut handle = handle.borrow_mut();
+                handle.check_open()?;
+
+                let remaining = handle.remaining();
+                if remaining.is_empty() {
+                    return Ok(Value::Nil);
+                }
+
+                let (line, consumed) = match remaining.iter().position(|&byte| byte == b'\n') {
+                    Some(end) if with_trailing.unwrap_or(false) => (&remaining[..=end], end + 1),
+                    Some(end) => (&remaining[..end], end + 1),
+                    None => (remaining, remaining.len()),
+                };
+                let line = lua.create_string(line)?;
+                handle.position += consumed;
+
+                Ok(Value::String(line))
+            }
+        })?,
+    )?;
+    table.set(
+        "readAll",
+        lua.create_native_function(move |lua, ()| {
+            let mut handle = handle.borrow_mut();
+            handle.check_open()?;
+
+            let contents = lua.create_string(handle.remaining())?;
+            handle.position = handle.contents.len();
+
+            Ok(contents)
+        })?,
+    )?;
+
+    Ok(table)
+}
+
+fn create_write_handle(
+    lua: &Lua,
+    state: Rc<SimulatorState>,
+    handle: Rc<RefCell<FileHandle>>,
+    is_binary: bool,
+) -> mlua::Result<Table<'_>> {
+    let table = lua.create_table()?;
+
+    table.set(
+        "write",
+        lua.create_native_function({
+            let handle = handle.clone();
+            move |lua, value: Value| {
+                let mut handle = handle.borrow_mut();
+                handle.check_open()?;
+
+                let bytes = match value {
+                    Value::Integer(_) | Value::Number(_) if is_binary => {
+                        vec![expect_number(1, value)? as i64 as u8]
+                    }
+                    Value::String(_) | Value::Integer(_) | Value::Number(_) => {
+                        expect_text(lua, 1, value)?
+                    }
+                    value if is_binary => return Err(bad_argument(1, "string or number", &value)),
+                    value => return Err(bad_argument(1, "string", &value)),
+                };
+                handle.write(&bytes);
+
+                Ok(())
+            }
+        })?,
+    )?;
+    if !is_binary {
+        table.set(
+            "writeLine",
+            lua.create_native_function({
+                let handle = handle.clone();
+                move |lua, value: Value| {
+                    let mut handle = handle.borrow_mut();
+                    handle.check_open()?;
+
+                    let mut bytes = expect_text(lua, 1, value)?;
+                    bytes.push(b'\n');
+                    handle.write(&bytes);
+
+                    Ok(())
+                }
+            })?,
+        )?;
+    }
+    table.set(
+        "flush",
+        lua.create_native_function({
+            let state = state.clone();
+            let handle = handle.clone();
+            move |_lua, ()| {
+                let mut handle = handle.borrow_mut();
+                handle.check_open()?;
+
+                handle.flush(&state)
+            }
+        })?,
+    )?;
+    table.set(
+        "close",
+        lua.create_native_function(move |_lua, ()| {
+            let mut handle = handle.borrow_mut();
+            handle.check_open()?;
+            handle.is_open = false;
+
+            handle.flush(&state)
+        })?,
+    )?;
+
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::Simulator;
+
+    #[test]
+    fn test_fs_read_write() {
+        let simulator = Simulator::new().unwrap();
+
+        let result: (String, String, Vec<String>) = simulator
+            .eval_lua(
+                r#"
+                local file = fs.open("logs/debug.txt", "w")
+                file.writeLine("first")
+                file.write(42)
+                file.close()
+
+                file = fs.open("logs/debug.txt", "a")
+                file.write("\nsecond")
+                file.close()
+
+                file = fs.open("logs/debug.txt", "r")
+                local line = file.readLine()
+                local rest = file.readAll()
+                file.close()
+
+                return line, rest, fs.list("logs")
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            (
+                "first".to_string(),
+                "42\nsecond".to_string(),
+                vec!["debug.txt".to_string()]
+            )
+        );
+        assert_eq!(
+            simulator.filesystem().read_file("logs/debug.txt").unwrap(),
+            b"first\n42\nsecond"
+        );
+    }
+
+    #[test]
+    fn test_fs_writes_are_buffered_until_flushed() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                local function contents()
+                    local file = fs.open("log.txt", "r")
+                    local contents = file.readAll()
+                    file.close()
+                    return contents
+                end
+
+                local log = fs.open("log.txt", "w")
+                log.write("started")
+                print(fs.exists("log.txt"), contents())
+                log.flush()
+                print(contents())
+                log.write(", stopped")
+                print(contents())
+                log.close()
+                print(contents())
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            ["true\t", "started", "started", "started, stopped", "",].join("\n")
+        );
+    }
+
+    #[test]
+    fn test_fs_errors() {
+        let simulator = Simulator::new().unwrap();
+        simulator
+            .filesystem_mut()
+            .mount_at_mut("rom")
+            .unwrap()
+            .insert_file("programs/hello.lua", "print('hello')");
+
+        let result: (Option<String>, String, bool, bool, bool) = simulator
+            .eval_lua(
+                r#"
+                local _, err = fs.open("missing.txt", "r")
+                local _, delete_err = pcall(fs.delete, "rom/programs/hello.lua")
+                local _, positioned_err = pcall(function() fs.makeDir("rom/x") end)
+                return err, delete_err, positioned_err:match("^.+:%d+: /rom/x: Access denied$") ~= nil,
+                    fs.isReadOnly("rom/programs"), fs.exists("rom/programs/hello.lua")
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            (
+                Some("/missing.txt: No such file".to_string()),
+                "/rom/programs/hello.lua: Access denied".to_string(),
+                true,
+                true,
+                true
+            )
+        );
+
+        let result: Result<(), _> = simulator.exec_lua(
+            r#"
+            local file = fs.open("x.txt", "w")
+            file.close()
+            file.write("too late")
+            "#,
+        );
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("attempt to use a closed file")
+        );
+    }
+
+    #[test]
+    fn test_fs_paths() {
+        let simulator = Simulator::new().unwrap();
+
+        let result: (String, String, String, String, i64) = simulator
+            .eval_lua(
+                r#"
+                fs.makeDir("a/b")
+                fs.copy("a", "c")
+                return fs.combine("a", "b/../c.lua"), fs.getName("a/c.lua"), fs.getDir("a/c.lua"),
+                    fs.getDrive("c/b"), fs.attributes("c").size
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            (
+                "a/c.lua".to_string(),
+                "c.lua".to_string(),
+                "a".to_string(),
+                "hdd".to_string(),
+                0
+            )
+        );
+    }
+
+    #[test]
+    fn test_fs_timestamps_follow_ticks() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                local file = fs.open("log.txt", "w")
+                file.write("started")
+                file.close()
+                sleep(1)
+                fs.makeDir("logs")
+                local file = fs.open("log.txt", "a")
+                file.write(", waited")
+                file.close()
+
+                local log, logs = fs.attributes("log.txt"), fs.attributes("logs")
+                print(log.created, log.modified, logs.created)
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(simulator.output(), "0\t1000\t1000\n");
+    }
+}
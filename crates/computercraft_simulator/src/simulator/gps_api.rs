@@ -0,0 +1,111 @@
+use mlua::Table;
+
+use crate::{GpsHost, Simulator, SimulatorResult};
+
+impl Simulator {
+    /// Sets up the `gps` API.
+    pub(super) fn init_gps_api(&mut self) -> SimulatorResult<()> {
+        let gps_table: Table = self
+            .lua
+            .load(include_str!("../lua/gps.lua"))
+            .set_name("@gps.lua")
+            .call(())?;
+
+        self.lua.globals().set("gps", gps_table)?;
+
+        Ok(())
+    }
+
+    /// Places a GPS host in the world, which every computer in it can locate
+    /// itself with while in range of the host's modem.
+    pub fn add_gps_host(&self, host: GpsHost) {
+        self.state.shared.gps_hosts.borrow_mut().push(host);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::Position;
+    use pretty_assertions::assert_eq;
+
+    use crate::{GpsHost, Modem, ModemKind, Peripheral, Simulator};
+
+    fn add_hosts(simulator: &Simulator, positions: &[Position], modem: ModemKind) {
+        for &position in positions {
+            simulator.add_gps_host(GpsHost { position, modem });
+        }
+    }
+
+    #[test]
+    fn test_locate() {
+        let simulator = Simulator::new().unwrap();
+        simulator.attach_peripheral("left", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        simulator.move_turtle_to(Position::new(10, 5, -20));
+        add_hosts(
+            &simulator,
+            &[
+                Position::new(0, 100, 0),
+                Position::new(30, 100, 0),
+                Position::new(0, 100, 30),
+                Position::new(0, 130, 0),
+            ],
+            ModemKind::Ender,
+        );
+
+        simulator
+            .exec_lua(
+                r#"
+                print(gps.locate())
+                turtle.up()
+                turtle.forward()
+                print(gps.locate(2, true))
+                print(peripheral.call("left", "isOpen", gps.CHANNEL_GPS))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "10\t5\t-20",
+                "Finding position...",
+                "96.83491106001 metres from 0,100,0",
+                "98.371743910536 metres from 30,100,0",
+                "107.41042779917 metres from 0,100,30",
+                "126.16259350537 metres from 0,130,0",
+                "Position is 10,6,-21",
+                "10\t6\t-21",
+                "false",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_locate_needs_four_hosts_in_range() {
+        let simulator = Simulator::new().unwrap();
+        simulator.attach_peripheral("left", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        add_hosts(
+            &simulator,
+            &[
+                Position::new(0, 10, 0),
+                Position::new(30, 10, 0),
+                Position::new(0, 10, 30),
+            ],
+            ModemKind::Wireless,
+        );
+
+        let result: Option<f64> = simulator.eval_lua("gps.locate()").unwrap();
+        assert_eq!(result, None);
+
+        // Too far away to hear the ping.
+        add_hosts(&simulator, &[Position::new(0, 100, 0)], ModemKind::Wireless);
+        let result: Option<f64> = simulator.eval_lua("gps.locate()").unwrap();
+        assert_eq!(result, None);
+
+        add_hosts(&simulator, &[Position::new(0, 40, 0)], ModemKind::Wireless);
+        let result: (f64, f64, f64) = simulator.eval_lua("gps.locate()").unwrap();
+        assert_eq!(result, (0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,435 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use mlua::{Function, Lua, Table, Value};
+
+use crate::http::Url;
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::fs_api::{FileHandle, create_close, create_read_handle, create_seek};
+use crate::simulator::websocket::WRAP_WEBSOCKET;
+use crate::simulator::{SimulatorState, expect::bad_argument};
+use crate::{
+    Event, EventValue, HttpHandler, HttpRequest, HttpResponse, Simulator, SimulatorResult,
+};
+
+impl Simulator {
+    /// Sets up the `http` API, including websockets.
+    pub(super) fn init_http_api(&mut self) -> SimulatorResult<()> {
+        let native = self.lua.create_table()?;
+        native.set(
+            "request",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |lua, (request, body, headers, binary): (Value, Value, Value, Value)| {
+                    let (request, binary) = parse_request(lua, request, body, headers, binary)?;
+
+                    Ok(match state.send_http_request(request, binary) {
+                        Ok(()) => (true, None),
+                        Err(err) => (false, Some(err)),
+                    })
+                }
+            })?,
+        )?;
+        native.set(
+            "checkURL",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, url: String| {
+                    if let Err(err) = check_url(&url) {
+                        return Ok((false, Some(err)));
+                    }
+
+                    let handler = state.shared.http_handler.borrow().clone();
+                    let event = match handler.check(&url) {
+                        Ok(()) => Event::new("http_check", vec![url.into(), true.into()]),
+                        Err(err) => {
+                            Event::new("http_check", vec![url.into(), false.into(), err.into()])
+                        }
+                    };
+                    state.events.borrow_mut().push_back(event);
+
+                    Ok((true, None))
+                }
+            })?,
+        )?;
+
+        native.set(
+            "websocket",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, (url, headers): (String, Value)| {
+                    let headers = parse_headers(headers)?;
+
+                    Ok(match state.connect_websocket(&url, headers) {
+                        Ok(()) => (true, None),
+                        Err(err) => (false, Some(err)),
+                    })
+                }
+            })?,
+        )?;
+
+        let (http_table, wrap_websocket): (Table, Function) = self
+            .lua
+            .load(include_str!("../lua/http.lua"))
+            .set_name("@http.lua")
+            .call(native)?;
+
+        self.lua.globals().set("http", http_table)?;
+        self.lua
+            .set_named_registry_value(WRAP_WEBSOCKET, wrap_websocket)?;
+
+        Ok(())
+    }
+
+    /// Sets what serves the requests every computer in the world makes with
+    /// the `http` API. By default, every request is denied.
+    pub fn set_http_handler(&self, handler: impl HttpHandler + 'static) {
+        *self.state.shared.http_handler.borrow_mut() = Rc::new(handler);
+    }
+}
+
+impl SimulatorState {
+    /// Hands the request to the world's HTTP handler if it allows the URL,
+    /// queueing an `http_success` or `http_failure` event with its response.
+    /// Returns why the request couldn't be made if its URL is invalid.
+    fn send_http_request(&self, request: HttpRequest, binary: bool) -> Result<(), String> {
+        check_url(&request.url)?;
+
+        let handler = self.shared.http_handler.borrow().clone();
+        let url = EventValue::from(request.url.as_str());
+        let event = match handler
+            .check(&request.url)
+            .and_then(|()| handler.handle(&request))
+        {
+            Ok(response) if response.is_success() => Event::new(
+                "http_success",
+                vec![
+                    url,
+                    EventValue::HttpResponse {
+                        response: Rc::new(response),
+                        binary,
+                    },
+                ],
+            ),
+            Ok(response) => Event::new(
+                "http_failure",
+                vec![
+                    url,
+                    response.message.as_str().into(),
+                    EventValue::HttpResponse {
+                        response: Rc::new(response),
+                        binary,
+                    },
+                ],
+            ),
+            Err(err) => Event::new("http_failure", vec![url, err.into()]),
+        };
+        self.events.borrow_mut().push_back(event);
+
+        Ok(())
+    }
+}
+
+/// Checks the URL can be used to make HTTP requests, returning the error
+/// ComputerCraft gives if it can't.
+fn check_url(url: &str) -> Result<(), String> {
+    let url = Url::parse(url)?;
+    if url.scheme != "http" && url.scheme != "https" {
+        return Err(format!("Invalid protocol '{}'", url.scheme));
+    }
+
+    Ok(())
+}
+
+/// Reads the arguments of the native `http.request`, which are either the
+/// parts of the request, or a table of them.
+fn parse_request<'lua>(
+    lua: &'lua Lua,
+    request: Value<'lua>,
+    body: Value<'lua>,
+    headers: Value<'lua>,
+    binary: Value<'lua>,
+) -> mlua::Result<(HttpRequest, bool)> {
+    let (url, body, headers, binary, method) = match request {
+        Value::Table(options) => (
+            options.get("url")?,
+            options.get("body")?,
+            options.get("headers")?,
+            options.get("binary")?,
+            options.get("method")?,
+        ),
+        url => (url, body, headers, binary, Value::Nil),
+    };
+
+    let url = match url {
+        Value::String(url) => url.to_string_lossy().into_owned(),
+        url => return Err(bad_argument(1, "string", &url)),
+    };
+    let body = match body {
+        Value::Nil => None,
+        body => Some(lua.unpack::<mlua::String>(body)?.as_bytes().to_vec()),
+    };
+    let headers = parse_headers(headers)?;
+    let method = match method {
+        Value::String(method) => method.to_string_lossy().to_ascii_uppercase(),
+        _ if body.is_some() => "POST".to_string(),
+        _ => "GET".to_string(),
+    };
+
+    let request = HttpRequest {
+        url,
+        method,
+        headers,
+        body,
+    };
+
+    Ok((request, binary.as_boolean().unwrap_or(false)))
+}
+
+/// Reads the headers of a request, if there is a table of them.
+fn parse_headers(headers: Value) -> mlua::Result<BTreeMap<String, String>> {
+    match headers {
+        Value::Table(headers) => headers
+            .pairs::<mlua::String, mlua::String>()
+            .map(|pair| {
+                let (name, value) = pair?;
+                Ok((
+                    name.to_string_lossy().into_owned(),
+                    value.to_string_lossy().into_owned(),
+                ))
+            })
+            .collect(),
+        _ => Ok(BTreeMap::new()),
+    }
+}
+
+/// Creates the handle to read the response, as given to programs in
+/// `http_success` and `http_failure` events.
+pub(super) fn create_response_handle<'lua>(
+    lua: &'lua Lua,
+    response: &HttpResponse,
+    binary: bool,
+) -> mlua::Result<Table<'lua>> {
+    let handle = Rc::new(RefCell::new(FileHandle::reading(response.body.clone())));
+
+    let table = create_read_handle(lua, handle.clone(), binary)?;
+    if binary {
+        table.set("seek", create_seek(lua, handle.clone())?)?;
+    }
+    table.set("close", create_close(lua, handle)?)?;
+
+    let (status, message) = (response.status, response.message.clone());
+    table.set(
+        "getResponseCode",
+        lua.create_native_function(move |_lua, ()| Ok((status, message.clone())))?,
+    )?;
+    let headers = response.headers.clone();
+    table.set(
+        "getResponseHeaders",
+        lua.create_native_function(move |_lua, ()| Ok(headers.clone()))?,
+    )?;
+
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{HttpHandler, HttpRequest, HttpResponse, LocalhostHandler, Simulator};
+
+    fn serve(request: &HttpRequest) -> Result<HttpResponse, String> {
+        match (request.method.as_str(), request.url.as_str()) {
+            ("GET", "https://example.com/version.txt") => {
+                Ok(HttpResponse::ok("1.2.0\nRelease notes\n")
+                    .with_header("Content-Type", "text/plain"))
+            }
+            ("GET", "https://example.com/logo.bin") => Ok(HttpResponse::ok(vec![0x89, b'P'])),
+            ("POST", "https://example.com/telemetry") => {
+                let body = request.body.clone().unwrap_or_default();
+                Ok(HttpResponse::new(201, [b"got ".as_slice(), &body].concat()))
+            }
+            ("GET", "https://example.com/offline") => Err("Could not connect".to_string()),
+            _ => Ok(HttpResponse::new(404, "Nothing here")),
+        }
+    }
+
+    #[test]
+    fn test_http_requests() {
+        let simulator = Simulator::new().unwrap();
+        simulator.set_http_handler(serve);
+
+        simulator
+            .exec_lua(
+                r#"
+                local response = http.get("https://example.com/version.txt")
+                print(response.getResponseCode())
+                print(response.getResponseHeaders()["Content-Type"])
+                print(response.readLine(), response.readAll())
+                response.close()
+                print(pcall(response.readAll))
+
+                local response = http.post("https://example.com/telemetry", "fuel=80")
+                print(response.getResponseCode())
+                print(response.readAll())
+
+                local response = http.get("https://example.com/logo.bin", nil, true)
+                print(response.read(), response.read(1), response.read())
+                print(response.seek("set", 0), response.read())
+
+                local response, err, failed = http.get("https://example.com/missing")
+                print(response, err, failed.readAll())
+                print(http.get("https://example.com/offline"))
+                print(http.get("example.com"))
+                print(http.get("ftp://example.com"))
+
+                http.request({ url = "https://example.com/version.txt", method = "GET" })
+                local _, url, response = os.pullEvent("http_success")
+                print(url, response.readLine())
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "200\tOK",
+                "text/plain",
+                "1.2.0\tRelease notes\n",
+                "false\tattempt to use a closed file",
+                "201\tCreated",
+                "got fuel=80",
+                "137\tP\tnil",
+                "0\t137",
+                "nil\tNot Found\tNothing here",
+                "nil\tCould not connect\tnil",
+                "nil\tMust specify http or https",
+                "nil\tInvalid protocol 'ftp'",
+                "https://example.com/version.txt\t1.2.0",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    /// Serves requests like [`serve`], but only allows them to `example.com`.
+    struct ExampleOnly;
+
+    impl HttpHandler for ExampleOnly {
+        fn check(&self, url: &str) -> Result<(), String> {
+            if !url.starts_with("https://example.com/") {
+                return Err("Domain not permitted".to_string());
+            }
+
+            Ok(())
+        }
+
+        fn handle(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+            serve(request)
+        }
+    }
+
+    #[test]
+    fn test_requests_are_checked_by_the_handler() {
+        let simulator = Simulator::new().unwrap();
+        simulator.set_http_handler(ExampleOnly);
+
+        simulator
+            .exec_lua(
+                r#"
+                print(http.get("https://example.org/version.txt"))
+                http.request("https://example.org/version.txt")
+                print(os.pullEvent("http_failure"))
+                print(http.get("https://example.com/version.txt").readLine())
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "nil\tDomain not permitted\tnil",
+                "http_failure\thttps://example.org/version.txt\tDomain not permitted",
+                "1.2.0",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_requests_denied_by_default() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                print(http.get("https://example.com"))
+                print(http.checkURL("https://example.com"))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            "nil\tDomain not permitted\tnil\nfalse\tDomain not permitted\n"
+        );
+    }
+
+    #[test]
+    fn test_localhost_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(length) = line.strip_prefix("Content-Length: ") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let reply = format!(
+                "{} {}",
+                request_line.trim(),
+                String::from_utf8(body).unwrap()
+            );
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{reply}",
+                reply.len()
+            )
+            .unwrap();
+        });
+
+        let simulator = Simulator::new().unwrap();
+        simulator.set_http_handler(LocalhostHandler::default());
+
+        let reply: String = simulator
+            .eval_lua(&format!(
+                r#"
+                assert(not http.get("https://example.com"))
+                return http.post("http://localhost:{port}/status?id=1", "ok").readAll()
+                "#
+            ))
+            .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(reply, "POST /status?id=1 HTTP/1.1 ok");
+    }
+}
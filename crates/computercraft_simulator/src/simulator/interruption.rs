@@ -0,0 +1,193 @@
+use mlua::{Function, MultiValue, Table};
+
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::PowerRequest;
+use crate::{Simulator, SimulatorResult};
+
+/// The turtle functions which count as actions when deciding when to
+/// interrupt a program: the ones which move the turtle or change the world.
+pub(super) const TURTLE_ACTIONS: &[&str] = &[
+    "forward",
+    "back",
+    "up",
+    "down",
+    "turnLeft",
+    "turnRight",
+    "dig",
+    "digUp",
+    "digDown",
+    "place",
+    "placeUp",
+    "placeDown",
+    "drop",
+    "dropUp",
+    "dropDown",
+    "suck",
+    "suckUp",
+    "suckDown",
+];
+
+/// When to interrupt the running program, as happens when the turtle's chunk
+/// is unloaded or the server restarts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interruption {
+    /// Right after the given turtle action completes, counting from 1.
+    AfterAction(u64),
+    /// Right after a turtle action picked at random from the first
+    /// `max_actions`, using the seed so the choice can be reproduced.
+    Random { seed: u64, max_actions: u64 },
+}
+
+impl Simulator {
+    /// Schedules the running program to be interrupted after a turtle action,
+    /// returning the action it will be interrupted after.
+    ///
+    /// Actions are counted from when this is called. When the program is
+    /// interrupted, it stops with [`SimulatorError::Interrupted`] without being
+    /// able to catch it, and the computer can be started again with
+    /// [`Simulator::reboot`].
+    ///
+    /// [`SimulatorError::Interrupted`]: crate::SimulatorError::Interrupted
+    pub fn schedule_interruption(&self, interruption: Interruption) -> u64 {
+        let action = match interruption {
+            Interruption::AfterAction(action) => action.max(1),
+            Interruption::Random { seed, max_actions } => {
+                split_mix_64(seed) % max_actions.max(1) + 1
+            }
+        };
+
+        self.state.turtle_actions.set(0);
+        self.state.interrupt_after.set(Some(action));
+
+        action
+    }
+
+    /// Wraps the turtle's actions so they count towards a scheduled interruption.
+    pub(super) fn wrap_turtle_actions(&self, turtle_table: &Table) -> SimulatorResult<()> {
+        for &name in TURTLE_ACTIONS {
+            let action: Function = turtle_table.get(name)?;
+            let action = self.lua.create_registry_value(action)?;
+
+            turtle_table.set(
+                name,
+                self.lua.create_native_function({
+                    let state = self.state.clone();
+                    move |lua, args: MultiValue| {
+                        let action: Function = lua.registry_value(&action)?;
+                        let result: MultiValue = action.call(args)?;
+
+                        let actions = state.turtle_actions.get() + 1;
+                        state.turtle_actions.set(actions);
+                        if state.interrupt_after.get() == Some(actions) {
+                            state.interrupt_after.set(None);
+                            state.power_request.set(Some(PowerRequest::Interrupt));
+
+                            return Err(mlua::Error::RuntimeError("Interrupted".to_string()));
+                        }
+
+                        Ok(result)
+                    }
+                })?,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A step of the SplitMix64 generator, which is plenty to pick a random action
+/// from a seed.
+fn split_mix_64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::Position;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::SimulatorError;
+
+    #[test]
+    fn test_interrupt_after_action() {
+        let simulator = Simulator::new().unwrap();
+
+        assert_eq!(
+            simulator.schedule_interruption(Interruption::AfterAction(3)),
+            3
+        );
+        let result = simulator.exec_lua(
+            r#"
+                for _ = 1, 5 do
+                    -- The interruption can't be caught.
+                    print(pcall(turtle.forward))
+                end
+            "#,
+        );
+
+        assert!(matches!(result, Err(SimulatorError::Interrupted)));
+        assert_eq!(simulator.output(), "true\ttrue\tnil\ntrue\ttrue\tnil\n");
+        assert_eq!(simulator.turtle().position, Position::new(0, 0, -3));
+    }
+
+    #[test]
+    fn test_startup_resumes_after_interruption() {
+        let mut simulator = Simulator::new().unwrap();
+        simulator
+            .filesystem_mut()
+            .write_file(
+                "startup.lua",
+                r#"
+                    local step = 0
+                    if fs.exists("step.txt") then
+                        local file = fs.open("step.txt", "r")
+                        step = tonumber(file.readAll())
+                        file.close()
+                    end
+                    print("Resuming after step " .. step)
+
+                    -- Record each step before taking it, so an interrupted step
+                    -- isn't taken again.
+                    for i = step + 1, 5 do
+                        local file = fs.open("step.txt", "w")
+                        file.write(tostring(i))
+                        file.close()
+                        turtle.forward()
+                    end
+                    print("Done")
+                "#,
+            )
+            .unwrap();
+
+        simulator.schedule_interruption(Interruption::AfterAction(3));
+        let result = simulator.boot();
+
+        assert!(matches!(result, Err(SimulatorError::Interrupted)));
+        assert_eq!(simulator.turtle().position, Position::new(0, 0, -3));
+
+        simulator.reboot().unwrap();
+
+        assert_eq!(simulator.turtle().position, Position::new(0, 0, -5));
+        assert_eq!(
+            simulator.output(),
+            ["Resuming after step 0", "Resuming after step 3", "Done", "",].join("\n")
+        );
+    }
+
+    #[test]
+    fn test_random_interruption_is_reproducible() {
+        let simulator = Simulator::new().unwrap();
+        let interruption = Interruption::Random {
+            seed: 42,
+            max_actions: 10,
+        };
+
+        let action = simulator.schedule_interruption(interruption);
+        assert!((1..=10).contains(&action));
+        assert_eq!(simulator.schedule_interruption(interruption), action);
+    }
+}
@@ -0,0 +1,193 @@
+use minecraft::ItemStack;
+use mlua::{IntoLuaMulti, Lua, LuaSerdeExt, MultiValue, Value};
+
+use crate::simulator::SimulatorState;
+use crate::simulator::expect::{expect_number, expect_string};
+use crate::simulator::peripheral_api::PeripheralLocation;
+use crate::{Inventory, ItemDetail};
+
+impl SimulatorState {
+    /// Calls a method of the inventory at the location.
+    pub(super) fn call_inventory<'lua>(
+        &self,
+        lua: &'lua Lua,
+        location: &PeripheralLocation,
+        method: &str,
+        args: MultiValue<'lua>,
+    ) -> mlua::Result<MultiValue<'lua>> {
+        let mut args = args.into_iter();
+        let mut arg = || args.next().unwrap_or(Value::Nil);
+
+        let size = self.with_inventory(location, |inventory| inventory.size());
+
+        match method {
+            "size" => size.into_lua_multi(lua),
+            "list" => {
+                let list = lua.create_table()?;
+                self.with_inventory(location, |inventory| {
+                    for (slot, stack) in inventory.items() {
+                        list.raw_set(slot + 1, item_table(lua, stack, false)?)?;
+                    }
+
+                    Ok::<_, mlua::Error>(())
+                })?;
+
+                list.into_lua_multi(lua)
+            }
+            "getItemDetail" => {
+                let slot = expect_slot(1, arg(), size, "Slot")?;
+                self.with_inventory(location, |inventory| {
+                    inventory
+                        .slot(slot)
+                        .map(|stack| item_table(lua, stack, true))
+                        .transpose()
+                })?
+                .into_lua_multi(lua)
+            }
+            "getItemLimit" => {
+                let slot = expect_slot(1, arg(), size, "Slot")?;
+                self.with_inventory(location, |inventory| inventory.slot_limit(slot))
+                    .into_lua_multi(lua)
+            }
+            "pushItems" => {
+                let name = String::from_utf8_lossy(&expect_string(1, arg())?).into_owned();
+                let (to, to_size) = self.resolve_inventory(location, &name, "Target")?;
+                let from_slot = expect_slot(2, arg(), size, "From slot")?;
+                let limit = expect_limit(3, arg())?;
+                let to_slot = match arg() {
+                    Value::Nil => None,
+                    value => Some(expect_slot(4, value, to_size, "To slot")?),
+                };
+
+                self.transfer(location, from_slot, &to, to_slot, limit)
+                    .into_lua_multi(lua)
+            }
+            "pullItems" => {
+                let name = String::from_utf8_lossy(&expect_string(1, arg())?).into_owned();
+                let (from, from_size) = self.resolve_inventory(location, &name, "Source")?;
+                let from_slot = expect_slot(2, arg(), from_size, "From slot")?;
+                let limit = expect_limit(3, arg())?;
+                let to_slot = match arg() {
+                    Value::Nil => None,
+                    value => Some(expect_slot(4, value, size, "To slot")?),
+                };
+
+                self.transfer(&from, from_slot, location, to_slot, limit)
+                    .into_lua_multi(lua)
+            }
+            _ => Err(mlua::Error::RuntimeError(format!(
+                "No such method {method}"
+            ))),
+        }
+    }
+
+    /// Runs the function on the inventory at the location.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no inventory at the location.
+    fn with_inventory<R>(
+        &self,
+        location: &PeripheralLocation,
+        f: impl FnOnce(&mut Inventory) -> R,
+    ) -> R {
+        self.with_peripheral(location, |peripheral| match peripheral.inventory_mut() {
+            Some(inventory) => f(inventory),
+            None => unreachable!("the peripheral should be an inventory"),
+        })
+        .expect("the inventory should exist")
+    }
+
+    /// Finds the inventory with the name which the inventory at the location
+    /// can move items to and from, returning where it is and its size.
+    ///
+    /// Like in ComputerCraft, inventories on a wired network can only reach
+    /// the others on the same network.
+    fn resolve_inventory(
+        &self,
+        location: &PeripheralLocation,
+        name: &str,
+        role: &str,
+    ) -> mlua::Result<(PeripheralLocation, usize)> {
+        let other = match location {
+            PeripheralLocation::Side(_) => PeripheralLocation::Side(name.to_string()),
+            PeripheralLocation::Remote { network, .. } => PeripheralLocation::Remote {
+                name: name.to_string(),
+                network: network.clone(),
+            },
+        };
+
+        let size = self.with_peripheral(&other, |peripheral| {
+            peripheral.inventory_mut().map(|inventory| inventory.size())
+        });
+        match size {
+            Some(Some(size)) => Ok((other, size)),
+            Some(None) => Err(mlua::Error::RuntimeError(format!(
+                "{role} '{name}' is not an inventory"
+            ))),
+            None => Err(mlua::Error::RuntimeError(format!(
+                "{role} '{name}' does not exist"
+            ))),
+        }
+    }
+
+    /// Moves up to `limit` items out of the slot of one inventory into
+    /// another, returning how many were moved.
+    fn transfer(
+        &self,
+        from: &PeripheralLocation,
+        from_slot: usize,
+        to: &PeripheralLocation,
+        to_slot: Option<usize>,
+        limit: u32,
+    ) -> u32 {
+        let Some(stack) = self.with_inventory(from, |inventory| inventory.take(from_slot, limit))
+        else {
+            return 0;
+        };
+
+        let count = stack.count;
+        let Some(leftover) = self.with_inventory(to, |inventory| inventory.insert(stack, to_slot))
+        else {
+            return count;
+        };
+
+        // Put back what didn't fit.
+        let moved = count - leftover.count;
+        self.with_inventory(from, |inventory| {
+            inventory.insert(leftover, Some(from_slot))
+        });
+
+        moved
+    }
+}
+
+/// Describes the items like ComputerCraft's inventory methods.
+fn item_table<'lua>(
+    lua: &'lua Lua,
+    stack: &ItemStack,
+    detailed: bool,
+) -> mlua::Result<Value<'lua>> {
+    lua.to_value(&ItemDetail::new(stack, detailed))
+}
+
+/// Expects the argument to be a slot of an inventory of the given size,
+/// returning it counting from 0.
+fn expect_slot(index: usize, value: Value, size: usize, what: &str) -> mlua::Result<usize> {
+    let slot = expect_number(index, value)?;
+    if !(1.0..=size as f64).contains(&slot) {
+        return Err(mlua::Error::RuntimeError(format!(
+            "{what} out of range (between 1 and {size})"
+        )));
+    }
+
+    Ok(slot as usize - 1)
+}
+
+/// Expects the argument to be the most items to move, if there is one.
+fn expect_limit(index: usize, value: Value) -> mlua::Result<u32> {
+    match value {
+        Value::Nil => Ok(u32::MAX),
+        value => Ok(expect_number(index, value)?.max(0.0) as u32),
+    }
+}
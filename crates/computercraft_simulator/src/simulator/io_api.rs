@@ -0,0 +1,87 @@
+use mlua::Table;
+
+use crate::{Simulator, SimulatorResult};
+
+impl Simulator {
+    /// Replaces Lua's `io` library, which would access the host's filesystem,
+    /// with ComputerCraft's, which is built on top of the `fs` API.
+    pub(super) fn init_io_api(&mut self) -> SimulatorResult<()> {
+        let io_table: Table = self
+            .lua
+            .load(include_str!("../lua/io.lua"))
+            .set_name("@io.lua")
+            .call(())?;
+
+        self.lua.globals().set("io", io_table)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::Simulator;
+
+    #[test]
+    fn test_io_open_and_lines() {
+        let simulator = Simulator::new().unwrap();
+
+        let lines: Vec<String> = simulator
+            .eval_lua(
+                r#"
+                local file = assert(io.open("debug.txt", "w"))
+                file:write("first\n", 2, "\n"):write("third")
+                file:close()
+                assert(io.type(file) == "closed file")
+
+                local lines = {}
+                for line in io.lines("debug.txt") do
+                    table.insert(lines, line)
+                end
+                return lines
+                "#,
+            )
+            .unwrap();
+        assert_eq!(lines, vec!["first", "2", "third"]);
+        assert_eq!(
+            simulator.filesystem().read_file("debug.txt").unwrap(),
+            b"first\n2\nthird"
+        );
+
+        let result: (Option<String>, String) = simulator
+            .eval_lua(r#"return select(2, io.open("missing.txt")), tostring(io.type(io.stdout))"#)
+            .unwrap();
+        assert_eq!(
+            result,
+            (
+                Some("/missing.txt: No such file".to_string()),
+                "file".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_io_output() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                io.write("Hello ", "world\n")
+                io.output("log.txt")
+                io.write("logged")
+                io.close()
+                io.output(io.stdout)
+                io.write("done\n")
+                "#,
+            )
+            .unwrap();
+        assert_eq!(simulator.output(), "Hello world\ndone\n");
+        assert_eq!(
+            simulator.filesystem().read_file("log.txt").unwrap(),
+            b"logged"
+        );
+    }
+}
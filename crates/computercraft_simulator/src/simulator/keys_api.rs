@@ -0,0 +1,29 @@
+use crate::keys::KEYS;
+use crate::simulator::CreateNativeFunction;
+use crate::{Key, Simulator, SimulatorResult};
+
+impl Simulator {
+    pub(super) fn init_keys_api(&mut self) -> SimulatorResult<()> {
+        let globals = self.lua.globals();
+
+        let keys_table = self.lua.create_table()?;
+
+        for (key, name) in KEYS {
+            keys_table.set(*name, key.code())?;
+        }
+
+        // Aliases kept by ComputerCraft for backwards compatibility.
+        keys_table.set("return", Key::ENTER.code())?;
+        keys_table.set("scollLock", Key::SCROLL_LOCK.code())?;
+
+        keys_table.set(
+            "getName",
+            self.lua
+                .create_native_function(|_lua, code: u32| Ok(Key(code).name()))?,
+        )?;
+
+        globals.set("keys", keys_table)?;
+
+        Ok(())
+    }
+}
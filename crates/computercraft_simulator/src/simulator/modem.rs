@@ -0,0 +1,384 @@
+use std::cell::RefMut;
+use std::rc::Weak;
+
+use minecraft::world::Position;
+use mlua::{FromLua, IntoLuaMulti, Lua, MultiValue, Value, Variadic};
+
+use crate::simulator::expect::{expect_number, expect_string};
+use crate::simulator::peripheral_api::PeripheralLocation;
+use crate::simulator::{SharedWorld, SimulatorState};
+use crate::{CHANNEL_GPS, Event, EventValue, Modem, ModemKind, Peripheral, WiredNetwork};
+
+impl SimulatorState {
+    /// Calls a method of the modem on the side.
+    pub(super) fn call_modem<'lua>(
+        &self,
+        lua: &'lua Lua,
+        side: &str,
+        method: &str,
+        args: MultiValue<'lua>,
+    ) -> mlua::Result<MultiValue<'lua>> {
+        let mut args = args.into_iter();
+        let mut arg = || args.next().unwrap_or(Value::Nil);
+
+        let modem = || {
+            RefMut::map(
+                self.peripherals.borrow_mut(),
+                |peripherals| match peripherals.get_mut(side) {
+                    Some(Peripheral::Modem(modem)) => modem,
+                    _ => unreachable!("the peripheral should be a modem"),
+                },
+            )
+        };
+
+        let kind = modem().kind;
+        match method {
+            "open" => {
+                let channel = expect_channel(1, arg())?;
+                if !modem().open(channel) {
+                    return Err(mlua::Error::RuntimeError(
+                        "Too many open channels".to_string(),
+                    ));
+                }
+
+                ().into_lua_multi(lua)
+            }
+            "isOpen" => {
+                let channel = expect_channel(1, arg())?;
+                modem().is_open(channel).into_lua_multi(lua)
+            }
+            "close" => {
+                let channel = expect_channel(1, arg())?;
+                modem().close(channel);
+
+                ().into_lua_multi(lua)
+            }
+            "closeAll" => {
+                modem().close_all();
+
+                ().into_lua_multi(lua)
+            }
+            "isWireless" => (kind != ModemKind::Wired).into_lua_multi(lua),
+            "transmit" => {
+                let channel = expect_channel(1, arg())?;
+                let reply_channel = expect_channel(2, arg())?;
+                let payload = EventValue::from_lua(arg(), lua)?;
+
+                self.transmit(side, channel, reply_channel, payload);
+
+                ().into_lua_multi(lua)
+            }
+            _ if kind == ModemKind::Wired => {
+                self.call_wired_modem(lua, side, method, args.collect())
+            }
+            _ => Err(mlua::Error::RuntimeError(format!(
+                "No such method {method}"
+            ))),
+        }
+    }
+
+    /// Calls one of the methods only wired modems have, which use the
+    /// peripherals on the modem's network.
+    fn call_wired_modem<'lua>(
+        &self,
+        lua: &'lua Lua,
+        side: &str,
+        method: &str,
+        args: Vec<Value<'lua>>,
+    ) -> mlua::Result<MultiValue<'lua>> {
+        let network = self.wired_network(side);
+        let mut args = args.into_iter();
+        let mut arg = |index| {
+            let value = expect_string(index, args.next().unwrap_or(Value::Nil))?;
+            Ok::<_, mlua::Error>(String::from_utf8_lossy(&value).into_owned())
+        };
+        let remote = |name: String| {
+            network
+                .clone()
+                .map(|network| PeripheralLocation::Remote { name, network })
+        };
+
+        match method {
+            "getNameLocal" => network
+                .as_ref()
+                .map(|_| format!("computer_{}", self.computer.borrow().id))
+                .into_lua_multi(lua),
+            "getNamesRemote" => network
+                .as_ref()
+                .map(|network| self.remote_peripheral_names(network))
+                .unwrap_or_default()
+                .into_lua_multi(lua),
+            "isPresentRemote" => remote(arg(1)?)
+                .and_then(|location| self.with_peripheral(&location, |_| ()))
+                .is_some()
+                .into_lua_multi(lua),
+            "getTypeRemote" => remote(arg(1)?)
+                .and_then(|location| {
+                    self.with_peripheral(&location, |peripheral| {
+                        peripheral
+                            .types()
+                            .into_iter()
+                            .map(String::from)
+                            .collect::<Variadic<_>>()
+                    })
+                })
+                .unwrap_or_default()
+                .into_lua_multi(lua),
+            "hasTypeRemote" => {
+                let location = remote(arg(1)?);
+                let kind = arg(2)?;
+
+                location
+                    .and_then(|location| {
+                        self.with_peripheral(&location, |peripheral| peripheral.has_type(&kind))
+                    })
+                    .into_lua_multi(lua)
+            }
+            "getMethodsRemote" => remote(arg(1)?)
+                .and_then(|location| {
+                    self.with_peripheral(&location, |peripheral| peripheral.methods().to_vec())
+                })
+                .into_lua_multi(lua),
+            "callRemote" => {
+                let name = arg(1)?;
+                let method = arg(2)?;
+
+                match remote(name.clone()) {
+                    Some(location) if self.with_peripheral(&location, |_| ()).is_some() => {
+                        let args = MultiValue::from_vec(args.collect());
+                        self.call_peripheral(lua, &location, &method, args)
+                    }
+                    _ => Err(mlua::Error::RuntimeError(format!("No peripheral: {name}"))),
+                }
+            }
+            _ => Err(mlua::Error::RuntimeError(format!(
+                "No such method {method}"
+            ))),
+        }
+    }
+
+    /// Returns the wired network of the wired modem on the side, if it is
+    /// connected to one.
+    fn wired_network(&self, side: &str) -> Option<WiredNetwork> {
+        let position = self.side_position(side)?;
+        WiredNetwork::find(&self.shared.world.borrow(), position)
+    }
+
+    /// Sends a message from the modem on the side to every open modem it
+    /// reaches, queueing `modem_message` events on their computers.
+    ///
+    /// Wireless modems reach the wireless modems in range, and GPS hosts in
+    /// range answer `PING`s sent on [`CHANNEL_GPS`]. Wired modems reach the
+    /// wired modems on their network.
+    fn transmit(&self, side: &str, channel: u16, reply_channel: u16, payload: EventValue) {
+        let position = self.position();
+        let kind = match self.peripherals.borrow().get(side) {
+            Some(Peripheral::Modem(modem)) => modem.kind,
+            _ => return,
+        };
+
+        let is_ping = channel == CHANNEL_GPS && payload == EventValue::from("PING");
+        let message = Message {
+            channel,
+            reply_channel,
+            payload,
+        };
+
+        if kind == ModemKind::Wired {
+            let Some(network) = self.wired_network(side) else {
+                return;
+            };
+
+            self.shared
+                .deliver(&message, Some((self, side)), |computer, side, modem| {
+                    if modem.kind != ModemKind::Wired {
+                        return None;
+                    }
+
+                    let distance = network.distance_to(computer.side_position(side)?)?;
+                    Some(f64::from(distance))
+                });
+            return;
+        }
+
+        self.shared
+            .deliver(&message, Some((self, side)), |computer, _, modem| {
+                wireless_reach(position, kind, computer.position(), modem.kind)
+            });
+
+        if is_ping {
+            let hosts = self.shared.gps_hosts.borrow().clone();
+            for host in hosts {
+                if wireless_reach(position, kind, host.position, host.modem).is_none() {
+                    continue;
+                }
+
+                let Position { x, y, z } = host.position;
+                let reply = Message {
+                    channel: reply_channel,
+                    reply_channel: CHANNEL_GPS,
+                    payload: EventValue::Table(vec![
+                        (1i64.into(), i64::from(x).into()),
+                        (2i64.into(), i64::from(y).into()),
+                        (3i64.into(), i64::from(z).into()),
+                    ]),
+                };
+                self.shared.deliver(&reply, None, |computer, _, modem| {
+                    wireless_reach(host.position, host.modem, computer.position(), modem.kind)
+                });
+            }
+        }
+    }
+}
+
+/// A message sent by a modem.
+struct Message {
+    channel: u16,
+    reply_channel: u16,
+    payload: EventValue,
+}
+
+impl SharedWorld {
+    /// Queues `modem_message` events on the computers with open modems the
+    /// message reaches, other than the modem which sent it.
+    ///
+    /// The `reach` function returns the distance the message travels to the
+    /// modem on the side of the computer, if it gets there.
+    fn deliver(
+        &self,
+        message: &Message,
+        sender: Option<(&SimulatorState, &str)>,
+        reach: impl Fn(&SimulatorState, &str, &Modem) -> Option<f64>,
+    ) {
+        let computers: Vec<_> = self
+            .computers
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+        for computer in computers {
+            let mut events = Vec::new();
+            for (name, peripheral) in computer.peripherals.borrow().iter() {
+                let Peripheral::Modem(modem) = peripheral else {
+                    continue;
+                };
+                let is_sender = sender
+                    .is_some_and(|(state, side)| std::ptr::eq(&*computer, state) && name == side);
+                if is_sender || !modem.is_open(message.channel) {
+                    continue;
+                }
+
+                if let Some(distance) = reach(&computer, name, modem) {
+                    events.push(Event::new(
+                        "modem_message",
+                        vec![
+                            name.as_str().into(),
+                            u32::from(message.channel).into(),
+                            u32::from(message.reply_channel).into(),
+                            message.payload.clone(),
+                            distance.into(),
+                        ],
+                    ));
+                }
+            }
+
+            computer.events.borrow_mut().extend(events);
+        }
+    }
+}
+
+/// Returns the distance between wireless modems at the positions, if they are
+/// in range of each other.
+///
+/// Like in ComputerCraft, the range is the larger of the two modems' ranges,
+/// so it is the same in both directions.
+fn wireless_reach(
+    from: Position,
+    from_kind: ModemKind,
+    to: Position,
+    to_kind: ModemKind,
+) -> Option<f64> {
+    if from_kind == ModemKind::Wired || to_kind == ModemKind::Wired {
+        return None;
+    }
+
+    let distance = distance(from, to);
+    let range = from_kind.range(from.y).max(to_kind.range(to.y));
+
+    (distance <= range).then_some(distance)
+}
+
+/// Expects the argument to be a channel a modem can use.
+fn expect_channel(index: usize, value: Value) -> mlua::Result<u16> {
+    let channel = expect_number(index, value)?;
+    if !(0.0..=f64::from(u16::MAX)).contains(&channel) {
+        return Err(mlua::Error::RuntimeError(
+            "Channel out of range".to_string(),
+        ));
+    }
+
+    Ok(channel as u16)
+}
+
+fn distance(from: Position, to: Position) -> f64 {
+    let dx = f64::from(to.x - from.x);
+    let dy = f64::from(to.y - from.y);
+    let dz = f64::from(to.z - from.z);
+
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::Position;
+    use pretty_assertions::assert_eq;
+
+    use crate::{ComputerFamily, ComputerKind, Modem, ModemKind, Peripheral, Simulator};
+
+    #[test]
+    fn test_transmit_within_range() {
+        let sender = Simulator::new().unwrap();
+        sender.attach_peripheral("left", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        let near = sender
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        near.move_computer_to(Position::new(0, 0, 64));
+        near.attach_peripheral("back", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        let far = sender
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        far.move_computer_to(Position::new(0, 0, 65));
+        far.attach_peripheral("back", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        let high = sender
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        high.move_computer_to(Position::new(0, 319, 200));
+        high.attach_peripheral("back", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        let ender = sender
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        ender.move_computer_to(Position::new(0, 0, 1000));
+        ender.attach_peripheral("back", Peripheral::Modem(Modem::new(ModemKind::Ender)));
+        for computer in [&near, &far, &high, &ender] {
+            computer
+                .exec_lua("peripheral.call('back', 'open', 7)")
+                .unwrap();
+        }
+
+        sender
+            .exec_lua("peripheral.call('left', 'transmit', 7, 8, { text = 'hello' })")
+            .unwrap();
+
+        let receive = r#"
+            local _, side, channel, reply, message, distance = os.pullEvent("modem_message")
+            return table.concat({ side, channel, reply, message.text, distance }, " ")
+        "#;
+        assert_eq!(
+            near.eval_lua::<String>(receive).unwrap(),
+            "back 7 8 hello 64"
+        );
+        assert!(far.eval_lua::<String>(receive).is_err());
+        assert!(high.eval_lua::<String>(receive).is_ok());
+        assert!(ender.eval_lua::<String>(receive).is_ok());
+    }
+}
@@ -0,0 +1,227 @@
+use mlua::{Function, IntoLuaMulti, Lua, MultiValue, Value};
+
+use crate::simulator::SimulatorState;
+use crate::simulator::expect::expect_number;
+use crate::simulator::peripheral_api::PeripheralLocation;
+use crate::simulator::term_api::create_redirect;
+use crate::{Event, Monitor, Peripheral, Simulator};
+
+impl Simulator {
+    /// Queues a `monitor_touch` event, as if the monitor with the name was
+    /// right-clicked at the position on its screen, counting from 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no advanced monitor with the name.
+    pub fn touch_monitor(&self, name: &str, x: u32, y: u32) {
+        let is_advanced = self.with_monitor_named(name, |monitor| monitor.is_advanced());
+        assert!(is_advanced, "only advanced monitors can be touched");
+
+        self.queue_event(Event::new(
+            "monitor_touch",
+            vec![name.into(), x.into(), y.into()],
+        ));
+    }
+
+    /// Changes how many blocks the monitor with the name is made of, queueing
+    /// a `monitor_resize` event if its screen changed size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no monitor with the name, or it would be bigger
+    /// than ComputerCraft allows.
+    pub fn resize_monitor(&self, name: &str, width: u32, height: u32) {
+        if self.with_monitor_named(name, |monitor| monitor.set_size(width, height)) {
+            self.queue_event(Event::new("monitor_resize", vec![name.into()]));
+        }
+    }
+
+    /// Runs the function on the monitor attached to the computer, or placed
+    /// in the world, with the name.
+    fn with_monitor_named<R>(&self, name: &str, f: impl FnOnce(&mut Monitor) -> R) -> R {
+        let mut peripheral = self
+            .attached_peripheral(name)
+            .or_else(|| self.placed_peripheral(name));
+        match peripheral.as_deref_mut() {
+            Some(Peripheral::Monitor(monitor)) => f(monitor),
+            _ => panic!("there is no monitor named {name:?}"),
+        }
+    }
+}
+
+impl SimulatorState {
+    /// Calls a method of the monitor at the location.
+    pub(super) fn call_monitor<'lua>(
+        &self,
+        lua: &'lua Lua,
+        location: &PeripheralLocation,
+        method: &str,
+        args: MultiValue<'lua>,
+    ) -> mlua::Result<MultiValue<'lua>> {
+        match method {
+            "setTextScale" => {
+                let scale = expect_number(1, args.into_iter().next().unwrap_or(Value::Nil))?;
+                let halves = (scale * 2.0).trunc();
+                if !(1.0..=10.0).contains(&halves) {
+                    return Err(mlua::Error::RuntimeError(
+                        "Expected number in range 0.5-5".to_string(),
+                    ));
+                }
+
+                if self.with_monitor(location, |monitor| monitor.set_text_scale(halves / 2.0)) {
+                    self.events
+                        .borrow_mut()
+                        .push_back(Event::new("monitor_resize", vec![location.name().into()]));
+                }
+
+                ().into_lua_multi(lua)
+            }
+            "getTextScale" => self
+                .with_monitor(location, |monitor| monitor.text_scale())
+                .into_lua_multi(lua),
+            // The rest are the same as the methods of `term`.
+            _ => {
+                let terminal = self.with_monitor(location, |monitor| monitor.shared_terminal());
+                let redirect = create_redirect(lua, terminal)?;
+                match redirect.get::<_, Option<Function>>(method)? {
+                    Some(function) => function.call(args),
+                    None => Err(mlua::Error::RuntimeError(format!(
+                        "No such method {method}"
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Runs the function on the monitor at the location.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no monitor at the location.
+    fn with_monitor<R>(
+        &self,
+        location: &PeripheralLocation,
+        f: impl FnOnce(&mut Monitor) -> R,
+    ) -> R {
+        self.with_peripheral(location, |peripheral| match peripheral {
+            Peripheral::Monitor(monitor) => f(monitor),
+            _ => unreachable!("the peripheral should be a monitor"),
+        })
+        .expect("the monitor should exist")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::Position;
+    use minecraft::{Block, BlockId};
+    use pretty_assertions::assert_eq;
+
+    use crate::{CABLE, Modem, ModemKind, Monitor, Peripheral, Simulator, WIRED_MODEM_FULL};
+
+    fn screen(simulator: &Simulator, name: &str) -> Vec<String> {
+        let peripheral = simulator
+            .attached_peripheral(name)
+            .or_else(|| simulator.placed_peripheral(name))
+            .unwrap();
+        let Peripheral::Monitor(monitor) = &*peripheral else {
+            panic!("expected a monitor");
+        };
+
+        let terminal = monitor.terminal();
+        (0..terminal.height())
+            .map(|y| terminal.line(y).unwrap().trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_monitor() {
+        let simulator = Simulator::new().unwrap();
+        simulator.attach_peripheral("top", Peripheral::Monitor(Monitor::new(2, 1, true)));
+
+        simulator
+            .exec_lua(
+                r#"
+                local monitor = peripheral.find("monitor")
+                print(monitor.getSize())
+                print(monitor.getTextScale(), monitor.isColour())
+
+                local previous = term.redirect(monitor)
+                print("Status: all systems go")
+                term.redirect(previous)
+
+                monitor.setTextScale(0.5)
+                print(os.pullEvent("monitor_resize"))
+                print(monitor.getSize())
+                print(pcall(monitor.setTextScale, 0.4))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "18\t5",
+                "1\ttrue",
+                "Status: all systems go",
+                "monitor_resize\ttop",
+                "36\t10",
+                "false\tperipheral.lua:121: Expected number in range 0.5-5",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            &screen(&simulator, "top")[..3],
+            ["Status: all", "systems go", ""]
+        );
+
+        simulator.touch_monitor("top", 3, 4);
+        simulator.resize_monitor("top", 3, 2);
+        let events: String = simulator
+            .eval_lua(
+                r#"
+                local _, side, x, y = os.pullEvent("monitor_touch")
+                local _, resized = os.pullEvent("monitor_resize")
+                return table.concat({ side, x, y, resized }, " ")
+                "#,
+            )
+            .unwrap();
+        assert_eq!(events, "top 3 4 top");
+    }
+
+    #[test]
+    fn test_remote_monitor() {
+        let simulator = Simulator::new().unwrap();
+        simulator.attach_peripheral("back", Peripheral::Modem(Modem::new(ModemKind::Wired)));
+        simulator.set_block_at(Position::new(0, 0, 1), Block { id: CABLE });
+        simulator.set_block_at(
+            Position::new(0, 0, 2),
+            Block {
+                id: WIRED_MODEM_FULL,
+            },
+        );
+        let name = simulator.place_peripheral(
+            Position::new(0, 0, 3),
+            Block {
+                id: BlockId::new_static("computercraft:monitor_normal"),
+            },
+            Peripheral::Monitor(Monitor::new(1, 1, false)),
+        );
+        assert_eq!(name, "monitor_0");
+
+        simulator
+            .exec_lua(
+                r#"
+                local monitor = peripheral.wrap("monitor_0")
+                monitor.setCursorPos(2, 2)
+                monitor.write("Hi")
+                print(monitor.isColor())
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(simulator.output(), "false\n");
+        assert_eq!(screen(&simulator, "monitor_0"), ["", " Hi", "", "", ""]);
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use mlua::{FromLuaMulti, Function, IntoLuaMulti, Lua, MultiValue};
+
+/// An error raised by one of the simulator's native functions, along with the
+/// position of the Lua code which called it.
+///
+/// ComputerCraft's Java functions raise plain strings prefixed with that
+/// position, so this is what Lua code catching the error sees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NativeError {
+    pub message: String,
+    /// The position of the caller, such as `startup.lua:3`, if it is Lua code.
+    pub position: Option<String>,
+}
+
+impl fmt::Display for NativeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.position {
+            Some(position) => write!(f, "{position}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for NativeError {}
+
+impl NativeError {
+    /// Records where the error returned by a native function was raised,
+    /// looking up the function's caller on the Lua stack.
+    ///
+    /// Errors which already passed through a native function keep the
+    /// position they were given, unless its caller wasn't Lua code either.
+    fn raised(lua: &Lua, err: mlua::Error) -> mlua::Error {
+        let message = match find_native_error(&err) {
+            Some(NativeError {
+                position: Some(_), ..
+            }) => return err,
+            Some(native) => native.message.clone(),
+            None => error_message(&err),
+        };
+
+        mlua::Error::external(Self {
+            message,
+            position: caller_position(lua),
+        })
+    }
+}
+
+/// Creates native functions which raise [`NativeError`]s.
+pub(crate) trait CreateNativeFunction {
+    /// Like [`Lua::create_function`], but the errors the function raises
+    /// record the position of the Lua code which called it.
+    fn create_native_function<'lua, A, R, F>(&'lua self, func: F) -> mlua::Result<Function<'lua>>
+    where
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+        F: Fn(&'lua Lua, A) -> mlua::Result<R> + 'static;
+}
+
+impl CreateNativeFunction for Lua {
+    fn create_native_function<'lua, A, R, F>(&'lua self, func: F) -> mlua::Result<Function<'lua>>
+    where
+        A: FromLuaMulti<'lua>,
+        R: IntoLuaMulti<'lua>,
+        F: Fn(&'lua Lua, A) -> mlua::Result<R> + 'static,
+    {
+        self.create_function(move |lua, args: MultiValue<'lua>| {
+            A::from_lua_multi(args, lua)
+                .and_then(|args| func(lua, args))
+                .map_err(|err| NativeError::raised(lua, err))
+        })
+    }
+}
+
+/// Returns the position of the code which called the running native
+/// function, if it is Lua code.
+fn caller_position(lua: &Lua) -> Option<String> {
+    // Level 0 is the native function itself, and level 1 is its caller.
+    let caller = lua.inspect_stack(1)?;
+    let line = caller.curr_line();
+    let source = caller.source();
+    if source.what == "C" || line < 0 {
+        return None;
+    }
+
+    Some(format!("{}:{line}", source.short_src?))
+}
+
+/// Returns the [`NativeError`] the error was caused by, if it was raised by
+/// a native function.
+fn find_native_error(err: &mlua::Error) -> Option<&NativeError> {
+    match err {
+        mlua::Error::CallbackError { cause, .. } => find_native_error(cause),
+        err => err.downcast_ref(),
+    }
+}
+
+/// Returns the message of the error without any of the context mlua adds.
+fn error_message(err: &mlua::Error) -> String {
+    match err {
+        mlua::Error::CallbackError { cause, .. } => error_message(cause),
+        mlua::Error::RuntimeError(message) => message.clone(),
+        err => err.to_string(),
+    }
+}
+
+/// Returns the message for an error raised by one of the simulator's native
+/// functions, prefixed with the position of the Lua code that called it.
+///
+/// This matches the errors raised by ComputerCraft's Java functions, which are
+/// plain strings rather than Rust error values.
+pub(crate) fn native_error_message(err: &mlua::Error) -> String {
+    match find_native_error(err) {
+        Some(native) => native.to_string(),
+        None => error_message(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::Simulator;
+
+    #[test]
+    fn test_native_errors_record_their_caller() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                local fn = load("term.setTextColour(0)", "@colours.lua")
+                print(pcall(fn))
+                print(pcall(term.setTextColour, 0))
+                print(pcall(fs.delete))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "false\tcolours.lua:1: Colour out of range",
+                "false\tColour out of range",
+                "false\tbad argument #1 (string expected, got nil)",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use mlua::{Value, Variadic};
+
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::PowerRequest;
+use crate::simulator::expect::{bad_argument, expect_number};
+use crate::{Event, EventValue, Simulator, SimulatorResult};
+
+/// The number of ticks in a second of Minecraft time.
+const TICKS_PER_SECOND: u64 = 20;
+
+impl Simulator {
+    pub(super) fn init_os_api(&mut self) -> SimulatorResult<()> {
+        let globals = self.lua.globals();
+
+        let os_table: mlua::Table = globals.get("os")?;
+
+        os_table.set(
+            "queueEvent",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, (name, args): (Value, Variadic<EventValue>)| {
+                    let Value::String(name) = name else {
+                        return Err(bad_argument(1, "string", &name));
+                    };
+
+                    state.events.borrow_mut().push_back(Event::new(
+                        name.to_string_lossy(),
+                        args.into_iter().collect(),
+                    ));
+
+                    Ok(())
+                }
+            })?,
+        )?;
+
+        // Time only passes as turtles act, or when every program is waiting,
+        // so the clock counts ticks rather than real time.
+        os_table.set(
+            "clock",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, ()| {
+                    let ticks = state.shared.ticks.get() - state.started_at.get();
+                    Ok(ticks as f64 / TICKS_PER_SECOND as f64)
+                }
+            })?,
+        )?;
+
+        os_table.set(
+            "startTimer",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, timer: Value| {
+                    let seconds = expect_number(1, timer)?;
+                    let ticks = (seconds * TICKS_PER_SECOND as f64).ceil().max(1.0) as u64;
+
+                    let id = state.next_timer_id.get();
+                    state.next_timer_id.set(id + 1);
+                    state
+                        .timers
+                        .borrow_mut()
+                        .insert(id, state.shared.ticks.get() + ticks);
+
+                    Ok(id)
+                }
+            })?,
+        )?;
+
+        os_table.set(
+            "cancelTimer",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, id: Value| {
+                    let id = expect_number(1, id)?;
+                    state.timers.borrow_mut().remove(&(id as u32));
+                    Ok(())
+                }
+            })?,
+        )?;
+
+        let get_computer_id = self.lua.create_native_function({
+            let state = self.state.clone();
+            move |_lua, ()| Ok(state.computer.borrow().id)
+        })?;
+        os_table.set("getComputerID", get_computer_id.clone())?;
+        os_table.set("computerID", get_computer_id)?;
+
+        // Labels are made of ComputerCraft's characters, which map one-to-one
+        // onto the first 256 code points.
+        let get_computer_label = self.lua.create_native_function({
+            let state = self.state.clone();
+            move |lua, ()| {
+                let computer = state.computer.borrow();
+                let Some(label) = &computer.label else {
+                    return Ok(Value::Nil);
+                };
+
+                let bytes: Vec<u8> = label.chars().map(|char| char as u8).collect();
+                Ok(Value::String(lua.create_string(bytes)?))
+            }
+        })?;
+        os_table.set("getComputerLabel", get_computer_label.clone())?;
+        os_table.set("computerLabel", get_computer_label)?;
+
+        os_table.set(
+            "setComputerLabel",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, label: Value| {
+                    let label = match label {
+                        Value::Nil => None,
+                        Value::String(label) => Some(
+                            label
+                                .as_bytes()
+                                .iter()
+                                .map(|&byte| char::from(byte))
+                                .collect::<String>(),
+                        ),
+                        label => return Err(bad_argument(1, "string or nil", &label)),
+                    };
+
+                    state.computer.borrow_mut().set_label(label.as_deref());
+                    Ok(())
+                }
+            })?,
+        )?;
+
+        // These only request that the computer turns off. `bios.lua` wraps them
+        // to stop the program, which is when the simulator acts on the request.
+        os_table.set(
+            "shutdown",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, ()| {
+                    state.power_request.set(Some(PowerRequest::Shutdown));
+                    Ok(())
+                }
+            })?,
+        )?;
+
+        os_table.set(
+            "reboot",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, ()| {
+                    state.power_request.set(Some(PowerRequest::Reboot));
+                    Ok(())
+                }
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::Simulator;
+
+    #[test]
+    fn test_timers_and_sleep() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                local first = os.startTimer(2)
+                local cancelled = os.startTimer(1)
+                os.cancelTimer(cancelled)
+                print(os.clock(), select(2, os.pullEvent("timer")) == first, os.clock())
+                sleep(0.5)
+                print(os.clock())
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(simulator.output(), "0\ttrue\t2\n2.5\n");
+    }
+}
@@ -0,0 +1,433 @@
+use std::cell::RefMut;
+
+use minecraft::Block;
+use minecraft::world::Position;
+use mlua::{Function, Lua, MultiValue, Table, Value, Variadic};
+
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::expect::expect_string;
+use crate::simulator::{PlacedPeripheral, SimulatorState};
+use crate::{Event, Peripheral, Simulator, SimulatorResult, WiredNetwork};
+
+impl Simulator {
+    /// Sets up the `peripheral` API: the native functions which act on a
+    /// single peripheral, with ComputerCraft's `peripheral` module on top.
+    pub(super) fn init_peripheral_api(&mut self) -> SimulatorResult<()> {
+        let native = self.lua.create_table()?;
+
+        native.set(
+            "isPresent",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, name: Value| {
+                    let name = String::from_utf8_lossy(&expect_string(1, name)?).into_owned();
+                    Ok(state.peripherals.borrow().contains_key(&name))
+                }
+            })?,
+        )?;
+
+        native.set(
+            "getType",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, name: Value| {
+                    let name = String::from_utf8_lossy(&expect_string(1, name)?).into_owned();
+                    Ok(state
+                        .with_peripheral(&PeripheralLocation::Side(name), |peripheral| {
+                            peripheral
+                                .types()
+                                .into_iter()
+                                .map(String::from)
+                                .collect::<Variadic<_>>()
+                        })
+                        .unwrap_or_default())
+                }
+            })?,
+        )?;
+
+        native.set(
+            "hasType",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, (name, kind): (Value, Value)| {
+                    let name = String::from_utf8_lossy(&expect_string(1, name)?).into_owned();
+                    let kind = String::from_utf8_lossy(&expect_string(2, kind)?).into_owned();
+                    Ok(
+                        state.with_peripheral(&PeripheralLocation::Side(name), |peripheral| {
+                            peripheral.has_type(&kind)
+                        }),
+                    )
+                }
+            })?,
+        )?;
+
+        native.set(
+            "getMethods",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, name: Value| {
+                    let name = String::from_utf8_lossy(&expect_string(1, name)?).into_owned();
+                    Ok(
+                        state.with_peripheral(&PeripheralLocation::Side(name), |peripheral| {
+                            peripheral.methods().to_vec()
+                        }),
+                    )
+                }
+            })?,
+        )?;
+
+        native.set(
+            "call",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |lua, (name, method, args): (Value, Value, Variadic<Value>)| {
+                    let name = String::from_utf8_lossy(&expect_string(1, name)?).into_owned();
+                    let method = String::from_utf8_lossy(&expect_string(2, method)?).into_owned();
+                    let args = MultiValue::from_vec(args.into_iter().collect());
+
+                    state.call_peripheral(lua, &PeripheralLocation::Side(name), &method, args)
+                }
+            })?,
+        )?;
+
+        let make_peripheral: Function = self
+            .lua
+            .load(include_str!("../lua/peripheral.lua"))
+            .set_name("@peripheral.lua")
+            .into_function()?;
+        let peripheral_table: Table = make_peripheral.call(native)?;
+
+        self.lua.globals().set("peripheral", peripheral_table)?;
+
+        Ok(())
+    }
+
+    /// Places the peripheral in the world, such as a chest, along with its
+    /// block. Computers can use it over a wired network once it is next to a
+    /// full-block wired modem.
+    ///
+    /// Returns the peripheral's name, made from its type like in
+    /// ComputerCraft, such as `minecraft:chest_0`.
+    pub fn place_peripheral(
+        &self,
+        position: Position,
+        block: Block,
+        peripheral: Peripheral,
+    ) -> String {
+        let kind = peripheral.types()[0].to_string();
+        let mut counts = self.state.shared.peripheral_counts.borrow_mut();
+        let count = counts.entry(kind.clone()).or_default();
+        let name = format!("{kind}_{count}");
+        *count += 1;
+
+        self.set_block_at(position, block);
+        self.state.shared.peripherals.borrow_mut().insert(
+            name.clone(),
+            PlacedPeripheral {
+                position,
+                peripheral,
+            },
+        );
+
+        name
+    }
+
+    /// Returns the peripheral placed in the world with the name.
+    pub fn placed_peripheral(&self, name: &str) -> Option<RefMut<'_, Peripheral>> {
+        RefMut::filter_map(self.state.shared.peripherals.borrow_mut(), |peripherals| {
+            peripherals
+                .get_mut(name)
+                .map(|placed| &mut placed.peripheral)
+        })
+        .ok()
+    }
+
+    /// Returns the peripheral attached to the side of the computer.
+    pub fn attached_peripheral(&self, side: &str) -> Option<RefMut<'_, Peripheral>> {
+        RefMut::filter_map(self.state.peripherals.borrow_mut(), |peripherals| {
+            peripherals.get_mut(side)
+        })
+        .ok()
+    }
+
+    /// Attaches the peripheral to the side of the computer, returning the
+    /// peripheral which was there before.
+    ///
+    /// Like in ComputerCraft, this queues a `peripheral` event.
+    pub fn attach_peripheral(&self, side: &str, peripheral: Peripheral) -> Option<Peripheral> {
+        self.state.unmount_disk(side);
+        let previous = self
+            .state
+            .peripherals
+            .borrow_mut()
+            .insert(side.to_string(), peripheral);
+        self.state.mount_disk(side);
+        self.queue_event(Event::new("peripheral", vec![side.into()]));
+
+        previous
+    }
+
+    /// Detaches the peripheral from the side of the computer, queueing a
+    /// `peripheral_detach` event if there was one.
+    pub fn detach_peripheral(&self, side: &str) -> Option<Peripheral> {
+        self.state.unmount_disk(side);
+        let peripheral = self.state.peripherals.borrow_mut().remove(side)?;
+        self.queue_event(Event::new("peripheral_detach", vec![side.into()]));
+
+        Some(peripheral)
+    }
+}
+
+/// Where a peripheral a computer uses is.
+#[derive(Debug, Clone)]
+pub(super) enum PeripheralLocation {
+    /// Attached to the side of the computer.
+    Side(String),
+    /// Placed in the world, on the wired network of one of the computer's
+    /// modems.
+    Remote { name: String, network: WiredNetwork },
+}
+
+impl PeripheralLocation {
+    /// Returns the name the computer knows the peripheral by.
+    pub(super) fn name(&self) -> &str {
+        match self {
+            Self::Side(name) | Self::Remote { name, .. } => name,
+        }
+    }
+}
+
+impl SimulatorState {
+    /// Runs the function on the peripheral at the location, returning `None`
+    /// if there is no peripheral there.
+    pub(super) fn with_peripheral<R>(
+        &self,
+        location: &PeripheralLocation,
+        f: impl FnOnce(&mut Peripheral) -> R,
+    ) -> Option<R> {
+        match location {
+            PeripheralLocation::Side(side) => self.peripherals.borrow_mut().get_mut(side).map(f),
+            PeripheralLocation::Remote { name, network } => {
+                let world = self.shared.world.borrow();
+                let mut peripherals = self.shared.peripherals.borrow_mut();
+                let placed = peripherals.get_mut(name)?;
+                network.peripheral_distance(&world, placed.position)?;
+
+                Some(f(&mut placed.peripheral))
+            }
+        }
+    }
+
+    /// Returns the names of the peripherals on the network, in sorted order.
+    pub(super) fn remote_peripheral_names(&self, network: &WiredNetwork) -> Vec<String> {
+        let world = self.shared.world.borrow();
+        self.shared
+            .peripherals
+            .borrow()
+            .iter()
+            .filter(|(_, placed)| {
+                network
+                    .peripheral_distance(&world, placed.position)
+                    .is_some()
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Calls a method of the peripheral at the location.
+    pub(super) fn call_peripheral<'lua>(
+        &self,
+        lua: &'lua Lua,
+        location: &PeripheralLocation,
+        method: &str,
+        args: MultiValue<'lua>,
+    ) -> mlua::Result<MultiValue<'lua>> {
+        enum Kind {
+            Modem,
+            Inventory,
+            Monitor,
+            DiskDrive,
+            Printer,
+            Speaker,
+        }
+
+        let kind = self.with_peripheral(location, |peripheral| match peripheral {
+            Peripheral::Modem(_) => Kind::Modem,
+            Peripheral::Inventory(_) => Kind::Inventory,
+            Peripheral::Monitor(_) => Kind::Monitor,
+            Peripheral::DiskDrive(_) => Kind::DiskDrive,
+            Peripheral::Printer(_) => Kind::Printer,
+            Peripheral::Speaker(_) => Kind::Speaker,
+        });
+
+        match (kind, location) {
+            (None, _) => Err(mlua::Error::RuntimeError(
+                "No peripheral attached".to_string(),
+            )),
+            (Some(Kind::Modem), PeripheralLocation::Side(side)) => {
+                self.call_modem(lua, side, method, args)
+            }
+            // Modems only work attached to a computer.
+            (Some(Kind::Modem), PeripheralLocation::Remote { .. }) => Err(
+                mlua::Error::RuntimeError(format!("No such method {method}")),
+            ),
+            (Some(Kind::Inventory), _) => self.call_inventory(lua, location, method, args),
+            (Some(Kind::Monitor), _) => self.call_monitor(lua, location, method, args),
+            (Some(Kind::DiskDrive), _) => self.call_drive(lua, location, method, args),
+            (Some(Kind::Printer), _) => self.call_printer(lua, location, method, args),
+            (Some(Kind::Speaker), _) => self.call_speaker(lua, location, method, args),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::Position;
+    use minecraft::{Block, ItemId, ItemStack};
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        CABLE, ComputerFamily, ComputerKind, Inventory, Modem, ModemKind, Peripheral, Simulator,
+        WIRED_MODEM_FULL,
+    };
+
+    #[test]
+    fn test_peripheral_api() {
+        let simulator = Simulator::new().unwrap();
+        simulator.attach_peripheral("left", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        simulator.attach_peripheral("top", Peripheral::Modem(Modem::new(ModemKind::Ender)));
+
+        simulator
+            .exec_lua(
+                r##"
+                print(table.concat(peripheral.getNames(), ","), peripheral.isPresent("right"))
+                print(peripheral.getType("left"), peripheral.hasType("left", "modem"), peripheral.hasType("right", "modem"))
+
+                local modem = peripheral.wrap("left")
+                modem.open(5)
+                print(peripheral.getName(modem), peripheral.getType(modem), modem.isOpen(5), peripheral.call("left", "isOpen", 6))
+                print(select("#", peripheral.find("modem")), peripheral.wrap("right"))
+                print(pcall(peripheral.call, "right", "open", 1))
+                print(pcall(modem.open, 70000))
+                "##,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "top,left\tfalse",
+                "modem\ttrue\tnil",
+                "left\tmodem\ttrue\tfalse",
+                "2\tnil",
+                "false\tperipheral.lua:121: No peripheral attached",
+                "false\tperipheral.lua:121: Channel out of range",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_wired_network() {
+        let turtle = Simulator::new().unwrap();
+        turtle.attach_peripheral("back", Peripheral::Modem(Modem::new(ModemKind::Wired)));
+        for z in 1..4 {
+            turtle.set_block_at(Position::new(0, 0, z), Block { id: CABLE });
+        }
+        turtle.set_block_at(
+            Position::new(0, 0, 4),
+            Block {
+                id: WIRED_MODEM_FULL,
+            },
+        );
+
+        let chest = Block {
+            id: Inventory::chest().block,
+        };
+        let first = turtle.place_peripheral(
+            Position::new(1, 0, 4),
+            chest.clone(),
+            Peripheral::Inventory(Inventory::chest()),
+        );
+        let second = turtle.place_peripheral(
+            Position::new(-1, 0, 4),
+            chest.clone(),
+            Peripheral::Inventory(Inventory::chest()),
+        );
+        turtle.place_peripheral(
+            Position::new(5, 0, 5),
+            chest,
+            Peripheral::Inventory(Inventory::chest()),
+        );
+        assert_eq!(
+            (first.as_str(), second.as_str()),
+            ("minecraft:chest_0", "minecraft:chest_1")
+        );
+
+        if let Peripheral::Inventory(inventory) = &mut *turtle.placed_peripheral(&first).unwrap() {
+            inventory.set_slot(
+                0,
+                Some(ItemStack::new(
+                    ItemId::new_static("minecraft:cobblestone"),
+                    10,
+                )),
+            );
+        }
+
+        let computer = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        computer.move_computer_to(Position::new(0, 1, 2));
+        computer.attach_peripheral("bottom", Peripheral::Modem(Modem::new(ModemKind::Wired)));
+        computer.attach_peripheral("top", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        computer
+            .exec_lua("peripheral.call('bottom', 'open', 3) peripheral.call('top', 'open', 3)")
+            .unwrap();
+
+        turtle
+            .exec_lua(
+                r#"
+                print(table.concat(peripheral.getNames(), ","))
+                local modem = peripheral.wrap("back")
+                print(modem.getNameLocal(), modem.isWireless(), peripheral.getType("minecraft:chest_1"))
+
+                local chest = peripheral.wrap("minecraft:chest_0")
+                print(chest.size(), chest.pushItems("minecraft:chest_1", 1, 4))
+                print(chest.list()[1].count, peripheral.call("minecraft:chest_1", "getItemDetail", 1).count)
+                print(pcall(chest.pushItems, "minecraft:chest_2", 1))
+                print(pcall(modem.callRemote, "minecraft:chest_2", "size"))
+                print(pcall(chest.getItemDetail, 28))
+
+                modem.transmit(3, 4, "over the wire")
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            turtle.output(),
+            [
+                "back,minecraft:chest_0,minecraft:chest_1",
+                "computer_0\tfalse\tminecraft:chest\tinventory",
+                "27\t4",
+                "6\t4",
+                "false\tperipheral.lua:117: Target 'minecraft:chest_2' does not exist",
+                "false\tperipheral.lua:121: No peripheral: minecraft:chest_2",
+                "false\tperipheral.lua:117: Slot out of range (between 1 and 27)",
+                "",
+            ]
+            .join("\n")
+        );
+
+        let message: String = computer
+            .eval_lua(
+                r#"
+                local _, side, channel, reply, message, distance = os.pullEvent("modem_message")
+                return table.concat({ side, channel, reply, message, distance }, " ")
+                "#,
+            )
+            .unwrap();
+        assert_eq!(message, "bottom 3 4 over the wire 1");
+        assert!(!computer.has_pending_events());
+    }
+}
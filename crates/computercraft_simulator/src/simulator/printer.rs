@@ -0,0 +1,218 @@
+use mlua::{IntoLuaMulti, Lua, MultiValue, Value};
+
+use crate::simulator::SimulatorState;
+use crate::simulator::expect::{expect_number, expect_text};
+use crate::simulator::peripheral_api::PeripheralLocation;
+use crate::{Peripheral, Printer};
+
+impl SimulatorState {
+    /// Calls a method of the printer at the location.
+    pub(super) fn call_printer<'lua>(
+        &self,
+        lua: &'lua Lua,
+        location: &PeripheralLocation,
+        method: &str,
+        args: MultiValue<'lua>,
+    ) -> mlua::Result<MultiValue<'lua>> {
+        match method {
+            "newPage" => self
+                .with_printer(location, |printer| printer.new_page())
+                .into_lua_multi(lua),
+            "endPage" => self
+                .with_page(location, |printer| printer.end_page())?
+                .into_lua_multi(lua),
+            "write" => {
+                let mut text = Vec::new();
+                for (index, value) in args.into_iter().enumerate() {
+                    text.extend(expect_text(lua, index + 1, value)?);
+                }
+
+                let text = String::from_utf8_lossy(&text);
+                self.with_page(location, |printer| printer.write(&text))?;
+
+                ().into_lua_multi(lua)
+            }
+            "getCursorPos" => {
+                let (x, y) = self
+                    .with_printer(location, |printer| printer.cursor_pos())
+                    .ok_or_else(page_not_started)?;
+
+                (x + 1, y + 1).into_lua_multi(lua)
+            }
+            "setCursorPos" => {
+                let mut args = args.into_iter();
+                let x = expect_number(1, args.next().unwrap_or(Value::Nil))?;
+                let y = expect_number(2, args.next().unwrap_or(Value::Nil))?;
+
+                self.with_page(location, |printer| {
+                    printer.set_cursor_pos(x as i32 - 1, y as i32 - 1)
+                })?;
+
+                ().into_lua_multi(lua)
+            }
+            "getPageSize" => {
+                self.with_page(location, |_| ())?;
+                (Printer::PAGE_WIDTH, Printer::PAGE_HEIGHT).into_lua_multi(lua)
+            }
+            "setPageTitle" => {
+                let title = match args.into_iter().next().unwrap_or(Value::Nil) {
+                    Value::Nil => Vec::new(),
+                    value => expect_text(lua, 1, value)?,
+                };
+
+                let title = String::from_utf8_lossy(&title);
+                self.with_page(location, |printer| printer.set_page_title(&title))?;
+
+                ().into_lua_multi(lua)
+            }
+            "getInkLevel" => self
+                .with_printer(location, |printer| printer.ink_level())
+                .into_lua_multi(lua),
+            "getPaperLevel" => self
+                .with_printer(location, |printer| printer.paper_level())
+                .into_lua_multi(lua),
+            // The rest are the methods of inventories.
+            _ => self.call_inventory(lua, location, method, args),
+        }
+    }
+
+    /// Runs the function on the printer at the location, if it is printing a
+    /// page.
+    fn with_page<R>(
+        &self,
+        location: &PeripheralLocation,
+        f: impl FnOnce(&mut Printer) -> R,
+    ) -> mlua::Result<R> {
+        self.with_printer(location, |printer| {
+            if !printer.is_printing() {
+                return Err(page_not_started());
+            }
+
+            Ok(f(printer))
+        })
+    }
+
+    /// Runs the function on the printer at the location.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no printer at the location.
+    fn with_printer<R>(
+        &self,
+        location: &PeripheralLocation,
+        f: impl FnOnce(&mut Printer) -> R,
+    ) -> R {
+        self.with_peripheral(location, |peripheral| match peripheral {
+            Peripheral::Printer(printer) => f(printer),
+            _ => unreachable!("the peripheral should be a printer"),
+        })
+        .expect("the printer should exist")
+    }
+}
+
+fn page_not_started() -> mlua::Error {
+    mlua::Error::RuntimeError("Page not started".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::{ItemId, ItemStack};
+    use pretty_assertions::assert_eq;
+
+    use crate::{PAPER, Peripheral, Printer, Simulator};
+
+    #[test]
+    fn test_printer() {
+        let simulator = Simulator::new().unwrap();
+        let mut printer = Printer::new();
+        printer.insert(ItemStack::new(ItemId::new_static("minecraft:black_dye"), 5));
+        printer.insert(ItemStack::new(PAPER, 3));
+        simulator.attach_peripheral("right", Peripheral::Printer(printer));
+
+        simulator
+            .exec_lua(
+                r#"
+                local printer = peripheral.find("printer")
+                print(pcall(printer.write, "Too soon"))
+                print(printer.getInkLevel(), printer.getPaperLevel())
+
+                print(printer.newPage())
+                print(printer.getPageSize())
+                printer.setPageTitle("Shopping list")
+                printer.write("- Eggs")
+                printer.setCursorPos(1, 2)
+                printer.write("- Milk x", 2)
+                print(printer.getCursorPos())
+                print(printer.endPage())
+                print(printer.getInkLevel(), printer.getPaperLevel())
+
+                local page = printer.getItemDetail(8)
+                print(page.name, page.count, page.title)
+                print(page.lines[1])
+                print(page.lines[2])
+                print(#page.lines)
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "false\tperipheral.lua:121: Page not started",
+                "5\t3",
+                "true",
+                "25\t21",
+                "10\t2",
+                "true",
+                "4\t2",
+                "computercraft:printed_page\t1\tShopping list",
+                "- Eggs                   ",
+                "- Milk x2                ",
+                "21",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_turtle_get_printed_page_detail() {
+        let turtle = Simulator::new().unwrap();
+        let mut printer = Printer::new();
+        printer.insert(ItemStack::new(ItemId::new_static("minecraft:black_dye"), 1));
+        printer.insert(ItemStack::new(PAPER, 1));
+        turtle.attach_peripheral("front", Peripheral::Printer(printer));
+
+        turtle
+            .exec_lua(
+                r#"
+                local printer = peripheral.wrap("front")
+                printer.newPage()
+                printer.setPageTitle("Notes")
+                printer.write("Hello")
+                printer.endPage()
+                turtle.suck()
+
+                local page = turtle.getItemDetail(1, true)
+                print(page.name, page.count, page.maxCount, page.title)
+                print(page.lines[1])
+                print(#page.lines)
+                page = turtle.getItemDetail(1)
+                print(page.name, page.maxCount, page.title, page.lines)
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            turtle.output(),
+            [
+                "computercraft:printed_page\t1\t1\tNotes",
+                "Hello                    ",
+                "21",
+                "computercraft:printed_page\tnil\tnil\tnil",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use mlua::{Function, IntoLuaMulti, Table};
+
+use crate::{Event, Simulator, SimulatorResult};
+
+/// The name of the registry value holding the function which handles events
+/// for rednet, as `rednet.run` does in ComputerCraft.
+const REDNET_HANDLER: &str = "rednet.handle_event";
+
+impl Simulator {
+    /// Sets up the `rednet` API.
+    pub(super) fn init_rednet_api(&mut self) -> SimulatorResult<()> {
+        let (rednet_table, handle_event): (Table, Function) = self
+            .lua
+            .load(include_str!("../lua/rednet.lua"))
+            .set_name("@rednet.lua")
+            .call(())?;
+
+        self.lua.globals().set("rednet", rednet_table)?;
+        self.lua
+            .set_named_registry_value(REDNET_HANDLER, handle_event)?;
+
+        Ok(())
+    }
+
+    /// Hands the event to rednet, which runs alongside every program like in
+    /// ComputerCraft, so it sees every event whatever the program waits for.
+    pub(super) fn run_rednet(&self, event: &Event) -> SimulatorResult<()> {
+        let handle_event: Function = self.lua.named_registry_value(REDNET_HANDLER)?;
+        handle_event.call::<_, ()>(event.clone().into_lua_multi(&self.lua)?)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::Position;
+    use pretty_assertions::assert_eq;
+
+    use crate::{ComputerFamily, ComputerKind, Fleet, Modem, ModemKind, Peripheral, Simulator};
+
+    #[test]
+    fn test_send_and_receive() {
+        let turtle = Simulator::new().unwrap();
+        turtle.attach_peripheral("left", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        let server = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        server.attach_peripheral("top", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        let other = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        other.attach_peripheral("top", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+
+        turtle
+            .filesystem_mut()
+            .write_file(
+                "client.lua",
+                r#"
+                rednet.open("left")
+                sleep(3)
+                local server = rednet.lookup("jobs", "server")
+                print("found", server)
+                rednet.send(server, "job?", "jobs")
+                print(rednet.receive("jobs"))
+                print(rednet.receive(1))
+                "#,
+            )
+            .unwrap();
+        server
+            .filesystem_mut()
+            .write_file(
+                "server.lua",
+                r#"
+                rednet.open("top")
+                rednet.host("jobs", "server")
+                local id, message = rednet.receive("jobs")
+                print(id, message)
+                rednet.send(id, "dig", "jobs")
+                "#,
+            )
+            .unwrap();
+        other
+            .filesystem_mut()
+            .write_file(
+                "other.lua",
+                r#"
+                rednet.open("top")
+                print(rednet.receive("jobs", 5))
+                "#,
+            )
+            .unwrap();
+
+        let mut fleet = Fleet::new(vec![turtle, server, other]);
+        fleet.start_program(1, "server", &[]).unwrap();
+        fleet.start_program(2, "other", &[]).unwrap();
+        fleet.start_program(0, "client", &[]).unwrap();
+        fleet.run().unwrap();
+
+        let output: Vec<_> = fleet
+            .computers()
+            .iter()
+            .map(|computer| computer.output())
+            .collect();
+        assert_eq!(
+            output,
+            ["found\t1\n1\tdig\tjobs\nnil\n", "0\tjob?\n", "nil\n",]
+        );
+    }
+
+    #[test]
+    fn test_repeater_reaches_computers_out_of_range() {
+        let sender = Simulator::new().unwrap();
+        sender.attach_peripheral("left", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        let repeater = sender
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        repeater.attach_peripheral("top", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        repeater.move_computer_to(Position::new(0, 0, 60));
+        let receiver = sender
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        receiver.attach_peripheral("top", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        receiver.move_computer_to(Position::new(0, 0, 120));
+
+        sender
+            .filesystem_mut()
+            .write_file(
+                "send.lua",
+                r#"
+                rednet.open("left")
+                sleep(1)
+                print(rednet.send(2, "hello"))
+                "#,
+            )
+            .unwrap();
+        receiver
+            .filesystem_mut()
+            .write_file(
+                "receive.lua",
+                r#"
+                rednet.open("top")
+                print(rednet.receive(5))
+                print(rednet.receive(1))
+                "#,
+            )
+            .unwrap();
+
+        let mut fleet = Fleet::new(vec![sender, repeater, receiver]);
+        fleet.start_program(1, "repeat", &[]).unwrap();
+        fleet.start_program(2, "receive", &[]).unwrap();
+        fleet.start_program(0, "send", &[]).unwrap();
+        // The repeater runs forever, so run until the receiver gives up.
+        while fleet.computers()[2].output().lines().count() < 2 {
+            fleet.tick().unwrap();
+        }
+
+        let output: Vec<_> = fleet
+            .computers()
+            .iter()
+            .map(|computer| computer.output())
+            .collect();
+        assert_eq!(
+            output,
+            [
+                "true\n",
+                "1 modem found.\n0 messages repeated.\n1 message repeated.\n",
+                "0\thello\tnil\nnil\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_messages_are_not_received() {
+        let sender = Simulator::new().unwrap();
+        sender.attach_peripheral("left", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        let receiver = sender
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        receiver.attach_peripheral("top", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+        receiver.move_computer_to(Position::new(0, 0, 120));
+
+        sender
+            .exec_lua(r#"rednet.open("left") rednet.send(1, "hello")"#)
+            .unwrap();
+
+        assert_eq!(
+            receiver
+                .eval_lua::<Option<String>>(
+                    r#"rednet.open("top") return (select(2, rednet.receive(1)))"#
+                )
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_repeated_messages_are_received_once() {
+        let simulator = Simulator::new().unwrap();
+        simulator.attach_peripheral("left", Peripheral::Modem(Modem::new(ModemKind::Wireless)));
+
+        simulator
+            .exec_lua(
+                r#"
+                rednet.open("left")
+                local message = { nMessageID = 7, nRecipient = 0, nSender = 3, message = "hi" }
+                os.queueEvent("modem_message", "left", 0, 3, message, 5)
+                os.queueEvent("modem_message", "left", 0, 3, message, 5)
+                os.queueEvent("modem_message", "left", 65533, 3, message, 5)
+                print(rednet.receive())
+                print(rednet.receive(1))
+                print(pcall(rednet.run))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            "3\thi\tnil\nnil\nfalse\trednet is already running\n"
+        );
+    }
+}
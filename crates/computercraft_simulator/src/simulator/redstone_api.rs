@@ -0,0 +1,421 @@
+use minecraft::world::Position;
+use mlua::Value;
+
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::expect::{bad_argument, expect_number, expect_string};
+use crate::simulator::{SharedWorld, SimulatorState};
+use crate::{Event, RedstoneSignals, SIDES, Simulator, SimulatorResult, side_index};
+
+impl Simulator {
+    /// Sets up the `redstone` API, also available as `rs`.
+    pub(super) fn init_redstone_api(&mut self) -> SimulatorResult<()> {
+        let globals = self.lua.globals();
+
+        let redstone_table = self.lua.create_table()?;
+
+        redstone_table.set(
+            "getSides",
+            self.lua
+                .create_native_function(|_lua, ()| Ok(SIDES.to_vec()))?,
+        )?;
+
+        redstone_table.set(
+            "setOutput",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, (side, on): (Value, Value)| {
+                    let side = expect_side(1, side)?;
+                    let power = match on {
+                        Value::Boolean(true) => 15,
+                        Value::Boolean(false) => 0,
+                        on => return Err(bad_argument(2, "boolean", &on)),
+                    };
+
+                    state.set_redstone_output(|outputs| outputs.analog[side] = power);
+                    Ok(())
+                }
+            })?,
+        )?;
+        redstone_table.set(
+            "getOutput",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, side: Value| {
+                    let side = expect_side(1, side)?;
+                    Ok(state.redstone_outputs.get().analog[side] > 0)
+                }
+            })?,
+        )?;
+        redstone_table.set(
+            "getInput",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, side: Value| {
+                    let side = expect_side(1, side)?;
+                    Ok(state.read_redstone_inputs().analog[side] > 0)
+                }
+            })?,
+        )?;
+
+        let set_analog_output = self.lua.create_native_function({
+            let state = self.state.clone();
+            move |_lua, (side, power): (Value, Value)| {
+                let side = expect_side(1, side)?;
+                let power = expect_number(2, power)?;
+                if !(0.0..=15.0).contains(&power) {
+                    return Err(mlua::Error::RuntimeError(
+                        "Expected number in range 0-15".to_string(),
+                    ));
+                }
+
+                state.set_redstone_output(|outputs| outputs.analog[side] = power as u8);
+                Ok(())
+            }
+        })?;
+        redstone_table.set("setAnalogOutput", set_analog_output.clone())?;
+        redstone_table.set("setAnalogueOutput", set_analog_output)?;
+
+        let get_analog_output = self.lua.create_native_function({
+            let state = self.state.clone();
+            move |_lua, side: Value| {
+                let side = expect_side(1, side)?;
+                Ok(state.redstone_outputs.get().analog[side])
+            }
+        })?;
+        redstone_table.set("getAnalogOutput", get_analog_output.clone())?;
+        redstone_table.set("getAnalogueOutput", get_analog_output)?;
+
+        let get_analog_input = self.lua.create_native_function({
+            let state = self.state.clone();
+            move |_lua, side: Value| {
+                let side = expect_side(1, side)?;
+                Ok(state.read_redstone_inputs().analog[side])
+            }
+        })?;
+        redstone_table.set("getAnalogInput", get_analog_input.clone())?;
+        redstone_table.set("getAnalogueInput", get_analog_input)?;
+
+        redstone_table.set(
+            "setBundledOutput",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, (side, colors): (Value, Value)| {
+                    let side = expect_side(1, side)?;
+                    let colors = expect_colors(2, colors)?;
+
+                    state.set_redstone_output(|outputs| outputs.bundled[side] = colors);
+                    Ok(())
+                }
+            })?,
+        )?;
+        redstone_table.set(
+            "getBundledOutput",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, side: Value| {
+                    let side = expect_side(1, side)?;
+                    Ok(state.redstone_outputs.get().bundled[side])
+                }
+            })?,
+        )?;
+        redstone_table.set(
+            "getBundledInput",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, side: Value| {
+                    let side = expect_side(1, side)?;
+                    Ok(state.read_redstone_inputs().bundled[side])
+                }
+            })?,
+        )?;
+        redstone_table.set(
+            "testBundledInput",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |_lua, (side, mask): (Value, Value)| {
+                    let side = expect_side(1, side)?;
+                    let mask = expect_colors(2, mask)?;
+                    Ok(state.read_redstone_inputs().bundled[side] & mask == mask)
+                }
+            })?,
+        )?;
+
+        globals.set("redstone", redstone_table.clone())?;
+        globals.set("rs", redstone_table)?;
+
+        Ok(())
+    }
+
+    /// Sets the redstone power given off at the position, such as by a lever,
+    /// queueing `redstone` events on the computers next to it.
+    pub fn set_redstone_power(&self, position: Position, power: u8) {
+        self.state
+            .shared
+            .world
+            .borrow_mut()
+            .set_redstone_power(position, power);
+        self.state.shared.update_redstone();
+    }
+
+    /// Sets the colors of the bundled cable at the position which are on,
+    /// queueing `redstone` events on the computers next to it.
+    pub fn set_bundled_power(&self, position: Position, colors: u16) {
+        self.state
+            .shared
+            .world
+            .borrow_mut()
+            .set_bundled_power(position, colors);
+        self.state.shared.update_redstone();
+    }
+
+    /// Powers the block on the side of the computer, so the computer receives
+    /// a signal of that strength on the side.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the side isn't one of [`SIDES`].
+    pub fn set_redstone_input(&self, side: &str, power: u8) {
+        self.set_redstone_power(self.expect_side_position(side), power);
+    }
+
+    /// Sets the colors of the bundled cable on the side of the computer which
+    /// are on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the side isn't one of [`SIDES`].
+    pub fn set_bundled_input(&self, side: &str, colors: u16) {
+        self.set_bundled_power(self.expect_side_position(side), colors);
+    }
+
+    /// Returns the redstone signals the computer receives on each side.
+    pub fn redstone_inputs(&self) -> RedstoneSignals {
+        self.state.read_redstone_inputs()
+    }
+
+    /// Returns the redstone signals the computer sends out of each side.
+    pub fn redstone_outputs(&self) -> RedstoneSignals {
+        self.state.redstone_outputs.get()
+    }
+
+    fn expect_side_position(&self, side: &str) -> Position {
+        self.state
+            .side_position(side)
+            .unwrap_or_else(|| panic!("{side:?} is not a side"))
+    }
+}
+
+impl SimulatorState {
+    /// Works out the redstone signals the computer receives on each side, from
+    /// the world and from the computers next to it.
+    pub(super) fn read_redstone_inputs(&self) -> RedstoneSignals {
+        let position = self.position();
+        let world = self.shared.world.borrow();
+        let computers: Vec<_> = self
+            .shared
+            .computers
+            .borrow()
+            .iter()
+            .filter_map(|computer| computer.upgrade())
+            .collect();
+
+        let mut inputs = RedstoneSignals::default();
+        for (ix, side) in SIDES.into_iter().enumerate() {
+            let Some(neighbour) = self.side_position(side) else {
+                continue;
+            };
+
+            inputs.analog[ix] = world.redstone_power(neighbour);
+            inputs.bundled[ix] = world.bundled_power(neighbour);
+
+            // Computers power the blocks their sides face.
+            for computer in computers
+                .iter()
+                .filter(|computer| computer.position() == neighbour)
+            {
+                let outputs = computer.redstone_outputs.get();
+                for (their_ix, their_side) in SIDES.into_iter().enumerate() {
+                    if computer.side_position(their_side) == Some(position) {
+                        inputs.analog[ix] = inputs.analog[ix].max(outputs.analog[their_ix]);
+                        inputs.bundled[ix] |= outputs.bundled[their_ix];
+                    }
+                }
+            }
+        }
+
+        inputs
+    }
+
+    /// Changes the computer's redstone outputs, updating the computers next
+    /// to it if they changed.
+    fn set_redstone_output(&self, f: impl FnOnce(&mut RedstoneSignals)) {
+        let mut outputs = self.redstone_outputs.get();
+        f(&mut outputs);
+        if self.redstone_outputs.replace(outputs) != outputs {
+            self.shared.update_redstone();
+        }
+    }
+}
+
+impl SharedWorld {
+    /// Queues a `redstone` event on each computer whose redstone inputs have
+    /// changed since it last checked them.
+    pub(super) fn update_redstone(&self) {
+        let computers: Vec<_> = self
+            .computers
+            .borrow()
+            .iter()
+            .filter_map(|computer| computer.upgrade())
+            .collect();
+
+        for computer in computers {
+            let inputs = computer.read_redstone_inputs();
+            if computer.redstone_inputs.replace(inputs) != inputs {
+                computer
+                    .events
+                    .borrow_mut()
+                    .push_back(Event::new("redstone", vec![]));
+            }
+        }
+    }
+}
+
+/// Expects the argument to be the name of a side, returning its index in
+/// [`SIDES`].
+fn expect_side(index: usize, value: Value) -> mlua::Result<usize> {
+    let side = String::from_utf8_lossy(&expect_string(index, value)?).into_owned();
+    side_index(&side).ok_or_else(|| {
+        mlua::Error::RuntimeError(format!("bad argument #{index} (unknown option {side})"))
+    })
+}
+
+/// Expects the argument to be a set of colors of bundled cable.
+fn expect_colors(index: usize, value: Value) -> mlua::Result<u16> {
+    let colors = expect_number(index, value)?;
+    if !(0.0..=f64::from(u16::MAX)).contains(&colors) {
+        return Err(mlua::Error::RuntimeError(
+            "Expected number in range 0-65535".to_string(),
+        ));
+    }
+
+    Ok(colors as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::Position;
+    use pretty_assertions::assert_eq;
+
+    use crate::{Color, ComputerFamily, ComputerKind, Simulator};
+
+    #[test]
+    fn test_redstone_inputs_and_outputs() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator.set_redstone_input("left", 7);
+        simulator.set_bundled_input("back", Color::Red.value());
+        simulator
+            .exec_lua(
+                r#"
+                print(os.pullEvent("redstone"))
+                print(rs.getInput("left"), rs.getAnalogInput("left"), rs.getInput("right"))
+                print(redstone.testBundledInput("back", colors.red), redstone.getBundledInput("back"))
+
+                redstone.setOutput("top", true)
+                redstone.setAnalogueOutput("front", 3)
+                redstone.setBundledOutput("bottom", colors.combine(colors.white, colors.blue))
+                print(redstone.getOutput("top"), redstone.getAnalogOutput("front"))
+                print(pcall(redstone.setAnalogOutput, "front", 16))
+                print(pcall(redstone.getInput, "middle"))
+                print(pcall(redstone.setOutput, "top", 1))
+                print(pcall(redstone.setBundledOutput, "bottom", 65536))
+                print(pcall(redstone.testBundledInput, "back", -1))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "redstone",
+                "true\t7\tfalse",
+                "true\t16384",
+                "true\t3",
+                "false\tExpected number in range 0-15",
+                "false\tbad argument #1 (unknown option middle)",
+                "false\tbad argument #2 (boolean expected, got number)",
+                "false\tExpected number in range 0-65535",
+                "false\tExpected number in range 0-65535",
+                "",
+            ]
+            .join("\n")
+        );
+
+        let outputs = simulator.redstone_outputs();
+        assert_eq!(
+            (outputs.analog("top"), outputs.analog("back")),
+            (Some(15), Some(0))
+        );
+        assert_eq!(outputs.bundled("bottom"), Some(0x801));
+        assert_eq!(outputs.analog("middle"), None);
+        assert_eq!(simulator.world().redstone_power(Position::new(-1, 0, 0)), 7);
+    }
+
+    #[test]
+    fn test_redstone_between_computers() {
+        let turtle = Simulator::new().unwrap();
+        let mut computer = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        computer.move_computer_to(Position::new(0, 0, -1));
+
+        turtle
+            .exec_lua("redstone.setAnalogOutput('front', 9)")
+            .unwrap();
+        let input: (String, u8) = computer
+            .eval_lua("return os.pullEvent('redstone'), redstone.getAnalogInput('back')")
+            .unwrap();
+        assert_eq!(input, ("redstone".to_string(), 9));
+
+        // Outputs are turned off when the computer reboots.
+        computer
+            .exec_lua("redstone.setOutput('back', true)")
+            .unwrap();
+        assert_eq!(turtle.redstone_inputs().analog("front"), Some(15));
+        computer.reboot().unwrap();
+        assert_eq!(turtle.redstone_inputs().analog("front"), Some(0));
+    }
+
+    #[test]
+    fn test_moving_updates_redstone_inputs() {
+        let turtle = Simulator::new().unwrap();
+        let computer = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+        turtle.set_redstone_power(Position::new(0, 0, -2), 5);
+
+        turtle
+            .exec_lua(
+                r#"
+                print(redstone.getAnalogInput("front"))
+                turtle.forward()
+                print(os.pullEvent("redstone"))
+                print(redstone.getAnalogInput("front"))
+                turtle.turnLeft()
+                print(os.pullEvent("redstone"))
+                print(redstone.getAnalogInput("front"), redstone.getAnalogInput("right"))
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            turtle.output(),
+            ["0", "redstone", "5", "redstone", "0\t5", ""].join("\n")
+        );
+
+        computer.move_computer_to(Position::new(0, 0, -3));
+        let input: (String, u8) = computer
+            .eval_lua("return os.pullEvent('redstone'), redstone.getAnalogInput('back')")
+            .unwrap();
+        assert_eq!(input, ("redstone".to_string(), 5));
+    }
+}
@@ -0,0 +1,211 @@
+use mlua::{Function, Table};
+
+use crate::{ComputerKind, Simulator, SimulatorResult};
+
+/// The name of the registry value holding the function which creates a
+/// `require` function and `package` table for a program.
+pub(super) const MAKE_REQUIRE: &str = "cc.require.make";
+
+impl Simulator {
+    /// Replaces Lua's `require` and `package`, which would search the host's
+    /// filesystem, with ComputerCraft's, which search the computer's.
+    ///
+    /// Every program can require `cc.expect`, which the other APIs written in
+    /// Lua use to check their arguments, so this has to be set up first.
+    pub(super) fn init_require(&mut self) -> SimulatorResult<()> {
+        let cc_expect: Table = self
+            .lua
+            .load(include_str!("../lua/expect.lua"))
+            .set_name("@expect.lua")
+            .call(())?;
+        let is_turtle = self.state.computer.borrow().kind == ComputerKind::Turtle;
+        let make_require: Function = self
+            .lua
+            .load(include_str!("../lua/require.lua"))
+            .set_name("@require.lua")
+            .call((cc_expect, is_turtle))?;
+        self.lua
+            .set_named_registry_value(MAKE_REQUIRE, make_require)?;
+
+        let globals = self.lua.globals();
+        let (require, package) = self.make_require(globals.clone(), "")?;
+        globals.set("require", require)?;
+        globals.set("package", package)?;
+
+        Ok(())
+    }
+
+    /// Creates a `require` function and `package` table for a program running
+    /// in the given environment, with relative paths resolved against `dir`.
+    pub(super) fn make_require<'lua>(
+        &'lua self,
+        env: Table<'lua>,
+        dir: &str,
+    ) -> SimulatorResult<(Function<'lua>, Table<'lua>)> {
+        let make_require: Function = self.lua.named_registry_value(MAKE_REQUIRE)?;
+
+        Ok(make_require.call((env, dir))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{ComputerFamily, ComputerKind, Simulator};
+
+    #[test]
+    fn test_require_caches_modules() {
+        let simulator = Simulator::new().unwrap();
+        simulator
+            .filesystem_mut()
+            .write_file(
+                "lib/counter.lua",
+                "loads = (loads or 0) + 1\nreturn { name = ..., path = select(2, ...) }",
+            )
+            .unwrap();
+
+        let result: (bool, String, String, i64, bool) = simulator
+            .eval_lua(
+                r#"
+                    local first = require("lib.counter")
+                    local second = require "lib.counter"
+                    return first == second, first.name, first.path, loads, package.loaded["lib.counter"] == first
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            result,
+            (
+                true,
+                "lib.counter".to_string(),
+                "lib/counter.lua".to_string(),
+                1,
+                true
+            )
+        );
+    }
+
+    #[test]
+    fn test_require_cc_expect() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                local expect = require("cc.expect")
+                local function greet(name, options)
+                    expect.expect(1, name, "string")
+                    expect.field(options, "greeting", "string", "nil")
+                    return (options.greeting or "Hello") .. ", " .. name
+                end
+
+                print(greet("turtle", {}))
+                print(pcall(greet, 42, {}))
+                print(pcall(greet, "turtle", { greeting = true }))
+                local function select_slot(slot)
+                    expect.range(slot, 1, 16)
+                    return slot
+                end
+                print(pcall(select_slot, 17))
+                print(pcall(settings.define, "fuel", { description = 1 }))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "Hello, turtle",
+                "false\tbad argument #1 (string expected, got number)",
+                "false\tbad field 'greeting' (string or nil expected, got boolean)",
+                "false\tnumber outside of range (expected 17 to be within 1 and 16)",
+                "false\tbad field 'description' (string or nil expected, got number)",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_require_missing_module() {
+        let simulator = Simulator::new().unwrap();
+
+        let message: String = simulator
+            .eval_lua(r#"select(2, pcall(require, "missing"))"#)
+            .unwrap();
+
+        assert_eq!(
+            message,
+            [
+                "module 'missing' not found:",
+                "  no field package.preload['missing']",
+                "  no file 'missing'",
+                "  no file 'missing.lua'",
+                "  no file 'missing/init.lua'",
+                "  no file '/missing'",
+                "  no file '/missing.lua'",
+                "  no file '/missing/init.lua'",
+                "  no file '/rom/modules/main/missing'",
+                "  no file '/rom/modules/main/missing.lua'",
+                "  no file '/rom/modules/main/missing/init.lua'",
+                "  no file '/rom/modules/turtle/missing'",
+                "  no file '/rom/modules/turtle/missing.lua'",
+                "  no file '/rom/modules/turtle/missing/init.lua'",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_only_turtles_search_turtle_modules() {
+        let turtle = Simulator::new().unwrap();
+        let computer = turtle
+            .new_computer(ComputerKind::Computer, ComputerFamily::Normal)
+            .unwrap();
+
+        let path = "return package.path";
+        assert!(
+            turtle
+                .eval_lua::<String>(path)
+                .unwrap()
+                .ends_with(";/rom/modules/turtle/?/init.lua")
+        );
+        assert!(
+            computer
+                .eval_lua::<String>(path)
+                .unwrap()
+                .ends_with(";/rom/modules/main/?/init.lua")
+        );
+    }
+
+    #[test]
+    fn test_require_relative_to_program() {
+        let simulator = Simulator::new().unwrap();
+        {
+            let mut filesystem = simulator.filesystem_mut();
+            filesystem
+                .write_file("programs/helper.lua", "return 'programs'")
+                .unwrap();
+            filesystem
+                .write_file("helper.lua", "return 'root'")
+                .unwrap();
+            filesystem
+                .write_file("programs/only_here.lua", "return 'only here'")
+                .unwrap();
+        }
+
+        let (require, _) = simulator
+            .make_require(simulator.lua.globals(), "programs")
+            .unwrap();
+        let result: (String, String) = simulator
+            .call_lua(
+                r#"local require = ... return require("helper"), require("only_here")"#,
+                require,
+            )
+            .unwrap();
+
+        assert_eq!(result, ("programs".to_string(), "only here".to_string()));
+    }
+}
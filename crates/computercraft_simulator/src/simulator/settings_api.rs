@@ -0,0 +1,96 @@
+use mlua::Table;
+
+use crate::{Simulator, SimulatorResult};
+
+impl Simulator {
+    /// Sets up the `settings` API. The settings are loaded from `/.settings`
+    /// by the BIOS, once ComputerCraft's own settings have been defined.
+    pub(super) fn init_settings_api(&mut self) -> SimulatorResult<()> {
+        let settings_table: Table = self
+            .lua
+            .load(include_str!("../lua/settings.lua"))
+            .set_name("@settings.lua")
+            .call(())?;
+
+        self.lua.globals().set("settings", settings_table)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::Simulator;
+
+    #[test]
+    fn test_define_set_and_get() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                settings.define("test.width", { description = "The width.", default = 3, type = "number" })
+                print(settings.get("test.width"), settings.get("test.missing", "fallback"))
+
+                settings.set("test.width", 5)
+                settings.set("test.name", "tunnel")
+                local details = settings.getDetails("test.width")
+                print(details.value, details.default, details.changed, details.description)
+                print(pcall(settings.set, "test.width", "wide"))
+
+                settings.unset("test.width")
+                print(settings.get("test.width"), table.concat(settings.getNames(), ","))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "3\tfallback",
+                "5\t3\ttrue\tThe width.",
+                "false\tbad argument #2 (number expected, got string)",
+                "3\tshell.allow_startup,shell.autocomplete_hidden,test.name,test.width",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_settings_are_saved_and_loaded_on_boot() {
+        let mut simulator = Simulator::new().unwrap();
+        simulator
+            .filesystem_mut()
+            .write_file("startup.lua", "print('Started')")
+            .unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                settings.set("shell.allow_startup", false)
+                settings.set("test.sizes", { 1, 2 })
+                settings.save()
+                "#,
+            )
+            .unwrap();
+        // The settings are saved in the same format as `textutils.serialize`,
+        // though in no particular order.
+        let saved =
+            String::from_utf8(simulator.filesystem().read_file(".settings").unwrap()).unwrap();
+        assert!(saved.contains("\n  [ \"shell.allow_startup\" ] = false,\n"));
+        assert!(saved.contains("\n  [ \"test.sizes\" ] = {\n    1,\n    2,\n  },\n"));
+
+        simulator.reboot().unwrap();
+
+        assert_eq!(simulator.output(), "");
+        assert_eq!(
+            simulator
+                .eval_lua::<u32>("settings.get('test.sizes')[2]")
+                .unwrap(),
+            2
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use mlua::{Function, Table};
+
+use crate::simulator::Program;
+use crate::{Simulator, SimulatorResult};
+
+/// The name of the registry value holding the function which runs a program
+/// for [`Simulator::run_program`].
+pub(super) const RUN_PROGRAM: &str = "shell.run_program";
+
+/// The name of the registry value holding the function which runs the
+/// startup programs.
+const RUN_STARTUP: &str = "shell.run_startup";
+
+impl Simulator {
+    /// Sets up the `shell` API, which is given to programs run through it
+    /// rather than being a global.
+    pub(super) fn init_shell_api(&mut self) -> SimulatorResult<()> {
+        let make_require: Function = self
+            .lua
+            .named_registry_value(super::require::MAKE_REQUIRE)?;
+        let (_shell, run_program, run_startup): (Table, Function, Function) = self
+            .lua
+            .load(include_str!("../lua/shell.lua"))
+            .set_name("@shell.lua")
+            .call(make_require)?;
+
+        self.lua
+            .set_named_registry_value(RUN_PROGRAM, run_program)?;
+        self.lua
+            .set_named_registry_value(RUN_STARTUP, run_startup)?;
+
+        Ok(())
+    }
+
+    /// Starts running the program like [`Simulator::run_program`], without
+    /// running any of it yet. It is run with [`Simulator::resume_program`].
+    pub(crate) fn start_program(&self, command: &str, args: &[&str]) -> SimulatorResult<Program> {
+        let run_program: Function = self.lua.named_registry_value(RUN_PROGRAM)?;
+
+        let mut run_args = vec![command];
+        run_args.extend_from_slice(args);
+        let thread = self
+            .lua
+            .create_thread(run_program.bind(mlua::Variadic::from_iter(run_args))?)?;
+
+        Ok(Program::new(self.lua.create_registry_value(thread)?))
+    }
+
+    /// Runs `startup.lua` and the programs in `startup/`, in that order.
+    pub(super) fn run_startup(&self) -> SimulatorResult<()> {
+        let run_startup: Function = self.lua.named_registry_value(RUN_STARTUP)?;
+
+        self.run_function(run_startup, ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{Simulator, SimulatorError};
+
+    #[test]
+    fn test_run_program_resolves_names_on_the_path() {
+        let simulator = Simulator::new().unwrap();
+        simulator
+            .filesystem_mut()
+            .write_file("programs/greet.lua", "print('Hello', ...)")
+            .unwrap();
+
+        // Programs in the repository's `programs` directory and in the ROM
+        // can both be run by name.
+        simulator.run_program("greet", &["there"]).unwrap();
+        simulator.run_program("repeat", &[]).unwrap();
+
+        assert_eq!(simulator.output(), "Hello\tthere\nNo modems found.\n");
+
+        let Err(SimulatorError::LuaRuntime(err)) = simulator.run_program("missing", &[]) else {
+            panic!("expected a Lua runtime error");
+        };
+        assert_eq!(err.message, "No such program");
+    }
+
+    #[test]
+    fn test_shell_run_and_aliases() {
+        let simulator = Simulator::new().unwrap();
+        {
+            let mut filesystem = simulator.filesystem_mut();
+            filesystem
+                .write_file("programs/greet.lua", "print('Hello, ' .. ...)")
+                .unwrap();
+            filesystem
+                .write_file("programs/broken.lua", "error('Oops')")
+                .unwrap();
+            filesystem
+                .write_file(
+                    "menu.lua",
+                    r#"
+                        shell.setAlias("hi", "greet")
+                        print(shell.resolveProgram("hi"), shell.resolve("x"), shell.path())
+                        print(shell.run("hi", '"big world"'), shell.run("broken"), shell.run("nope"))
+                        print(shell.openTab("greet tab"), shell.getRunningProgram())
+                    "#,
+                )
+                .unwrap();
+        }
+
+        simulator.run_program("menu.lua", &[]).unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "programs/greet.lua\tx\t.:/programs:/rom/programs:/rom/programs/rednet:/rom/programs/turtle",
+                "Hello, big world",
+                "programs/broken.lua:1: Oops",
+                "No such program",
+                "true\tfalse\tfalse",
+                "Hello, tab",
+                "2\tmenu.lua",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_shell_complete() {
+        let simulator = Simulator::new().unwrap();
+        {
+            let mut filesystem = simulator.filesystem_mut();
+            filesystem.write_file("programs/tree_farm.lua", "").unwrap();
+            filesystem
+                .write_file("programs/tunnel_miner.lua", "")
+                .unwrap();
+            filesystem.write_file("tools/x.lua", "").unwrap();
+            filesystem
+                .write_file(
+                    "complete.lua",
+                    r#"
+                        shell.setCompletionFunction("programs/tree_farm.lua", function(shell, index, current, previous)
+                            return { index .. ":" .. current .. ":" .. previous[1] }
+                        end)
+                        print(table.concat(shell.complete("t"), ","))
+                        print(table.concat(shell.complete("tree_farm sm"), ","))
+                        print(table.concat(shell.programs(), ","))
+                    "#,
+                )
+                .unwrap();
+        }
+
+        simulator.run_program("complete", &[]).unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            "ools/,ree_farm ,unnel_miner\n1:sm:tree_farm\ncomplete,repeat,tree_farm,tunnel_miner\n"
+        );
+    }
+}
@@ -0,0 +1,309 @@
+use minecraft::ItemId;
+use mlua::{IntoLuaMulti, Lua, MultiValue, Value};
+
+use crate::simulator::SimulatorState;
+use crate::simulator::expect::{bad_argument, expect_number, expect_string};
+use crate::simulator::peripheral_api::PeripheralLocation;
+use crate::{INSTRUMENTS, Peripheral, PlayedSound, SPEAKER, Simulator, Speaker, TurtleSide};
+
+impl Simulator {
+    /// Equips the turtle with the upgrade on the side, or unequips it. Upgrades
+    /// which are peripherals, like speakers, are attached to that side.
+    pub fn equip_turtle_upgrade(&self, side: TurtleSide, upgrade: Option<ItemId>) {
+        let name = match side {
+            TurtleSide::Left => "left",
+            TurtleSide::Right => "right",
+        };
+
+        self.detach_peripheral(name);
+        if upgrade.as_ref() == Some(&SPEAKER) {
+            self.attach_peripheral(name, Peripheral::Speaker(Speaker::new()));
+        }
+
+        self.turtle_mut().set_upgrade(side, upgrade);
+    }
+
+    /// Returns everything the speaker attached to the computer, or placed in
+    /// the world, with the name played.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no speaker with the name.
+    pub fn speaker_log(&self, name: &str) -> Vec<PlayedSound> {
+        let peripheral = self
+            .attached_peripheral(name)
+            .or_else(|| self.placed_peripheral(name));
+        match peripheral.as_deref() {
+            Some(Peripheral::Speaker(speaker)) => speaker.log().to_vec(),
+            _ => panic!("there is no speaker named {name:?}"),
+        }
+    }
+}
+
+impl SimulatorState {
+    /// Calls a method of the speaker at the location.
+    pub(super) fn call_speaker<'lua>(
+        &self,
+        lua: &'lua Lua,
+        location: &PeripheralLocation,
+        method: &str,
+        args: MultiValue<'lua>,
+    ) -> mlua::Result<MultiValue<'lua>> {
+        let mut args = args.into_iter();
+        let mut arg = || args.next().unwrap_or(Value::Nil);
+
+        let now = self.shared.ticks.get();
+
+        match method {
+            "playNote" => {
+                let instrument = String::from_utf8_lossy(&expect_string(1, arg())?).into_owned();
+                let volume = optional_number(2, arg(), 1.0)?.clamp(0.0, 3.0);
+                let pitch = optional_number(3, arg(), 12.0)?.clamp(0.0, 24.0);
+
+                if !INSTRUMENTS.contains(&instrument.as_str()) {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "Invalid instrument, \"{instrument}\"!"
+                    )));
+                }
+
+                self.with_speaker(location, |speaker| {
+                    speaker.play_note(now, &instrument, volume, pitch)
+                })
+                .into_lua_multi(lua)
+            }
+            "playSound" => {
+                let name = String::from_utf8_lossy(&expect_string(1, arg())?).into_owned();
+                let volume = optional_number(2, arg(), 1.0)?.clamp(0.0, 3.0);
+                let pitch = optional_number(3, arg(), 1.0)?.clamp(0.5, 2.0);
+
+                if !is_resource_location(&name) {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "Malformed sound name '{name}' "
+                    )));
+                }
+
+                self.with_speaker(location, |speaker| {
+                    speaker.play_sound(now, &name, volume, pitch)
+                })
+                .into_lua_multi(lua)
+            }
+            "playAudio" => {
+                let samples = expect_audio(arg())?;
+                let volume = match arg() {
+                    Value::Nil => None,
+                    value => Some(expect_number(2, value)?.clamp(0.0, 3.0)),
+                };
+
+                let until =
+                    self.with_speaker(location, |speaker| speaker.play_audio(now, samples, volume));
+                if let Some(until) = until {
+                    // Once it runs out, the speaker asks for more audio.
+                    self.speakers_playing
+                        .borrow_mut()
+                        .insert(location.name().to_string(), until);
+                }
+
+                until.is_some().into_lua_multi(lua)
+            }
+            "stop" => {
+                self.with_speaker(location, |speaker| speaker.stop());
+                self.speakers_playing.borrow_mut().remove(location.name());
+
+                ().into_lua_multi(lua)
+            }
+            _ => Err(mlua::Error::RuntimeError(format!(
+                "No such method {method}"
+            ))),
+        }
+    }
+
+    /// Runs the function on the speaker at the location.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no speaker at the location.
+    fn with_speaker<R>(
+        &self,
+        location: &PeripheralLocation,
+        f: impl FnOnce(&mut Speaker) -> R,
+    ) -> R {
+        self.with_peripheral(location, |peripheral| match peripheral {
+            Peripheral::Speaker(speaker) => f(speaker),
+            _ => unreachable!("the peripheral should be a speaker"),
+        })
+        .expect("the speaker should exist")
+    }
+}
+
+/// Expects the argument to be a number, if there is one.
+fn optional_number(index: usize, value: Value, default: f64) -> mlua::Result<f64> {
+    match value {
+        Value::Nil => Ok(default),
+        value => expect_number(index, value),
+    }
+}
+
+/// Expects the argument to be a list of audio samples, returning how many
+/// there are.
+fn expect_audio(value: Value) -> mlua::Result<usize> {
+    let Value::Table(audio) = value else {
+        return Err(bad_argument(1, "table", &value));
+    };
+
+    let length = audio.raw_len();
+    if length == 0 {
+        return Err(mlua::Error::RuntimeError(
+            "Cannot play empty audio".to_string(),
+        ));
+    }
+    if length > Speaker::MAX_AUDIO_SAMPLES {
+        return Err(mlua::Error::RuntimeError(
+            "Audio data is too large".to_string(),
+        ));
+    }
+
+    for index in 1..=length {
+        let sample = match audio.raw_get(index)? {
+            Value::Integer(sample) => sample as f64,
+            Value::Number(sample) => sample,
+            _ => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "table item #{index} must be a number"
+                )));
+            }
+        };
+        if !(-128.0..=127.0).contains(&sample) {
+            return Err(mlua::Error::RuntimeError(format!(
+                "table item #{index} must be between -128 and 127"
+            )));
+        }
+    }
+
+    Ok(length)
+}
+
+/// Returns whether the name is a valid Minecraft resource location, like
+/// `minecraft:entity.creeper.primed`.
+fn is_resource_location(name: &str) -> bool {
+    let (namespace, path) = name.split_once(':').unwrap_or(("minecraft", name));
+
+    !namespace.is_empty()
+        && !path.is_empty()
+        && namespace
+            .chars()
+            .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.'))
+        && path
+            .chars()
+            .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.' | '/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{Peripheral, PlayedSound, SPEAKER, Simulator, SoundKind, Speaker, TurtleSide};
+
+    #[test]
+    fn test_speaker() {
+        let simulator = Simulator::new().unwrap();
+        simulator.attach_peripheral("top", Peripheral::Speaker(Speaker::new()));
+
+        simulator
+            .exec_lua(
+                r#"
+                local speaker = peripheral.find("speaker")
+                sleep(1)
+                print(speaker.playNote("bell", 2, 18))
+                print(speaker.playSound("entity.creeper.primed"))
+                print(speaker.playSound("block.bell.use"))
+                print(pcall(speaker.playNote, "kazoo"))
+
+                local audio = {}
+                for i = 1, 4800 do audio[i] = 0 end
+                print(speaker.playAudio(audio, 0.5))
+                print(speaker.playAudio(audio))
+                print(os.pullEvent("speaker_audio_empty"))
+                print(speaker.playAudio(audio))
+                print(pcall(speaker.playAudio, { 200 }))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "true",
+                "true",
+                "false",
+                "false\tperipheral.lua:121: Invalid instrument, \"kazoo\"!",
+                "true",
+                "false",
+                "speaker_audio_empty\ttop",
+                "true",
+                "false\tperipheral.lua:121: table item #1 must be between -128 and 127",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            simulator.speaker_log("top"),
+            [
+                PlayedSound {
+                    tick: 20,
+                    kind: SoundKind::Note {
+                        instrument: "bell".to_string(),
+                        volume: 2.0,
+                        pitch: 18.0,
+                    },
+                },
+                PlayedSound {
+                    tick: 20,
+                    kind: SoundKind::Sound {
+                        name: "entity.creeper.primed".to_string(),
+                        volume: 1.0,
+                        pitch: 1.0,
+                    },
+                },
+                PlayedSound {
+                    tick: 20,
+                    kind: SoundKind::Audio {
+                        samples: 4800,
+                        volume: 0.5,
+                    },
+                },
+                PlayedSound {
+                    tick: 22,
+                    kind: SoundKind::Audio {
+                        samples: 4800,
+                        volume: 0.5,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_turtle_speaker_upgrade() {
+        let simulator = Simulator::new().unwrap();
+        simulator.equip_turtle_upgrade(TurtleSide::Left, Some(SPEAKER));
+
+        simulator
+            .exec_lua(
+                r#"
+                print(peripheral.getType("left"))
+                for _ = 1, 9 do
+                    if not peripheral.call("left", "playNote", "pling") then
+                        print("Too many notes")
+                    end
+                end
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(simulator.output(), "speaker\nToo many notes\n");
+        assert_eq!(simulator.speaker_log("left").len(), 8);
+
+        simulator.equip_turtle_upgrade(TurtleSide::Left, None);
+        assert!(simulator.attached_peripheral("left").is_none());
+    }
+}
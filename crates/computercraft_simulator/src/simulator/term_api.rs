@@ -0,0 +1,454 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mlua::{Function, Lua, Table, Value, Variadic};
+
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::expect::{bad_argument, expect_number, expect_string, expect_text};
+use crate::terminal::{WrapStep, wrap_text};
+use crate::{Color, Simulator, SimulatorResult, Terminal, unpack_rgb};
+
+/// The name of the registry value holding the current redirect target.
+const CURRENT_REDIRECT: &str = "term.current";
+
+/// The name of the registry value holding the native redirect target.
+const NATIVE_REDIRECT: &str = "term.native";
+
+impl Simulator {
+    pub(super) fn init_term_api(&mut self) -> SimulatorResult<()> {
+        let globals = self.lua.globals();
+
+        let native = create_redirect(&self.lua, self.state.terminal.clone())?;
+        self.lua
+            .set_named_registry_value(NATIVE_REDIRECT, native.clone())?;
+        self.lua
+            .set_named_registry_value(CURRENT_REDIRECT, native.clone())?;
+
+        let term_table = self.lua.create_table()?;
+
+        for pair in native.pairs::<String, Value>() {
+            let (method, _) = pair?;
+
+            term_table.set(
+                method.clone(),
+                self.lua
+                    .create_native_function(move |lua, args: Variadic<Value>| {
+                        let target = current_redirect(lua)?;
+                        let function: Function = target.get(method.as_str())?;
+
+                        function.call::<_, mlua::MultiValue>(args)
+                    })?,
+            )?;
+        }
+
+        term_table.set(
+            "redirect",
+            self.lua.create_native_function(|lua, target: Value| {
+                let Value::Table(target) = target else {
+                    return Err(bad_argument(1, "table", &target));
+                };
+
+                let term: Table = lua.globals().get("term")?;
+                if target == term {
+                    return Err(mlua::Error::RuntimeError(
+                        "term is not a recommended redirect target, try term.current() instead"
+                            .to_string(),
+                    ));
+                }
+
+                // Like ComputerCraft, methods the target is missing only fail
+                // once they are called.
+                let native: Table = lua.named_registry_value(NATIVE_REDIRECT)?;
+                for pair in native.pairs::<String, Value>() {
+                    let (method, _) = pair?;
+                    if !matches!(target.get(method.as_str())?, Value::Function(_)) {
+                        let message = format!("Redirect object is missing method {method}.");
+                        let stub =
+                            lua.create_native_function(move |_lua, ()| -> mlua::Result<()> {
+                                Err(mlua::Error::RuntimeError(message.clone()))
+                            })?;
+                        target.set(method, stub)?;
+                    }
+                }
+
+                let previous = current_redirect(lua)?;
+                lua.set_named_registry_value(CURRENT_REDIRECT, target)?;
+
+                Ok(previous)
+            })?,
+        )?;
+        term_table.set(
+            "current",
+            self.lua
+                .create_native_function(|lua, ()| current_redirect(lua))?,
+        )?;
+        term_table.set(
+            "native",
+            self.lua.create_native_function(|lua, ()| {
+                lua.named_registry_value::<Table>(NATIVE_REDIRECT)
+            })?,
+        )?;
+
+        let native_palette_color = self.lua.create_native_function(|_lua, color: Value| {
+            let color = expect_color(1, color)?;
+            let [r, g, b] = unpack_rgb(color.default_rgb());
+
+            Ok((r, g, b))
+        })?;
+        term_table.set("nativePaletteColour", native_palette_color.clone())?;
+        term_table.set("nativePaletteColor", native_palette_color)?;
+
+        globals.set("term", term_table)?;
+
+        globals.set(
+            "write",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |lua, text: Value| {
+                    let text = expect_text(lua, 1, text)?;
+
+                    state.record_output(&text);
+                    write_wrapped(lua, &state.terminal, &current_redirect(lua)?, &text)
+                }
+            })?,
+        )?;
+        globals.set(
+            "print",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |lua, values: Variadic<Value>| {
+                    let text = format_print(lua, values)?;
+
+                    state.record_output(&text);
+                    write_wrapped(lua, &state.terminal, &current_redirect(lua)?, &text)
+                }
+            })?,
+        )?;
+        globals.set(
+            "printError",
+            self.lua.create_native_function({
+                let state = self.state.clone();
+                move |lua, values: Variadic<Value>| {
+                    let text = format_print(lua, values)?;
+                    let target = current_redirect(lua)?;
+
+                    state.record_output(&text);
+
+                    let is_color = target.get::<_, Function>("isColour")?.call::<_, bool>(())?;
+                    if is_color {
+                        let previous_color = target
+                            .get::<_, Function>("getTextColour")?
+                            .call::<_, Value>(())?;
+                        target
+                            .get::<_, Function>("setTextColour")?
+                            .call::<_, ()>(Color::Red.value())?;
+                        write_wrapped(lua, &state.terminal, &target, &text)?;
+                        target
+                            .get::<_, Function>("setTextColour")?
+                            .call::<_, ()>(previous_color)?;
+                    } else {
+                        write_wrapped(lua, &state.terminal, &target, &text)?;
+                    }
+
+                    Ok(())
+                }
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+fn current_redirect(lua: &Lua) -> mlua::Result<Table<'_>> {
+    lua.named_registry_value(CURRENT_REDIRECT)
+}
+
+/// Joins the values with tabs and appends a newline, the same way `print` does.
+fn format_print(lua: &Lua, values: Variadic<Value>) -> mlua::Result<Vec<u8>> {
+    let tostring: Function = lua.globals().get("tostring")?;
+
+    let mut text = Vec::new();
+    for (ix, value) in values.into_iter().enumerate() {
+        if ix > 0 {
+            text.push(b'\t');
+        }
+
+        let value: mlua::String = tostring.call(value)?;
+        text.extend_from_slice(value.as_bytes());
+    }
+    text.push(b'\n');
+
+    Ok(text)
+}
+
+/// Writes the text to the redirect target, wrapping words onto new lines and scrolling as needed.
+///
+/// The native terminal wraps the text itself with [`Terminal::write_wrapped`]. Other targets are
+/// driven through their methods, like the `write` function from ComputerCraft's `bios.lua`.
+///
+/// Returns the number of lines printed.
+fn write_wrapped(
+    lua: &Lua,
+    native: &RefCell<Terminal>,
+    target: &Table,
+    text: &[u8],
+) -> mlua::Result<usize> {
+    let native_redirect: Table = lua.named_registry_value(NATIVE_REDIRECT)?;
+    if *target == native_redirect {
+        return Ok(native.borrow_mut().write_wrapped(text));
+    }
+
+    let get_size: Function = target.get("getSize")?;
+    let get_cursor_pos: Function = target.get("getCursorPos")?;
+    let set_cursor_pos: Function = target.get("setCursorPos")?;
+    let scroll: Function = target.get("scroll")?;
+    let write: Function = target.get("write")?;
+
+    let (width, height) = get_size.call::<_, (i32, i32)>(())?;
+    let (x, _) = get_cursor_pos.call::<_, (i32, i32)>(())?;
+    let mut lines_printed = 0;
+
+    for step in wrap_text(text, width, x - 1) {
+        match step {
+            WrapStep::Write(text) => write.call::<_, ()>(lua.create_string(text)?)?,
+            WrapStep::NewLine => {
+                let (_, y) = get_cursor_pos.call::<_, (i32, i32)>(())?;
+                if y < height {
+                    set_cursor_pos.call::<_, ()>((1, y + 1))?;
+                } else {
+                    set_cursor_pos.call::<_, ()>((1, height))?;
+                    scroll.call::<_, ()>(1)?;
+                }
+
+                lines_printed += 1;
+            }
+        }
+    }
+
+    Ok(lines_printed)
+}
+
+/// Expects the argument to be a color from the `colors` API.
+fn expect_color(index: usize, value: Value) -> mlua::Result<Color> {
+    let value = expect_number(index, value)?;
+    if value < 1.0 {
+        return Err(mlua::Error::RuntimeError("Colour out of range".to_string()));
+    }
+
+    Color::from_value(value as u32)
+        .ok_or_else(|| mlua::Error::RuntimeError("Colour out of range".to_string()))
+}
+
+/// Creates a redirect object (the same kind of object as `term.native()`) backed by the given terminal.
+pub(crate) fn create_redirect(
+    lua: &Lua,
+    terminal: Rc<RefCell<Terminal>>,
+) -> mlua::Result<Table<'_>> {
+    let redirect = lua.create_table()?;
+
+    redirect.set(
+        "write",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |lua, text: Value| {
+                let text = expect_text(lua, 1, text)?;
+                terminal.borrow_mut().write(&text);
+
+                Ok(())
+            }
+        })?,
+    )?;
+    redirect.set(
+        "blit",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |_lua, (text, text_colors, background_colors): (Value, Value, Value)| {
+                let text = expect_string(1, text)?;
+                let text_colors = expect_string(2, text_colors)?;
+                let background_colors = expect_string(3, background_colors)?;
+                if text_colors.len() != text.len() || background_colors.len() != text.len() {
+                    return Err(mlua::Error::RuntimeError(
+                        "Arguments must be the same length".to_string(),
+                    ));
+                }
+
+                terminal
+                    .borrow_mut()
+                    .blit(&text, &text_colors, &background_colors);
+
+                Ok(())
+            }
+        })?,
+    )?;
+    redirect.set(
+        "scroll",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |_lua, n: Value| {
+                let n = expect_number(1, n)?;
+                terminal.borrow_mut().scroll(n.floor() as i32);
+
+                Ok(())
+            }
+        })?,
+    )?;
+    redirect.set(
+        "getCursorPos",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |_lua, ()| {
+                let (x, y) = terminal.borrow().cursor_pos();
+
+                Ok((x + 1, y + 1))
+            }
+        })?,
+    )?;
+    redirect.set(
+        "setCursorPos",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |_lua, (x, y): (Value, Value)| {
+                let x = expect_number(1, x)?;
+                let y = expect_number(2, y)?;
+                terminal
+                    .borrow_mut()
+                    .set_cursor_pos(x.floor() as i32 - 1, y.floor() as i32 - 1);
+
+                Ok(())
+            }
+        })?,
+    )?;
+    redirect.set(
+        "getCursorBlink",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |_lua, ()| Ok(terminal.borrow().cursor_blink())
+        })?,
+    )?;
+    redirect.set(
+        "setCursorBlink",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |_lua, blink: Value| {
+                let Value::Boolean(blink) = blink else {
+                    return Err(bad_argument(1, "boolean", &blink));
+                };
+                terminal.borrow_mut().set_cursor_blink(blink);
+
+                Ok(())
+            }
+        })?,
+    )?;
+    redirect.set(
+        "getSize",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |_lua, ()| {
+                let terminal = terminal.borrow();
+
+                Ok((terminal.width(), terminal.height()))
+            }
+        })?,
+    )?;
+    redirect.set(
+        "clear",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |_lua, ()| {
+                terminal.borrow_mut().clear();
+
+                Ok(())
+            }
+        })?,
+    )?;
+    redirect.set(
+        "clearLine",
+        lua.create_native_function({
+            let terminal = terminal.clone();
+            move |_lua, ()| {
+                terminal.borrow_mut().clear_line();
+
+                Ok(())
+            }
+        })?,
+    )?;
+
+    let get_text_color = lua.create_native_function({
+        let terminal = terminal.clone();
+        move |_lua, ()| Ok(terminal.borrow().text_color().value())
+    })?;
+    redirect.set("getTextColour", get_text_color.clone())?;
+    redirect.set("getTextColor", get_text_color)?;
+
+    let set_text_color = lua.create_native_function({
+        let terminal = terminal.clone();
+        move |_lua, color: Value| {
+            let color = expect_color(1, color)?;
+            terminal.borrow_mut().set_text_color(color);
+
+            Ok(())
+        }
+    })?;
+    redirect.set("setTextColour", set_text_color.clone())?;
+    redirect.set("setTextColor", set_text_color)?;
+
+    let get_background_color = lua.create_native_function({
+        let terminal = terminal.clone();
+        move |_lua, ()| Ok(terminal.borrow().background_color().value())
+    })?;
+    redirect.set("getBackgroundColour", get_background_color.clone())?;
+    redirect.set("getBackgroundColor", get_background_color)?;
+
+    let set_background_color = lua.create_native_function({
+        let terminal = terminal.clone();
+        move |_lua, color: Value| {
+            let color = expect_color(1, color)?;
+            terminal.borrow_mut().set_background_color(color);
+
+            Ok(())
+        }
+    })?;
+    redirect.set("setBackgroundColour", set_background_color.clone())?;
+    redirect.set("setBackgroundColor", set_background_color)?;
+
+    let is_color = lua.create_native_function({
+        let terminal = terminal.clone();
+        move |_lua, ()| Ok(terminal.borrow().is_color())
+    })?;
+    redirect.set("isColour", is_color.clone())?;
+    redirect.set("isColor", is_color)?;
+
+    let get_palette_color = lua.create_native_function({
+        let terminal = terminal.clone();
+        move |_lua, color: Value| {
+            let color = expect_color(1, color)?;
+            let [r, g, b] = terminal.borrow().palette_color(color);
+
+            Ok((r, g, b))
+        }
+    })?;
+    redirect.set("getPaletteColour", get_palette_color.clone())?;
+    redirect.set("getPaletteColor", get_palette_color)?;
+
+    let set_palette_color = lua.create_native_function({
+        let terminal = terminal.clone();
+        move |_lua, (color, r, g, b): (Value, Value, Option<Value>, Option<Value>)| {
+            let color = expect_color(1, color)?;
+            let rgb = match (g, b) {
+                (None, None) => unpack_rgb(expect_number(2, r)? as u32),
+                (g, b) => [
+                    expect_number(2, r)?,
+                    expect_number(3, g.unwrap_or(Value::Nil))?,
+                    expect_number(4, b.unwrap_or(Value::Nil))?,
+                ],
+            };
+            terminal.borrow_mut().set_palette_color(color, rgb);
+
+            Ok(())
+        }
+    })?;
+    redirect.set("setPaletteColour", set_palette_color.clone())?;
+    redirect.set("setPaletteColor", set_palette_color)?;
+
+    Ok(redirect)
+}
@@ -0,0 +1,1110 @@
+use std::collections::HashMap;
+use std::ffi::c_void;
+
+use mlua::{ChunkMode, Lua, MultiValue, Table, Value};
+
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::expect::{bad_argument, expect_number, expect_string};
+use crate::{Simulator, SimulatorResult};
+
+/// The name of the registry value holding `textutils.empty_json_array`.
+const EMPTY_JSON_ARRAY: &str = "textutils.empty_json_array";
+
+/// The name of the registry value holding `textutils.json_null`.
+const JSON_NULL: &str = "textutils.json_null";
+
+/// Strings which can't be used as bare keys in a serialized table.
+const LUA_KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+impl Simulator {
+    pub(super) fn init_textutils_api(&mut self) -> SimulatorResult<()> {
+        let globals = self.lua.globals();
+
+        let textutils_table = self.lua.create_table()?;
+
+        let empty_json_array = create_sentinel(&self.lua, "[]", "empty_json_array")?;
+        let json_null = create_sentinel(&self.lua, "null", "json_null")?;
+        self.lua
+            .set_named_registry_value(EMPTY_JSON_ARRAY, empty_json_array.clone())?;
+        self.lua
+            .set_named_registry_value(JSON_NULL, json_null.clone())?;
+        textutils_table.set("empty_json_array", empty_json_array)?;
+        textutils_table.set("json_null", json_null)?;
+
+        let serialize =
+            self.lua
+                .create_native_function(|lua, (value, options): (Value, Value)| {
+                    let options = match options {
+                        Value::Nil => SerializeOptions::default(),
+                        Value::Table(options) => SerializeOptions {
+                            compact: expect_bool_field(&options, "compact")?,
+                            allow_repetitions: expect_bool_field(&options, "allow_repetitions")?,
+                        },
+                        options => return Err(bad_argument(2, "table", &options)),
+                    };
+
+                    let mut output = Vec::new();
+                    serialize(lua, &value, &mut HashMap::new(), b"", &options, &mut output)?;
+
+                    lua.create_string(output)
+                })?;
+        textutils_table.set("serialize", serialize.clone())?;
+        textutils_table.set("serialise", serialize)?;
+
+        let unserialize = self.lua.create_native_function(|lua, text: Value| {
+            let text = expect_string(1, text)?;
+
+            let mut code = b"return ".to_vec();
+            code.extend(text);
+
+            let function = lua
+                .load(code)
+                .set_name("unserialize")
+                .set_mode(ChunkMode::Text)
+                .set_environment(lua.create_table()?)
+                .into_function();
+
+            Ok(function
+                .and_then(|function| function.call::<_, Value>(()))
+                .unwrap_or(Value::Nil))
+        })?;
+        textutils_table.set("unserialize", unserialize.clone())?;
+        textutils_table.set("unserialise", unserialize)?;
+
+        let serialize_json =
+            self.lua
+                .create_native_function(|lua, (value, options): (Value, Value)| {
+                    let options = match options {
+                        Value::Nil => JsonOptions::default(),
+                        Value::Boolean(nbt_style) => JsonOptions {
+                            nbt_style,
+                            ..JsonOptions::default()
+                        },
+                        Value::Table(options) => {
+                            expect_bool_field(&options, "allow_repetitions")?;
+
+                            JsonOptions {
+                                nbt_style: expect_bool_field(&options, "nbt_style")?,
+                                unicode_strings: expect_bool_field(&options, "unicode_strings")?,
+                            }
+                        }
+                        options => return Err(bad_argument(2, "table or boolean", &options)),
+                    };
+
+                    let sentinels = (
+                        lua.named_registry_value(EMPTY_JSON_ARRAY)?,
+                        lua.named_registry_value(JSON_NULL)?,
+                    );
+                    let mut output = Vec::new();
+                    serialize_json(
+                        lua,
+                        &value,
+                        &mut HashMap::new(),
+                        &options,
+                        &sentinels,
+                        &mut output,
+                    )?;
+
+                    lua.create_string(output)
+                })?;
+        textutils_table.set("serializeJSON", serialize_json.clone())?;
+        textutils_table.set("serialiseJSON", serialize_json)?;
+
+        let unserialize_json =
+            self.lua
+                .create_native_function(|lua, (text, options): (Value, Value)| {
+                    let text = expect_string(1, text)?;
+                    let (nbt_style, parse_null, parse_empty_array) = match options {
+                        Value::Nil => (false, false, true),
+                        Value::Table(options) => (
+                            expect_bool_field(&options, "nbt_style")?,
+                            expect_bool_field(&options, "parse_null")?,
+                            options.get::<_, Option<bool>>("parse_empty_array")? != Some(false),
+                        ),
+                        options => return Err(bad_argument(2, "table", &options)),
+                    };
+
+                    let mut parser = JsonParser {
+                        lua,
+                        input: &text,
+                        pos: 0,
+                        nbt_style,
+                        parse_null,
+                        parse_empty_array,
+                    };
+
+                    match parser.parse() {
+                        Ok(value) => Ok((value, None)),
+                        Err(JsonError::Malformed { pos, message }) => Ok((
+                            Value::Nil,
+                            Some(format!("Malformed JSON at position {}: {message}", pos + 1)),
+                        )),
+                        Err(JsonError::Lua(err)) => Err(err),
+                    }
+                })?;
+        textutils_table.set("unserializeJSON", unserialize_json.clone())?;
+        textutils_table.set("unserialiseJSON", unserialize_json)?;
+
+        textutils_table.set(
+            "urlEncode",
+            self.lua.create_native_function(|lua, text: Value| {
+                let text = expect_string(1, text)?;
+
+                lua.create_string(url_encode(&text))
+            })?,
+        )?;
+        textutils_table.set(
+            "formatTime",
+            self.lua
+                .create_native_function(|_lua, (time, twenty_four_hour): (Value, Value)| {
+                    let time = expect_number(1, time)?;
+                    let twenty_four_hour = match twenty_four_hour {
+                        Value::Nil => false,
+                        Value::Boolean(twenty_four_hour) => twenty_four_hour,
+                        value => return Err(bad_argument(2, "boolean", &value)),
+                    };
+
+                    Ok(format_time(time, twenty_four_hour))
+                })?,
+        )?;
+
+        globals.set("textutils", textutils_table)?;
+
+        Ok(())
+    }
+}
+
+/// Creates one of the special values used by the JSON functions, which can't
+/// be modified.
+fn create_sentinel<'lua>(
+    lua: &'lua Lua,
+    text: &'static str,
+    name: &'static str,
+) -> mlua::Result<Table<'lua>> {
+    let metatable = lua.create_table()?;
+    metatable.set(
+        "__newindex",
+        lua.create_native_function(move |_lua, _: MultiValue| -> mlua::Result<()> {
+            Err(mlua::Error::RuntimeError(format!(
+                "attempt to mutate textutils.{name}"
+            )))
+        })?,
+    )?;
+    metatable.set(
+        "__tostring",
+        lua.create_native_function(move |_lua, _: Value| Ok(text))?,
+    )?;
+
+    let table = lua.create_table()?;
+    table.set_metatable(Some(metatable));
+
+    Ok(table)
+}
+
+/// Expects the field of an options table to be a boolean or `nil`.
+fn expect_bool_field(options: &Table, name: &str) -> mlua::Result<bool> {
+    match options.get::<_, Value>(name)? {
+        Value::Nil => Ok(false),
+        Value::Boolean(value) => Ok(value),
+        value => Err(mlua::Error::RuntimeError(format!(
+            "bad field '{name}' (boolean expected, got {})",
+            value.type_name()
+        ))),
+    }
+}
+
+/// The punctuation used when serializing a table.
+struct TableSyntax {
+    open: &'static [u8],
+    open_key: &'static [u8],
+    close_key: &'static [u8],
+    equal: &'static [u8],
+    comma: &'static [u8],
+}
+
+const PRETTY_SYNTAX: TableSyntax = TableSyntax {
+    open: b"{\n",
+    open_key: b"[ ",
+    close_key: b" ] = ",
+    equal: b" = ",
+    comma: b",\n",
+};
+
+const COMPACT_SYNTAX: TableSyntax = TableSyntax {
+    open: b"{",
+    open_key: b"[",
+    close_key: b"]=",
+    equal: b"=",
+    comma: b",",
+};
+
+#[derive(Debug, Default)]
+struct SerializeOptions {
+    compact: bool,
+    allow_repetitions: bool,
+}
+
+/// Serializes the value as Lua source, the same way as `textutils.serialize`.
+///
+/// `tracking` holds the tables currently being serialized (`true`) and, unless
+/// repetitions are allowed, the tables that have already been serialized (`false`).
+fn serialize(
+    lua: &Lua,
+    value: &Value,
+    tracking: &mut HashMap<*const c_void, bool>,
+    indent: &[u8],
+    options: &SerializeOptions,
+    output: &mut Vec<u8>,
+) -> mlua::Result<()> {
+    match value {
+        Value::Table(table) => {
+            let pointer = table.to_pointer();
+            match tracking.get(&pointer) {
+                Some(true) => {
+                    return Err(mlua::Error::RuntimeError(
+                        "Cannot serialize table with recursive entries".to_string(),
+                    ));
+                }
+                Some(false) => {
+                    return Err(mlua::Error::RuntimeError(
+                        "Cannot serialize table with repeated entries".to_string(),
+                    ));
+                }
+                None => {}
+            }
+            tracking.insert(pointer, true);
+
+            let entries = table
+                .clone()
+                .pairs::<Value, Value>()
+                .collect::<mlua::Result<Vec<_>>>()?;
+            if entries.is_empty() {
+                output.extend(b"{}");
+            } else {
+                let syntax = if options.compact {
+                    &COMPACT_SYNTAX
+                } else {
+                    &PRETTY_SYNTAX
+                };
+                let sub_indent = if options.compact {
+                    Vec::new()
+                } else {
+                    [indent, b"  "].concat()
+                };
+                let sub_indent = sub_indent.as_slice();
+
+                output.extend(syntax.open);
+
+                let mut array_len = 0;
+                loop {
+                    let value: Value = table.raw_get(array_len + 1)?;
+                    if value.is_nil() {
+                        break;
+                    }
+
+                    output.extend(sub_indent);
+                    serialize(lua, &value, tracking, sub_indent, options, output)?;
+                    output.extend(syntax.comma);
+                    array_len += 1;
+                }
+
+                for (key, value) in entries {
+                    if array_index(&key).is_some_and(|index| index <= array_len) {
+                        continue;
+                    }
+
+                    output.extend(sub_indent);
+                    match &key {
+                        Value::String(key) if is_identifier(key.as_bytes()) => {
+                            output.extend(key.as_bytes());
+                            output.extend(syntax.equal);
+                        }
+                        key => {
+                            output.extend(syntax.open_key);
+                            serialize(lua, key, tracking, sub_indent, options, output)?;
+                            output.extend(syntax.close_key);
+                        }
+                    }
+                    serialize(lua, &value, tracking, sub_indent, options, output)?;
+                    output.extend(syntax.comma);
+                }
+
+                output.extend(indent);
+                output.push(b'}');
+            }
+
+            if options.allow_repetitions {
+                tracking.remove(&pointer);
+            } else {
+                tracking.insert(pointer, false);
+            }
+        }
+        Value::String(text) => output.extend(quote_string(text.as_bytes())),
+        Value::Integer(_) | Value::Number(_) => {
+            let number = match *value {
+                Value::Integer(number) => number as f64,
+                Value::Number(number) => number,
+                _ => unreachable!(),
+            };
+
+            if number.is_nan() {
+                output.extend(b"0/0");
+            } else if number == f64::INFINITY {
+                output.extend(b"1/0");
+            } else if number == f64::NEG_INFINITY {
+                output.extend(b"-1/0");
+            } else {
+                output.extend(to_string(lua, value.clone())?);
+            }
+        }
+        Value::Boolean(_) | Value::Nil => output.extend(to_string(lua, value.clone())?),
+        value => {
+            return Err(mlua::Error::RuntimeError(format!(
+                "Cannot serialize type {}",
+                value.type_name()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the value as a positive integer, if it is one.
+fn array_index(value: &Value) -> Option<i64> {
+    match *value {
+        Value::Integer(index) if index >= 1 => Some(index),
+        Value::Number(index) if index >= 1.0 && index.fract() == 0.0 => Some(index as i64),
+        _ => None,
+    }
+}
+
+/// Returns whether the key can be written without brackets and quotes.
+fn is_identifier(key: &[u8]) -> bool {
+    let Some((first, rest)) = key.split_first() else {
+        return false;
+    };
+
+    (first.is_ascii_alphabetic() || *first == b'_')
+        && rest
+            .iter()
+            .all(|char| char.is_ascii_alphanumeric() || *char == b'_')
+        && !LUA_KEYWORDS.iter().any(|keyword| keyword.as_bytes() == key)
+}
+
+/// Quotes the string the same way as `string.format("%q")`.
+fn quote_string(text: &[u8]) -> Vec<u8> {
+    let mut output = vec![b'"'];
+    for (ix, &char) in text.iter().enumerate() {
+        match char {
+            b'"' | b'\\' | b'\n' => {
+                output.push(b'\\');
+                output.push(char);
+            }
+            char if char.is_ascii_control() => {
+                let next_is_digit = text.get(ix + 1).is_some_and(u8::is_ascii_digit);
+                let escaped = if next_is_digit {
+                    format!("\\{char:03}")
+                } else {
+                    format!("\\{char}")
+                };
+                output.extend(escaped.as_bytes());
+            }
+            char => output.push(char),
+        }
+    }
+    output.push(b'"');
+
+    output
+}
+
+/// Converts the value to a string, the same way as `tostring`.
+fn to_string(lua: &Lua, value: Value) -> mlua::Result<Vec<u8>> {
+    let tostring: mlua::Function = lua.globals().get("tostring")?;
+
+    Ok(tostring.call::<_, mlua::String>(value)?.as_bytes().to_vec())
+}
+
+#[derive(Debug, Default)]
+struct JsonOptions {
+    nbt_style: bool,
+    unicode_strings: bool,
+}
+
+/// Serializes the value as JSON, the same way as `textutils.serializeJSON`.
+fn serialize_json(
+    lua: &Lua,
+    value: &Value,
+    tracking: &mut HashMap<*const c_void, bool>,
+    options: &JsonOptions,
+    sentinels: &(Table, Table),
+    output: &mut Vec<u8>,
+) -> mlua::Result<()> {
+    let (empty_json_array, json_null) = sentinels;
+
+    match value {
+        Value::Table(table) if table == empty_json_array => output.extend(b"[]"),
+        Value::Table(table) if table == json_null => output.extend(b"null"),
+        Value::Table(table) => {
+            let pointer = table.to_pointer();
+            if tracking.insert(pointer, true).is_some() {
+                return Err(mlua::Error::RuntimeError(
+                    "Cannot serialize table with recursive entries".to_string(),
+                ));
+            }
+
+            let mut object = vec![b'{'];
+            let mut object_len = 0;
+            let mut largest_index: f64 = 0.0;
+            for pair in table.clone().pairs::<Value, Value>() {
+                let (key, value) = pair?;
+                match key {
+                    Value::String(key) => {
+                        if object_len > 0 {
+                            object.push(b',');
+                        }
+
+                        if options.nbt_style {
+                            object.extend(key.as_bytes());
+                        } else {
+                            object.extend(json_string(key.as_bytes(), options)?);
+                        }
+                        object.push(b':');
+                        serialize_json(lua, &value, tracking, options, sentinels, &mut object)?;
+                        object_len += 1;
+                    }
+                    Value::Integer(index) if index as f64 > largest_index => {
+                        largest_index = index as f64;
+                    }
+                    Value::Number(index) if index > largest_index => largest_index = index,
+                    _ => {}
+                }
+            }
+            object.push(b'}');
+
+            if object_len > 0 || largest_index < 1.0 {
+                output.extend(object);
+            } else {
+                output.push(b'[');
+                for index in 1..=(largest_index as i64) {
+                    if index > 1 {
+                        output.push(b',');
+                    }
+
+                    let value: Value = table.get(index)?;
+                    if value.is_nil() {
+                        output.extend(b"null");
+                    } else {
+                        serialize_json(lua, &value, tracking, options, sentinels, output)?;
+                    }
+                }
+                output.push(b']');
+            }
+
+            tracking.remove(&pointer);
+        }
+        Value::String(text) => output.extend(json_string(text.as_bytes(), options)?),
+        Value::Integer(_) | Value::Number(_) | Value::Boolean(_) => {
+            output.extend(to_string(lua, value.clone())?);
+        }
+        value => {
+            return Err(mlua::Error::RuntimeError(format!(
+                "Cannot serialize type {}",
+                value.type_name()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Quotes the string as a JSON string.
+///
+/// Unless `unicode_strings` is set, each byte is treated as a single
+/// character, as ComputerCraft strings aren't UTF-8.
+fn json_string(text: &[u8], options: &JsonOptions) -> mlua::Result<Vec<u8>> {
+    fn escape(char: u32) -> Option<&'static [u8]> {
+        Some(match char {
+            0x22 => b"\\\"",
+            0x5C => b"\\\\",
+            0x08 => b"\\b",
+            0x0C => b"\\f",
+            0x0A => b"\\n",
+            0x0D => b"\\r",
+            0x09 => b"\\t",
+            _ => return None,
+        })
+    }
+
+    let mut output = vec![b'"'];
+    if options.unicode_strings && !text.is_ascii() {
+        let text = std::str::from_utf8(text)
+            .map_err(|_| mlua::Error::RuntimeError("invalid UTF-8 code".to_string()))?;
+        for char in text.chars() {
+            let code = char as u32;
+            if code > 0xFFFF {
+                // Encode the codepoint as a UTF-16 surrogate pair.
+                let code = code - 0x10000;
+                let (high, low) = ((code >> 10) + 0xD800, (code & 0x3FF) + 0xDC00);
+                output.extend(format!("\\u{high:04X}\\u{low:04X}").as_bytes());
+            } else if let Some(escaped) = escape(code) {
+                output.extend(escaped);
+            } else if !(0x20..0x7F).contains(&code) {
+                output.extend(format!("\\u{code:04X}").as_bytes());
+            } else {
+                output.push(code as u8);
+            }
+        }
+    } else {
+        for &char in text {
+            if let Some(escaped) = escape(char as u32) {
+                output.extend(escaped);
+            } else if !(0x20..0x7F).contains(&char) {
+                output.extend(format!("\\u00{char:02X}").as_bytes());
+            } else {
+                output.push(char);
+            }
+        }
+    }
+    output.push(b'"');
+
+    Ok(output)
+}
+
+enum JsonError {
+    Malformed { pos: usize, message: String },
+    Lua(mlua::Error),
+}
+
+impl From<mlua::Error> for JsonError {
+    fn from(err: mlua::Error) -> Self {
+        Self::Lua(err)
+    }
+}
+
+type JsonResult<T> = Result<T, JsonError>;
+
+/// Parses JSON into Lua values, the same way as `textutils.unserializeJSON`.
+struct JsonParser<'lua, 'a> {
+    lua: &'lua Lua,
+    input: &'a [u8],
+    pos: usize,
+    nbt_style: bool,
+    parse_null: bool,
+    parse_empty_array: bool,
+}
+
+impl<'lua> JsonParser<'lua, '_> {
+    fn parse(&mut self) -> JsonResult<Value<'lua>> {
+        let value = self.parse_value()?;
+
+        self.skip_whitespace();
+        if let Some(char) = self.peek() {
+            return Err(self.error(format!(
+                "Unexpected trailing character {}.",
+                String::from_utf8_lossy(&quote_string(&[char]))
+            )));
+        }
+
+        Ok(value)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|char| matches!(char, b' ' | b'\t' | b'\r' | b'\n'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, message: String) -> JsonError {
+        JsonError::Malformed {
+            pos: self.pos,
+            message,
+        }
+    }
+
+    /// Returns the error for when the next character isn't the expected one.
+    fn expected(&self, expected: &str) -> JsonError {
+        let actual = match self.peek() {
+            Some(char) => String::from_utf8_lossy(&quote_string(&[char])).into_owned(),
+            None => "end of input".to_string(),
+        };
+
+        self.error(format!("Unexpected {actual}, expected {expected}."))
+    }
+
+    fn expect(&mut self, char: u8, expected: &str) -> JsonResult<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(char) {
+            return Err(self.expected(expected));
+        }
+
+        self.pos += 1;
+
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> JsonResult<Value<'lua>> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(b'"') => Ok(Value::String(self.lua.create_string(self.parse_string()?)?)),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(b't') => self.parse_literal(b"true", Value::Boolean(true)),
+            Some(b'f') => self.parse_literal(b"false", Value::Boolean(false)),
+            Some(b'n') => {
+                let null = if self.parse_null {
+                    Value::Table(self.lua.named_registry_value(JSON_NULL)?)
+                } else {
+                    Value::Nil
+                };
+                self.parse_literal(b"null", null)
+            }
+            Some(b'{') => self.parse_object(),
+            Some(b'[') => self.parse_array(),
+            _ => Err(self.expected("value")),
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &[u8], value: Value<'lua>) -> JsonResult<Value<'lua>> {
+        for &char in literal {
+            if self.peek() != Some(char) {
+                return Err(self.expected(&format!("'{}'", String::from_utf8_lossy(literal))));
+            }
+
+            self.pos += 1;
+        }
+
+        Ok(value)
+    }
+
+    fn skip_digits(&mut self) -> JsonResult<()> {
+        if !self.peek().is_some_and(|char| char.is_ascii_digit()) {
+            return Err(self.expected("digit"));
+        }
+
+        while self.peek().is_some_and(|char| char.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        Ok(())
+    }
+
+    fn parse_number(&mut self) -> JsonResult<Value<'lua>> {
+        let start = self.pos;
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        self.skip_digits()?;
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            self.skip_digits()?;
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            self.skip_digits()?;
+        }
+
+        let number: f64 = std::str::from_utf8(&self.input[start..self.pos])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| self.error("Malformed number.".to_string()))?;
+
+        // NBT numbers may have a suffix specifying their type.
+        if self.nbt_style
+            && self
+                .peek()
+                .is_some_and(|char| b"bBsSlLfFdD".contains(&char))
+        {
+            self.pos += 1;
+        }
+
+        Ok(Value::Number(number))
+    }
+
+    fn parse_string(&mut self) -> JsonResult<Vec<u8>> {
+        self.pos += 1;
+
+        let mut output = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.expected("'\"'")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(output);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escaped = match self.peek() {
+                        Some(b'"') => b'"',
+                        Some(b'\\') => b'\\',
+                        Some(b'/') => b'/',
+                        Some(b'b') => 0x08,
+                        Some(b'f') => 0x0C,
+                        Some(b'n') => b'\n',
+                        Some(b'r') => b'\r',
+                        Some(b't') => b'\t',
+                        Some(b'u') => {
+                            self.pos += 1;
+                            let code = self.parse_unicode_escape()?;
+                            encode_char(code, &mut output);
+                            continue;
+                        }
+                        _ => return Err(self.expected("escape character")),
+                    };
+
+                    output.push(escaped);
+                    self.pos += 1;
+                }
+                Some(char) if char < 0x20 => {
+                    return Err(self.error(format!(
+                        "Unescaped control character {}.",
+                        String::from_utf8_lossy(&quote_string(&[char]))
+                    )));
+                }
+                Some(char) => {
+                    output.push(char);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn parse_hex(&mut self) -> JsonResult<u32> {
+        let code = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .filter(|hex| hex.bytes().all(|char| char.is_ascii_hexdigit()))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| self.error("Malformed unicode escape.".to_string()))?;
+        self.pos += 4;
+
+        Ok(code)
+    }
+
+    fn parse_unicode_escape(&mut self) -> JsonResult<u32> {
+        let code = self.parse_hex()?;
+        if !(0xD800..0xDC00).contains(&code) || !self.input[self.pos..].starts_with(b"\\u") {
+            return Ok(code);
+        }
+
+        // Combine a UTF-16 surrogate pair into a single codepoint.
+        let start = self.pos;
+        self.pos += 2;
+        let low = self.parse_hex()?;
+        if !(0xDC00..0xE000).contains(&low) {
+            self.pos = start;
+            return Ok(code);
+        }
+
+        Ok(0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00))
+    }
+
+    fn parse_object(&mut self) -> JsonResult<Value<'lua>> {
+        self.pos += 1;
+        let table = self.lua.create_table()?;
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Table(table));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some(b'"') => self.parse_string()?,
+                Some(char) if self.nbt_style && is_nbt_key_char(char) => {
+                    let start = self.pos;
+                    while self.peek().is_some_and(is_nbt_key_char) {
+                        self.pos += 1;
+                    }
+                    self.input[start..self.pos].to_vec()
+                }
+                _ => return Err(self.expected("object key")),
+            };
+
+            self.expect(b':', "':'")?;
+            let value = self.parse_value()?;
+            table.raw_set(self.lua.create_string(key)?, value)?;
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Table(table));
+                }
+                _ => return Err(self.expected("',' or '}'")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> JsonResult<Value<'lua>> {
+        self.pos += 1;
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Table(if self.parse_empty_array {
+                self.lua.named_registry_value(EMPTY_JSON_ARRAY)?
+            } else {
+                self.lua.create_table()?
+            }));
+        }
+
+        // NBT arrays may specify the type of their elements.
+        if self.nbt_style
+            && matches!(
+                self.input.get(self.pos..self.pos + 2),
+                Some(b"B;" | b"I;" | b"L;")
+            )
+        {
+            self.pos += 2;
+        }
+
+        let table = self.lua.create_table()?;
+        let mut index = 1;
+        loop {
+            let value = self.parse_value()?;
+            table.raw_set(index, value)?;
+            index += 1;
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Table(table));
+                }
+                _ => return Err(self.expected("',' or ']'")),
+            }
+        }
+    }
+}
+
+fn is_nbt_key_char(char: u8) -> bool {
+    char.is_ascii_alphanumeric() || matches!(char, b'_' | b'-' | b'.' | b'+')
+}
+
+/// Adds the character to the string, as a single byte if it fits in one and
+/// as UTF-8 otherwise.
+fn encode_char(code: u32, output: &mut Vec<u8>) {
+    if code < 0x100 {
+        output.push(code as u8);
+    } else {
+        let char = char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+        output.extend(char.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+}
+
+fn url_encode(text: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    for &char in text {
+        match char {
+            b'\n' => output.extend(b"%0D%0A"),
+            b' ' => output.push(b'+'),
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' => output.push(char),
+            char if char < 128 => output.extend(format!("%{char:02X}").as_bytes()),
+            // Non-ASCII characters are encoded as UTF-8.
+            char => output
+                .extend(format!("%{:02X}%{:02X}", 192 + (char >> 6), 128 + (char & 63)).as_bytes()),
+        }
+    }
+
+    output
+}
+
+fn format_time(time: f64, twenty_four_hour: bool) -> String {
+    let mut time = time;
+    let period = if twenty_four_hour {
+        None
+    } else {
+        let period = if time >= 12.0 { "PM" } else { "AM" };
+        if time >= 13.0 {
+            time -= 12.0;
+        }
+        Some(period)
+    };
+
+    let hour = time.floor();
+    let minute = ((time - hour) * 60.0).floor();
+    match period {
+        Some(period) => {
+            let hour = if hour == 0.0 { 12.0 } else { hour };
+            format!("{}:{:02} {period}", hour as i64, minute as i64)
+        }
+        None => format!("{}:{:02}", hour as i64, minute as i64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::Simulator;
+
+    #[test]
+    fn test_serialize() {
+        let simulator = Simulator::new().unwrap();
+
+        let result: (String, String, bool, bool) = simulator
+            .eval_lua(
+                r#"
+                local data = { 1, "two\n", name = "minecraft:stone", ["end"] = true, [10] = 0/0 }
+                local shared = {}
+                return textutils.serialize(data),
+                    textutils.serialize({ x = 1.5, y = { shared, shared } }, { compact = true, allow_repetitions = true }),
+                    textutils.serialize("a\0" .. "1\r\"") == string.format("%q", "a\0" .. "1\r\""),
+                    textutils.unserialize(textutils.serialize(data)).name == "minecraft:stone"
+                "#,
+            )
+            .unwrap();
+        let (serialized, compact, quoted, round_trips) = result;
+
+        let mut lines: Vec<&str> = serialized.lines().collect();
+        assert_eq!(lines.remove(0), "{");
+        assert_eq!(lines.remove(0), "  1,");
+        assert_eq!(lines.remove(0), "  \"two\\");
+        assert_eq!(lines.remove(0), "\",");
+        assert_eq!(lines.pop(), Some("}"));
+        lines.sort();
+        assert_eq!(
+            lines,
+            vec![
+                "  [ \"end\" ] = true,",
+                "  [ 10 ] = 0/0,",
+                "  name = \"minecraft:stone\","
+            ]
+        );
+        assert!(compact == "{y={{},{},},x=1.5,}" || compact == "{x=1.5,y={{},{},},}");
+        assert!(quoted);
+        assert!(round_trips);
+
+        let result: Result<(), _> = simulator.exec_lua(
+            r#"
+            local shared = {}
+            textutils.serialize({ shared, shared })
+            "#,
+        );
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Cannot serialize table with repeated entries")
+        );
+    }
+
+    #[test]
+    fn test_serialize_json() {
+        let simulator = Simulator::new().unwrap();
+
+        let result: (String, String, String, String, String) = simulator
+            .eval_lua(
+                r#"
+                return textutils.serializeJSON({ 1, nil, "caf\233", true }),
+                    textutils.serializeJSON({ name = "x\n" }),
+                    textutils.serializeJSON({ list = textutils.empty_json_array, value = textutils.json_null, n = 2 }, true):len(),
+                    textutils.serializeJSON("café 😀", { unicode_strings = true }),
+                    textutils.serializeJSON({})
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            (
+                r#"[1,null,"caf\u00E9",true]"#.to_string(),
+                r#"{"name":"x\n"}"#.to_string(),
+                "24".to_string(),
+                r#""caf\u00E9 \uD83D\uDE00""#.to_string(),
+                "{}".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_unserialize_json() {
+        let simulator = Simulator::new().unwrap();
+
+        let result: (String, f64, bool, bool, String) = simulator
+            .eval_lua(
+                r#"
+                local data = textutils.unserializeJSON([[ {"name": "café", "counts": [1, 2.5e1, null, -3], "ok": true, "none": null} ]])
+                local nbt = textutils.unserializeJSON("{Count:3b,id:\"minecraft:stone\",list:[]}", { nbt_style = true })
+                return data.name, data.counts[2], data.counts[3] == nil and data.counts[4] == -3,
+                    nbt.Count == 3 and nbt.list == textutils.empty_json_array,
+                    textutils.serialize(data.ok)
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            ("café".to_string(), 25.0, true, true, "true".to_string())
+        );
+
+        let result: (Option<String>, String) = simulator
+            .eval_lua(
+                r#"
+                return textutils.unserializeJSON("[1, 2"), select(2, textutils.unserializeJSON("{\"a\" 1}"))
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            (
+                None,
+                "Malformed JSON at position 6: Unexpected \"1\", expected ':'.".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_time_and_url_encode() {
+        let simulator = Simulator::new().unwrap();
+
+        let result: (String, String, String, String) = simulator
+            .eval_lua(
+                r#"
+                return textutils.formatTime(0.5), textutils.formatTime(13.75), textutils.formatTime(13.75, true),
+                    textutils.urlEncode("a b&c\n\233")
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            (
+                "12:30 AM".to_string(),
+                "1:45 PM".to_string(),
+                "13:45".to_string(),
+                "a+b%26c%0D%0A%C3%A9".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_tabulate() {
+        let simulator = Simulator::new().unwrap();
+        simulator.set_terminal_size(20, 5);
+
+        simulator
+            .exec_lua(r#"textutils.tabulate({ "apple", "banana", "cherry", "date" }, colors.red, { "x" })"#)
+            .unwrap();
+        assert_eq!(
+            simulator.terminal().contents(),
+            "apple  banana\ncherry date\nx\n\n\n"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use mlua::{Function, Table};
+
+use crate::simulator::interruption::TURTLE_ACTIONS;
+use crate::{Simulator, SimulatorResult};
+
+/// The name of the registry value holding the value turtle actions yield once
+/// they complete, to end the computer's turn for the tick.
+const TURTLE_TICK: &str = "turtle.tick";
+
+impl Simulator {
+    /// Returns the value turtle actions yield to end the computer's turn.
+    pub(super) fn turtle_tick(&self) -> SimulatorResult<Table> {
+        if let Some(tick) = self
+            .lua
+            .named_registry_value::<Option<Table>>(TURTLE_TICK)?
+        {
+            return Ok(tick);
+        }
+
+        let tick = self.lua.create_table()?;
+        self.lua
+            .set_named_registry_value(TURTLE_TICK, tick.clone())?;
+
+        Ok(tick)
+    }
+
+    /// Makes the turtle's actions yield once they complete, as each one takes a
+    /// tick in ComputerCraft. This lets the other computers in the world act in
+    /// between them.
+    pub(super) fn yield_after_turtle_actions(&self, turtle_table: &Table) -> SimulatorResult<()> {
+        let make_wrapper: Function = self
+            .lua
+            .load(
+                r#"
+                local action, tick = ...
+                return function(...)
+                    local result = table.pack(action(...))
+                    coroutine.yield(tick)
+                    return table.unpack(result, 1, result.n)
+                end
+                "#,
+            )
+            .set_name("@tick.lua")
+            .into_function()?;
+
+        let tick = self.turtle_tick()?;
+        for &name in TURTLE_ACTIONS {
+            let action: Function = turtle_table.get(name)?;
+            let wrapper: Function = make_wrapper.call((action, tick.clone()))?;
+            turtle_table.set(name, wrapper)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minecraft::world::Position;
+    use pretty_assertions::assert_eq;
+
+    use crate::Simulator;
+
+    #[test]
+    fn test_turtle_actions_in_coroutines() {
+        let simulator = Simulator::new().unwrap();
+
+        simulator
+            .exec_lua(
+                r#"
+                local co = coroutine.create(function()
+                    turtle.forward()
+                    coroutine.yield("inner")
+                    return turtle.forward()
+                end)
+                print(coroutine.resume(co))
+                print(coroutine.resume(co))
+                print(coroutine.wrap(function() return turtle.back() end)())
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            "true\tinner\ntrue\ttrue\tnil\ntrue\tnil\n"
+        );
+        assert_eq!(simulator.turtle().position, Position::new(0, 0, -1));
+    }
+}
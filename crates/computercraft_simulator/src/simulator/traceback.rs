@@ -0,0 +1,182 @@
+use std::fmt;
+
+use crate::simulator::native_error_message;
+
+/// A frame of a Lua stack traceback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    /// The chunk the frame is in, such as `programs/wheat_farmer.lua`, or
+    /// `[C]` for a native function.
+    pub file: String,
+    pub line: Option<u32>,
+    /// The name of the function, `<anonymous>` if it doesn't have one, or
+    /// `None` for the main chunk.
+    pub function: Option<String>,
+}
+
+impl StackFrame {
+    /// Parses a line of a traceback produced by `luaL_traceback`, such as
+    /// `programs/wheat_farmer.lua:42: in function 'harvest'`.
+    fn parse(line: &str) -> Option<Self> {
+        let (position, description) = line.trim().split_once(": in ")?;
+
+        let (file, line) = match position.rsplit_once(':') {
+            Some((file, line)) if line.parse::<u32>().is_ok() => {
+                (file.to_string(), line.parse().ok())
+            }
+            _ => (position.to_string(), None),
+        };
+        let function = match description {
+            "main chunk" => None,
+            description => Some(
+                description
+                    .strip_prefix("function '")
+                    .and_then(|name| name.strip_suffix('\''))
+                    .unwrap_or("<anonymous>")
+                    .to_string(),
+            ),
+        };
+
+        Some(Self {
+            file,
+            line,
+            function,
+        })
+    }
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.function {
+            Some(function) => write!(f, "{function}")?,
+            None => write!(f, "main chunk")?,
+        }
+        write!(f, " ({}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// An error raised by a Lua program, along with where it was raised.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LuaRuntimeError {
+    /// The error message, as Lua code catching the error would see it.
+    pub message: String,
+    /// The frames of the stack when the error was raised, innermost first.
+    pub traceback: Vec<StackFrame>,
+}
+
+impl LuaRuntimeError {
+    /// Extracts the message and traceback from an error raised while running
+    /// Lua code, if it is one.
+    pub(super) fn from_lua_error(err: &mlua::Error) -> Option<Self> {
+        let (message, traceback) = match err {
+            mlua::Error::RuntimeError(message) => match message.split_once("\nstack traceback:") {
+                Some((message, traceback)) => (message.to_string(), traceback),
+                None => (message.clone(), ""),
+            },
+            mlua::Error::CallbackError { traceback, .. } => (
+                native_error_message(err),
+                traceback
+                    .split_once("stack traceback:")
+                    .map_or("", |(_, traceback)| traceback),
+            ),
+            _ => return None,
+        };
+
+        Some(Self {
+            message,
+            traceback: traceback.lines().filter_map(StackFrame::parse).collect(),
+        })
+    }
+}
+
+impl fmt::Display for LuaRuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        for frame in &self.traceback {
+            write!(f, "\n  at {frame}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for LuaRuntimeError {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Simulator, SimulatorError};
+
+    #[test]
+    fn test_runtime_error_traceback() {
+        let simulator = Simulator::new().unwrap();
+        simulator
+            .filesystem_mut()
+            .write_file(
+                "programs/broken.lua",
+                "local function inner()\n  local x = nil\n  return x.y\nend\n\nlocal function outer()\n  inner()\nend\n\nouter()\n",
+            )
+            .unwrap();
+
+        let result = simulator.exec_lua_file("programs/broken.lua");
+        let Err(SimulatorError::LuaRuntime(err)) = &result else {
+            panic!("expected a Lua runtime error");
+        };
+
+        assert_eq!(
+            err.message,
+            "programs/broken.lua:3: attempt to index local 'x' (a nil value)"
+        );
+        assert_eq!(
+            err.to_string(),
+            [
+                "programs/broken.lua:3: attempt to index local 'x' (a nil value)",
+                "  at inner (programs/broken.lua:3)",
+                "  at outer (programs/broken.lua:7)",
+                "  at main chunk (programs/broken.lua:10)",
+            ]
+            .join("\n")
+        );
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .starts_with("Lua runtime error: programs/broken.lua:3:")
+        );
+    }
+
+    #[test]
+    fn test_native_error_traceback() {
+        let simulator = Simulator::new().unwrap();
+        simulator
+            .filesystem_mut()
+            .write_file("lib/open.lua", "return function() fs.open(1) end")
+            .unwrap();
+
+        let Err(SimulatorError::LuaRuntime(err)) = simulator.exec_lua(r#"require("lib.open")()"#)
+        else {
+            panic!("expected a Lua runtime error");
+        };
+
+        assert_eq!(
+            err.message,
+            "lib/open.lua:1: bad argument #1 (string expected, got number)"
+        );
+        assert_eq!(
+            err.traceback[0],
+            StackFrame {
+                file: "[C]".to_string(),
+                line: None,
+                function: Some("open".to_string()),
+            }
+        );
+        assert_eq!(err.traceback[1].file, "lib/open.lua");
+        assert_eq!(err.traceback[1].line, Some(1));
+    }
+}
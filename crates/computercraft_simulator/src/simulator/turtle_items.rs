@@ -0,0 +1,293 @@
+use std::rc::Rc;
+
+use minecraft::ItemStack;
+use minecraft::world::Position;
+use mlua::{Table, Value};
+
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::expect::expect_number;
+use crate::simulator::{SharedWorld, SimulatorState, TurtleResultExt};
+use crate::{
+    InteractDirection, Peripheral, SIDES, Simulator, SimulatorResult, TurtleDropError,
+    TurtleSuckError,
+};
+
+impl Simulator {
+    /// Returns the items lying on the ground in the block at the position.
+    pub fn items_at(&self, position: Position) -> Vec<ItemStack> {
+        self.state
+            .shared
+            .ground_items
+            .borrow()
+            .get(&position)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Adds the turtle functions which move items between the turtle and the
+    /// inventory or ground in front of, above or below it.
+    pub(super) fn init_turtle_drop_and_suck(&self, turtle_table: &Table) -> SimulatorResult<()> {
+        for (name, direction) in [
+            ("drop", InteractDirection::Forward),
+            ("dropUp", InteractDirection::Up),
+            ("dropDown", InteractDirection::Down),
+        ] {
+            turtle_table.set(
+                name,
+                self.lua.create_native_function({
+                    let state = self.state.clone();
+                    move |_lua, count: Value| {
+                        let count = expect_count(count)?;
+                        Ok(state.turtle_drop(direction, count).to_lua_result())
+                    }
+                })?,
+            )?;
+        }
+
+        for (name, direction) in [
+            ("suck", InteractDirection::Forward),
+            ("suckUp", InteractDirection::Up),
+            ("suckDown", InteractDirection::Down),
+        ] {
+            turtle_table.set(
+                name,
+                self.lua.create_native_function({
+                    let state = self.state.clone();
+                    move |_lua, count: Value| {
+                        let count = expect_count(count)?;
+                        Ok(state.turtle_suck(direction, count).to_lua_result())
+                    }
+                })?,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TurtleResultExt<Option<String>> for Result<(), TurtleDropError> {
+    fn to_lua_result(self) -> (bool, Option<String>) {
+        match self {
+            Ok(_) => (true, None),
+            Err(err) => (false, Some(err.to_string())),
+        }
+    }
+}
+
+impl TurtleResultExt<Option<String>> for Result<(), TurtleSuckError> {
+    fn to_lua_result(self) -> (bool, Option<String>) {
+        match self {
+            Ok(_) => (true, None),
+            Err(err) => (false, Some(err.to_string())),
+        }
+    }
+}
+
+/// Expects the argument to be how many items to move, if there is one.
+fn expect_count(value: Value) -> mlua::Result<u32> {
+    if value == Value::Nil {
+        return Ok(64);
+    }
+
+    let count = expect_number(1, value)?;
+    if !(0.0..=64.0).contains(&count) {
+        return Err(mlua::Error::RuntimeError(format!(
+            "Item count {count} out of range"
+        )));
+    }
+
+    Ok(count as u32)
+}
+
+impl SimulatorState {
+    /// Drops up to `limit` of the items in the selected slot into the
+    /// inventory in the direction, or onto the ground if there isn't one.
+    fn turtle_drop(&self, direction: InteractDirection, limit: u32) -> Result<(), TurtleDropError> {
+        let (position, slot) = {
+            let turtle = self.turtle();
+            (turtle.target(direction), turtle.selected_slot)
+        };
+
+        let Some(stack) = self.turtle_mut().take_items(slot, limit) else {
+            return Err(TurtleDropError::NoItems);
+        };
+
+        let count = stack.count;
+        let Some(leftover) = self.shared.insert_items_at(position, stack) else {
+            return Ok(());
+        };
+
+        // Put back what didn't fit.
+        let moved = count - leftover.count;
+        let mut turtle = self.turtle_mut();
+        match &mut turtle.inventory[slot] {
+            Some(stack) => stack.count += leftover.count,
+            empty @ None => *empty = Some(leftover),
+        }
+
+        if moved == 0 {
+            return Err(TurtleDropError::NoSpace);
+        }
+
+        Ok(())
+    }
+
+    /// Takes up to `limit` items from the inventory in the direction, or off
+    /// the ground if there isn't one, into the turtle's inventory.
+    fn turtle_suck(&self, direction: InteractDirection, limit: u32) -> Result<(), TurtleSuckError> {
+        let position = self.turtle().target(direction);
+
+        let Some(stack) = self.shared.take_items_at(position, limit) else {
+            return Err(TurtleSuckError::NoItems);
+        };
+
+        let count = stack.count;
+        let Some(leftover) = self.turtle_mut().store_items(stack) else {
+            return Ok(());
+        };
+
+        // Put back what didn't fit.
+        let moved = count - leftover.count;
+        if let Some(leftover) = self.shared.insert_items_at(position, leftover) {
+            self.shared.drop_item(position, leftover);
+        }
+
+        if moved == 0 {
+            return Err(TurtleSuckError::NoSpace);
+        }
+
+        Ok(())
+    }
+}
+
+/// A peripheral with a block in the world.
+enum PeripheralAt {
+    /// Placed in the world, by name.
+    Placed(String),
+    /// Attached to the side of a computer.
+    Attached(Rc<SimulatorState>, &'static str),
+}
+
+impl SharedWorld {
+    /// Finds the peripheral whose block is at the position.
+    fn peripheral_at(&self, position: Position) -> Option<PeripheralAt> {
+        let placed = self
+            .peripherals
+            .borrow()
+            .iter()
+            .find(|(_, placed)| placed.position == position)
+            .map(|(name, _)| name.clone());
+        if let Some(name) = placed {
+            return Some(PeripheralAt::Placed(name));
+        }
+
+        let computers: Vec<_> = self
+            .computers
+            .borrow()
+            .iter()
+            .filter_map(|computer| computer.upgrade())
+            .collect();
+        computers.into_iter().find_map(|computer| {
+            let side = SIDES.into_iter().find(|&side| {
+                computer.peripherals.borrow().contains_key(side)
+                    && computer.side_position(side) == Some(position)
+            })?;
+
+            Some(PeripheralAt::Attached(computer, side))
+        })
+    }
+
+    /// Puts the items into the peripheral at the position, or drops them on
+    /// the ground if there isn't one. Returns the items which didn't fit.
+    fn insert_items_at(&self, position: Position, stack: ItemStack) -> Option<ItemStack> {
+        match self.peripheral_at(position) {
+            Some(PeripheralAt::Placed(name)) => self
+                .peripherals
+                .borrow_mut()
+                .get_mut(&name)
+                .expect("the peripheral should exist")
+                .peripheral
+                .insert_items(stack),
+            Some(PeripheralAt::Attached(computer, side)) => {
+                let leftover = computer
+                    .peripherals
+                    .borrow_mut()
+                    .get_mut(side)
+                    .expect("the peripheral should exist")
+                    .insert_items(stack);
+                computer.mount_disk(side);
+
+                leftover
+            }
+            None => {
+                self.drop_item(position, stack);
+                None
+            }
+        }
+    }
+
+    /// Takes up to `limit` items out of the peripheral at the position, or
+    /// off the ground if there isn't one.
+    fn take_items_at(&self, position: Position, limit: u32) -> Option<ItemStack> {
+        match self.peripheral_at(position) {
+            Some(PeripheralAt::Placed(name)) => self
+                .peripherals
+                .borrow_mut()
+                .get_mut(&name)
+                .expect("the peripheral should exist")
+                .peripheral
+                .take_items(limit),
+            Some(PeripheralAt::Attached(computer, side)) => {
+                // The disk's files are unmounted while it is still in the
+                // drive, so only do so if it is about to be taken out.
+                let takes_disk = matches!(
+                    computer.peripherals.borrow().get(side),
+                    Some(Peripheral::DiskDrive(drive)) if limit > 0 && drive.disk().is_some()
+                );
+                if takes_disk {
+                    computer.unmount_disk(side);
+                }
+
+                computer
+                    .peripherals
+                    .borrow_mut()
+                    .get_mut(side)
+                    .expect("the peripheral should exist")
+                    .take_items(limit)
+            }
+            None => {
+                let mut stack = self.pick_up_item(position)?;
+                let taken = stack.split(limit);
+                if !stack.is_empty() {
+                    self.drop_item(position, stack);
+                }
+
+                Some(taken).filter(|taken| !taken.is_empty())
+            }
+        }
+    }
+
+    /// Drops the items on the ground in the block at the position.
+    pub(super) fn drop_item(&self, position: Position, stack: ItemStack) {
+        if !stack.is_empty() {
+            self.ground_items
+                .borrow_mut()
+                .entry(position)
+                .or_default()
+                .push(stack);
+        }
+    }
+
+    /// Picks up the items which were dropped first in the block at the
+    /// position.
+    fn pick_up_item(&self, position: Position) -> Option<ItemStack> {
+        let mut ground_items = self.ground_items.borrow_mut();
+        let items = ground_items.get_mut(&position)?;
+        let stack = items.remove(0);
+        if items.is_empty() {
+            ground_items.remove(&position);
+        }
+
+        Some(stack)
+    }
+}
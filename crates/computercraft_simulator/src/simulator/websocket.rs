@@ -0,0 +1,404 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::{Rc, Weak};
+
+use mlua::{Function, Lua, Table};
+
+use crate::http::Url;
+use crate::simulator::CreateNativeFunction;
+use crate::simulator::SimulatorState;
+use crate::{Event, EventValue, Simulator};
+
+/// The name of the registry value holding the Lua function which adds
+/// `receive` to websocket handles, as it has to wait for events.
+pub(super) const WRAP_WEBSOCKET: &str = "computercraft_simulator:wrap_websocket";
+
+/// A message sent over a websocket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebsocketMessage {
+    pub data: Vec<u8>,
+    pub binary: bool,
+}
+
+/// The server end of a websocket, which tests use to play the server
+/// computers connect to with `http.websocket`.
+///
+/// A server accepts one connection at a time. Messages sent before a
+/// computer connects are delivered once it does.
+#[derive(Debug, Clone)]
+pub struct WebsocketServer {
+    channel: Rc<RefCell<Channel>>,
+}
+
+#[derive(Debug)]
+struct Channel {
+    url: String,
+    /// The computer connected to the server, if one is.
+    computer: Option<Weak<SimulatorState>>,
+    /// The number of connections made so far, which tells handles to earlier
+    /// connections they are closed.
+    connections: u64,
+    /// The headers the computer connected with.
+    headers: BTreeMap<String, String>,
+    /// The messages sent to the computer before it connected.
+    pending: VecDeque<WebsocketMessage>,
+    /// The messages the computer sent which haven't been received yet.
+    received: VecDeque<WebsocketMessage>,
+}
+
+impl WebsocketServer {
+    /// Returns the URL computers connect to.
+    pub fn url(&self) -> String {
+        self.channel.borrow().url.clone()
+    }
+
+    /// Returns whether a computer is connected.
+    pub fn is_connected(&self) -> bool {
+        self.channel.borrow().computer.is_some()
+    }
+
+    /// Returns the headers the last computer to connect sent.
+    pub fn headers(&self) -> BTreeMap<String, String> {
+        self.channel.borrow().headers.clone()
+    }
+
+    /// Sends the message to the connected computer, queueing a
+    /// `websocket_message` event, or to the next computer to connect.
+    pub fn send(&self, data: impl Into<Vec<u8>>, binary: bool) {
+        let message = WebsocketMessage {
+            data: data.into(),
+            binary,
+        };
+
+        let mut channel = self.channel.borrow_mut();
+        match channel.connected() {
+            Some(computer) => computer
+                .events
+                .borrow_mut()
+                .push_back(channel.event(message)),
+            None => channel.pending.push_back(message),
+        }
+    }
+
+    /// Removes the oldest message the computer sent which hasn't been
+    /// received yet.
+    pub fn receive(&self) -> Option<WebsocketMessage> {
+        self.channel.borrow_mut().received.pop_front()
+    }
+
+    /// Closes the connection to the computer, queueing a `websocket_closed`
+    /// event with the code and reason.
+    pub fn close(&self, code: u16, reason: &str) {
+        let mut channel = self.channel.borrow_mut();
+        if let Some(computer) = channel.connected() {
+            computer.events.borrow_mut().push_back(Event::new(
+                "websocket_closed",
+                vec![
+                    channel.url.as_str().into(),
+                    reason.into(),
+                    u32::from(code).into(),
+                ],
+            ));
+        }
+        channel.computer = None;
+    }
+}
+
+impl Channel {
+    /// Returns the computer connected to the server, if it's still running.
+    fn connected(&self) -> Option<Rc<SimulatorState>> {
+        self.computer.as_ref().and_then(Weak::upgrade)
+    }
+
+    /// Creates the `websocket_message` event for the message.
+    fn event(&self, message: WebsocketMessage) -> Event {
+        Event::new(
+            "websocket_message",
+            vec![
+                self.url.as_str().into(),
+                EventValue::String(message.data),
+                message.binary.into(),
+            ],
+        )
+    }
+}
+
+/// A computer's connection to a [`WebsocketServer`], which becomes a handle
+/// to the websocket when given to the computer.
+#[derive(Debug, Clone)]
+pub struct WebsocketConnection {
+    channel: Rc<RefCell<Channel>>,
+    /// Which of the server's connections this is.
+    id: u64,
+}
+
+impl PartialEq for WebsocketConnection {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.channel, &other.channel) && self.id == other.id
+    }
+}
+
+impl WebsocketConnection {
+    fn is_open(&self) -> bool {
+        let channel = self.channel.borrow();
+        channel.computer.is_some() && channel.connections == self.id
+    }
+
+    fn check_open(&self) -> mlua::Result<()> {
+        if !self.is_open() {
+            return Err(mlua::Error::RuntimeError(
+                "attempt to use a closed file".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Simulator {
+    /// Starts a websocket server at the URL, which computers in the world can
+    /// connect to. Starting another server at the URL replaces it.
+    pub fn listen_websocket(&self, url: &str) -> WebsocketServer {
+        let server = WebsocketServer {
+            channel: Rc::new(RefCell::new(Channel {
+                url: url.to_string(),
+                computer: None,
+                connections: 0,
+                headers: BTreeMap::new(),
+                pending: VecDeque::new(),
+                received: VecDeque::new(),
+            })),
+        };
+
+        self.state
+            .shared
+            .websocket_servers
+            .borrow_mut()
+            .insert(url.to_string(), server.clone());
+
+        server
+    }
+}
+
+impl SimulatorState {
+    /// Connects the computer to the websocket server at the URL, queueing a
+    /// `websocket_success` or `websocket_failure` event. Returns why the
+    /// connection couldn't be attempted if the URL is invalid.
+    pub(super) fn connect_websocket(
+        self: &Rc<Self>,
+        url: &str,
+        headers: BTreeMap<String, String>,
+    ) -> Result<(), String> {
+        let scheme = Url::parse(url)
+            .map_err(|_| "Must specify ws:// or wss://".to_string())?
+            .scheme;
+        if scheme != "ws" && scheme != "wss" {
+            return Err(format!("Invalid scheme '{scheme}'"));
+        }
+
+        let server = self.shared.websocket_servers.borrow().get(url).cloned();
+        let mut events = self.events.borrow_mut();
+        let Some(server) = server.filter(|server| !server.is_connected()) else {
+            events.push_back(Event::new(
+                "websocket_failure",
+                vec![url.into(), "Could not connect".into()],
+            ));
+            return Ok(());
+        };
+
+        let mut channel = server.channel.borrow_mut();
+        channel.computer = Some(Rc::downgrade(self));
+        channel.connections += 1;
+        channel.headers = headers;
+
+        let connection = WebsocketConnection {
+            channel: server.channel.clone(),
+            id: channel.connections,
+        };
+        events.push_back(Event::new(
+            "websocket_success",
+            vec![url.into(), EventValue::Websocket(connection)],
+        ));
+        while let Some(message) = channel.pending.pop_front() {
+            events.push_back(channel.event(message));
+        }
+
+        Ok(())
+    }
+
+    /// Closes the computer's websockets, as it is turning off.
+    pub(super) fn close_websockets(&self) {
+        for server in self.shared.websocket_servers.borrow().values() {
+            let mut channel = server.channel.borrow_mut();
+            let is_this_computer = channel
+                .computer
+                .as_ref()
+                .is_some_and(|computer| std::ptr::eq(computer.as_ptr(), self));
+            if is_this_computer {
+                channel.computer = None;
+            }
+        }
+    }
+}
+
+/// Creates the handle to the websocket, as given to programs in
+/// `websocket_success` events.
+pub(super) fn create_websocket_handle<'lua>(
+    lua: &'lua Lua,
+    connection: WebsocketConnection,
+) -> mlua::Result<Table<'lua>> {
+    let handle = lua.create_table()?;
+
+    handle.set(
+        "send",
+        lua.create_native_function({
+            let connection = connection.clone();
+            move |_lua, (message, binary): (mlua::String, Option<bool>)| {
+                connection.check_open()?;
+                connection
+                    .channel
+                    .borrow_mut()
+                    .received
+                    .push_back(WebsocketMessage {
+                        data: message.as_bytes().to_vec(),
+                        binary: binary.unwrap_or(false),
+                    });
+
+                Ok(())
+            }
+        })?,
+    )?;
+    handle.set(
+        "close",
+        lua.create_native_function({
+            let connection = connection.clone();
+            move |_lua, ()| {
+                if connection.is_open() {
+                    connection.channel.borrow_mut().computer = None;
+                }
+
+                Ok(())
+            }
+        })?,
+    )?;
+
+    let is_open = lua.create_native_function({
+        let connection = connection.clone();
+        move |_lua, ()| Ok(connection.is_open())
+    })?;
+
+    let url = connection.channel.borrow().url.clone();
+    let wrap: Function = lua.named_registry_value(WRAP_WEBSOCKET)?;
+    wrap.call::<_, ()>((handle.clone(), url, is_open))?;
+
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{Simulator, WebsocketMessage};
+
+    #[test]
+    fn test_websocket() {
+        let simulator = Simulator::new().unwrap();
+        let server = simulator.listen_websocket("ws://dashboard.local/turtles");
+        server.send("forward", false);
+        server.send([0x01, 0x02], true);
+
+        simulator
+            .exec_lua(
+                r#"
+                local ws = assert(http.websocket("ws://dashboard.local/turtles", { Token = "abc" }))
+                print(ws.receive())
+                local message, binary = ws.receive(5)
+                print(#message, binary)
+                print(ws.receive(0.5))
+                ws.send("fuel: 80")
+                ws.send("\0", true)
+                ws.close()
+                print(pcall(ws.send, "fuel: 79"))
+                print(pcall(ws.receive))
+
+                print(http.websocket("ws://elsewhere.local"))
+                print(http.websocket("https://dashboard.local/turtles"))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "forward\tfalse",
+                "2\ttrue",
+                "nil",
+                "false\tattempt to use a closed file",
+                "false\tattempt to use a closed file",
+                "false\tCould not connect",
+                "false\tInvalid scheme 'https'",
+                "",
+            ]
+            .join("\n")
+        );
+        assert!(!server.is_connected());
+        assert_eq!(server.headers()["Token"], "abc");
+        assert_eq!(
+            server.receive(),
+            Some(WebsocketMessage {
+                data: b"fuel: 80".to_vec(),
+                binary: false,
+            })
+        );
+        assert_eq!(
+            server.receive(),
+            Some(WebsocketMessage {
+                data: vec![0],
+                binary: true,
+            })
+        );
+        assert_eq!(server.receive(), None);
+    }
+
+    #[test]
+    fn test_websocket_closed_by_server() {
+        let simulator = Simulator::new().unwrap();
+        let server = simulator.listen_websocket("wss://dashboard.local");
+
+        simulator
+            .exec_lua(
+                r#"
+                http.websocketAsync("wss://dashboard.local")
+                local _, url
+                _, url, ws = os.pullEvent("websocket_success")
+                print(url)
+                "#,
+            )
+            .unwrap();
+        assert!(server.is_connected());
+
+        server.send("stop", false);
+        server.close(1000, "Shutting down");
+        simulator
+            .exec_lua(
+                r#"
+                print(os.pullEvent("websocket_message"))
+                print(os.pullEvent())
+                print(pcall(ws.receive))
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulator.output(),
+            [
+                "wss://dashboard.local",
+                "websocket_message\twss://dashboard.local\tstop\tfalse",
+                "websocket_closed\twss://dashboard.local\tShutting down\t1000",
+                "false\tattempt to use a closed file",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
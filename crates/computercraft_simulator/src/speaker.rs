@@ -0,0 +1,175 @@
+use minecraft::ItemId;
+
+/// The item of a speaker, which turtles can also equip as an upgrade.
+pub const SPEAKER: ItemId = ItemId::new_static("computercraft:speaker");
+
+/// The instruments a speaker can play notes on, like a note block.
+pub const INSTRUMENTS: [&str; 16] = [
+    "harp",
+    "basedrum",
+    "snare",
+    "hat",
+    "bass",
+    "flute",
+    "bell",
+    "guitar",
+    "chime",
+    "xylophone",
+    "iron_xylophone",
+    "cow_bell",
+    "didgeridoo",
+    "bit",
+    "banjo",
+    "pling",
+];
+
+/// Something a speaker played.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SoundKind {
+    /// A note, played with `playNote`.
+    Note {
+        instrument: String,
+        volume: f64,
+        pitch: f64,
+    },
+    /// A Minecraft sound, played with `playSound`.
+    Sound {
+        name: String,
+        volume: f64,
+        pitch: f64,
+    },
+    /// A buffer of audio samples, played with `playAudio`.
+    Audio { samples: usize, volume: f64 },
+}
+
+/// Something a speaker played, and when.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayedSound {
+    /// The tick the speaker started playing it.
+    pub tick: u64,
+    pub kind: SoundKind,
+}
+
+/// A speaker, which keeps a log of everything it played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Speaker {
+    log: Vec<PlayedSound>,
+    /// The tick the audio the speaker is streaming runs out.
+    audio_until: u64,
+    /// The volume of the audio, which later buffers keep if not given one.
+    audio_volume: f64,
+}
+
+impl Default for Speaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Speaker {
+    /// The most notes a speaker can play in a tick.
+    pub const MAX_NOTES_PER_TICK: usize = 8;
+
+    /// The most audio samples a speaker accepts at once.
+    pub const MAX_AUDIO_SAMPLES: usize = 128 * 1024;
+
+    /// How many audio samples a speaker plays each tick, at 48kHz.
+    pub const SAMPLES_PER_TICK: usize = 48_000 / 20;
+
+    pub fn new() -> Self {
+        Self {
+            log: Vec::new(),
+            audio_until: 0,
+            audio_volume: 1.0,
+        }
+    }
+
+    /// Returns everything the speaker played, oldest first.
+    pub fn log(&self) -> &[PlayedSound] {
+        &self.log
+    }
+
+    /// Returns whether the speaker is still streaming audio at the tick.
+    pub fn is_playing_audio(&self, tick: u64) -> bool {
+        self.audio_until > tick
+    }
+
+    /// Plays a note at the tick, returning `false` if too many notes were
+    /// already played during it.
+    pub fn play_note(&mut self, tick: u64, instrument: &str, volume: f64, pitch: f64) -> bool {
+        let notes = self
+            .played_at(tick)
+            .filter(|sound| matches!(sound.kind, SoundKind::Note { .. }))
+            .count();
+        if notes >= Self::MAX_NOTES_PER_TICK {
+            return false;
+        }
+
+        self.log.push(PlayedSound {
+            tick,
+            kind: SoundKind::Note {
+                instrument: instrument.to_string(),
+                volume,
+                pitch,
+            },
+        });
+        true
+    }
+
+    /// Plays a sound at the tick, returning `false` if another sound was
+    /// already played during it, or audio is still playing.
+    pub fn play_sound(&mut self, tick: u64, name: &str, volume: f64, pitch: f64) -> bool {
+        let busy = self.is_playing_audio(tick)
+            || self
+                .played_at(tick)
+                .any(|sound| matches!(sound.kind, SoundKind::Sound { .. }));
+        if busy {
+            return false;
+        }
+
+        self.log.push(PlayedSound {
+            tick,
+            kind: SoundKind::Sound {
+                name: name.to_string(),
+                volume,
+                pitch,
+            },
+        });
+        true
+    }
+
+    /// Starts streaming the number of audio samples at the tick, returning
+    /// the tick they run out, or `None` if the previous ones are still
+    /// playing.
+    pub fn play_audio(&mut self, tick: u64, samples: usize, volume: Option<f64>) -> Option<u64> {
+        if self.is_playing_audio(tick) {
+            return None;
+        }
+
+        if let Some(volume) = volume {
+            self.audio_volume = volume;
+        }
+        self.audio_until = tick + samples.div_ceil(Self::SAMPLES_PER_TICK) as u64;
+        self.log.push(PlayedSound {
+            tick,
+            kind: SoundKind::Audio {
+                samples,
+                volume: self.audio_volume,
+            },
+        });
+
+        Some(self.audio_until)
+    }
+
+    /// Stops the audio the speaker is streaming.
+    pub fn stop(&mut self) {
+        self.audio_until = 0;
+    }
+
+    fn played_at(&self, tick: u64) -> impl Iterator<Item = &PlayedSound> {
+        self.log
+            .iter()
+            .rev()
+            .take_while(move |sound| sound.tick == tick)
+    }
+}
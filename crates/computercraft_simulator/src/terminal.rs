@@ -0,0 +1,510 @@
+use crate::{Color, unpack_rgb};
+
+/// The width of a computer's terminal, in characters.
+pub const TERMINAL_WIDTH: usize = 51;
+
+/// The height of a computer's terminal, in characters.
+pub const TERMINAL_HEIGHT: usize = 19;
+
+/// A single character cell of a terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub char: u8,
+    pub text_color: Color,
+    pub background_color: Color,
+}
+
+/// An in-memory terminal screen.
+///
+/// Like in ComputerCraft, characters are stored as bytes and colors are stored
+/// as their `term.blit` hex characters.
+#[derive(Debug, Clone)]
+pub struct Terminal {
+    width: usize,
+    height: usize,
+    /// Whether the terminal supports colors.
+    is_color: bool,
+    /// The zero-based cursor position. The cursor may be positioned off-screen.
+    cursor_x: i32,
+    cursor_y: i32,
+    cursor_blink: bool,
+    text_color: Color,
+    background_color: Color,
+    palette: [[f64; 3]; 16],
+    text: Vec<Vec<u8>>,
+    text_colors: Vec<Vec<u8>>,
+    background_colors: Vec<Vec<u8>>,
+}
+
+impl Terminal {
+    pub fn new(width: usize, height: usize, is_color: bool) -> Self {
+        Self {
+            width,
+            height,
+            is_color,
+            cursor_x: 0,
+            cursor_y: 0,
+            cursor_blink: false,
+            text_color: Color::White,
+            background_color: Color::Black,
+            palette: Color::ALL.map(|color| unpack_rgb(color.default_rgb())),
+            text: vec![vec![b' '; width]; height],
+            text_colors: vec![vec![Color::White.to_blit(); width]; height],
+            background_colors: vec![vec![Color::Black.to_blit(); width]; height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_color(&self) -> bool {
+        self.is_color
+    }
+
+    pub fn set_color(&mut self, is_color: bool) {
+        self.is_color = is_color;
+    }
+
+    /// Resizes the terminal, preserving the existing contents where possible.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let text_color = self.text_color.to_blit();
+        let background_color = self.background_color.to_blit();
+
+        for (buffer, fill) in [
+            (&mut self.text, b' '),
+            (&mut self.text_colors, text_color),
+            (&mut self.background_colors, background_color),
+        ] {
+            buffer.resize(height, vec![fill; width]);
+            for line in buffer {
+                line.resize(width, fill);
+            }
+        }
+
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Returns the zero-based cursor position.
+    pub fn cursor_pos(&self) -> (i32, i32) {
+        (self.cursor_x, self.cursor_y)
+    }
+
+    /// Sets the zero-based cursor position.
+    pub fn set_cursor_pos(&mut self, x: i32, y: i32) {
+        self.cursor_x = x;
+        self.cursor_y = y;
+    }
+
+    pub fn cursor_blink(&self) -> bool {
+        self.cursor_blink
+    }
+
+    pub fn set_cursor_blink(&mut self, blink: bool) {
+        self.cursor_blink = blink;
+    }
+
+    pub fn text_color(&self) -> Color {
+        self.text_color
+    }
+
+    pub fn set_text_color(&mut self, color: Color) {
+        self.text_color = color;
+    }
+
+    pub fn background_color(&self) -> Color {
+        self.background_color
+    }
+
+    pub fn set_background_color(&mut self, color: Color) {
+        self.background_color = color;
+    }
+
+    /// Returns the RGB value of the color in the terminal's palette, with each channel in the range `0.0..=1.0`.
+    pub fn palette_color(&self, color: Color) -> [f64; 3] {
+        self.palette[color.index()]
+    }
+
+    pub fn set_palette_color(&mut self, color: Color, rgb: [f64; 3]) {
+        self.palette[color.index()] = rgb;
+    }
+
+    /// Writes the text at the cursor position without wrapping, moving the cursor to the end of the text.
+    pub fn write(&mut self, text: &[u8]) {
+        let text_colors = vec![self.text_color.to_blit(); text.len()];
+        let background_colors = vec![self.background_color.to_blit(); text.len()];
+
+        self.blit(text, &text_colors, &background_colors);
+    }
+
+    /// Writes the text at the cursor position, using the given blit colors for each character.
+    ///
+    /// All three arguments must be the same length.
+    pub fn blit(&mut self, text: &[u8], text_colors: &[u8], background_colors: &[u8]) {
+        debug_assert_eq!(text.len(), text_colors.len());
+        debug_assert_eq!(text.len(), background_colors.len());
+
+        if let Ok(y) = usize::try_from(self.cursor_y)
+            && y < self.height
+        {
+            for ix in 0..text.len() {
+                let Ok(x) = usize::try_from(self.cursor_x + ix as i32) else {
+                    continue;
+                };
+                if x >= self.width {
+                    break;
+                }
+
+                self.text[y][x] = text[ix];
+                self.text_colors[y][x] = text_colors[ix];
+                self.background_colors[y][x] = background_colors[ix];
+            }
+        }
+
+        self.cursor_x += text.len() as i32;
+    }
+
+    /// Writes the text at the cursor position, wrapping words onto new lines
+    /// and scrolling as needed.
+    ///
+    /// This matches the behavior of the `write` function from ComputerCraft's `bios.lua`.
+    ///
+    /// Returns the number of lines printed.
+    pub fn write_wrapped(&mut self, text: &[u8]) -> usize {
+        let mut lines_printed = 0;
+        for step in wrap_text(text, self.width as i32, self.cursor_x) {
+            match step {
+                WrapStep::Write(text) => self.write(text),
+                WrapStep::NewLine => {
+                    if self.cursor_y + 1 < self.height as i32 {
+                        self.set_cursor_pos(0, self.cursor_y + 1);
+                    } else {
+                        self.set_cursor_pos(0, self.height as i32 - 1);
+                        self.scroll(1);
+                    }
+
+                    lines_printed += 1;
+                }
+            }
+        }
+
+        lines_printed
+    }
+
+    /// Moves the contents of the terminal up by `n` lines (or down, if `n` is negative).
+    pub fn scroll(&mut self, n: i32) {
+        let shift = n.unsigned_abs() as usize;
+        let height = self.height;
+        let text_color = self.text_color.to_blit();
+        let background_color = self.background_color.to_blit();
+
+        for (buffer, fill) in [
+            (&mut self.text, b' '),
+            (&mut self.text_colors, text_color),
+            (&mut self.background_colors, background_color),
+        ] {
+            let blank = vec![fill; self.width];
+
+            if shift >= height {
+                buffer.fill(blank);
+            } else if n > 0 {
+                buffer.drain(..shift);
+                buffer.resize(height, blank);
+            } else if n < 0 {
+                buffer.truncate(height - shift);
+                buffer.splice(0..0, std::iter::repeat_n(blank, shift));
+            }
+        }
+    }
+
+    /// Resets the terminal to how it is when the computer turns on, keeping its size.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.width, self.height, self.is_color);
+    }
+
+    /// Clears the terminal, filling it with the current background color.
+    pub fn clear(&mut self) {
+        for y in 0..self.height {
+            self.fill_line(y);
+        }
+    }
+
+    /// Clears the line the cursor is on, filling it with the current background color.
+    pub fn clear_line(&mut self) {
+        if let Ok(y) = usize::try_from(self.cursor_y)
+            && y < self.height
+        {
+            self.fill_line(y);
+        }
+    }
+
+    fn fill_line(&mut self, y: usize) {
+        self.text[y].fill(b' ');
+        self.text_colors[y].fill(self.text_color.to_blit());
+        self.background_colors[y].fill(self.background_color.to_blit());
+    }
+
+    /// Returns the cell at the given zero-based position, if it exists.
+    pub fn cell(&self, x: usize, y: usize) -> Option<Cell> {
+        let char = *self.text.get(y)?.get(x)?;
+        let text_color = Color::from_blit(self.text_colors[y][x]).unwrap_or(Color::White);
+        let background_color =
+            Color::from_blit(self.background_colors[y][x]).unwrap_or(Color::Black);
+
+        Some(Cell {
+            char,
+            text_color,
+            background_color,
+        })
+    }
+
+    /// Returns the text and blit colors on the given zero-based line, if it exists.
+    pub fn blit_line(&self, y: usize) -> Option<(&[u8], &[u8], &[u8])> {
+        Some((
+            self.text.get(y)?,
+            self.text_colors.get(y)?,
+            self.background_colors.get(y)?,
+        ))
+    }
+
+    /// Returns the text on the given zero-based line, if it exists.
+    pub fn line(&self, y: usize) -> Option<String> {
+        self.text
+            .get(y)
+            .map(|line| line.iter().map(|byte| *byte as char).collect())
+    }
+
+    /// Returns the contents of the screen as text, with trailing whitespace removed from each line.
+    pub fn contents(&self) -> String {
+        let mut contents = String::new();
+        for y in 0..self.height {
+            let line = self.line(y).unwrap_or_default();
+            contents.push_str(line.trim_end());
+            contents.push('\n');
+        }
+
+        contents
+    }
+
+    /// Returns the contents of the screen as text with ANSI escape codes, using
+    /// 24-bit colors from the terminal's palette.
+    pub fn ansi_contents(&self) -> String {
+        let mut contents = String::new();
+        for y in 0..self.height {
+            let mut current_colors = None;
+            for x in 0..self.width {
+                let cell = self.cell(x, y).expect("cell should be on-screen");
+
+                let colors = (cell.text_color, cell.background_color);
+                if current_colors != Some(colors) {
+                    let [fr, fg, fb] = self.palette_color(cell.text_color).map(to_byte);
+                    let [br, bg, bb] = self.palette_color(cell.background_color).map(to_byte);
+                    contents.push_str(&format!(
+                        "\x1b[38;2;{fr};{fg};{fb}m\x1b[48;2;{br};{bg};{bb}m"
+                    ));
+                    current_colors = Some(colors);
+                }
+
+                contents.push(cell.char as char);
+            }
+
+            contents.push_str("\x1b[0m\n");
+        }
+
+        contents
+    }
+}
+
+/// A step in writing text with word wrapping, as planned by [`wrap_text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WrapStep<'a> {
+    /// Writes the text at the cursor without wrapping.
+    Write(&'a [u8]),
+    /// Moves the cursor to the start of the next line, scrolling if it is on
+    /// the last line.
+    NewLine,
+}
+
+/// Plans writing the text to a terminal of the given width, starting at the
+/// zero-based cursor column, wrapping words onto new lines. Nothing fits on a
+/// terminal with no width, so nothing is written to one.
+///
+/// This is shared by [`Terminal::write_wrapped`] and the `write` function of
+/// redirect targets which aren't backed by a [`Terminal`].
+pub(crate) fn wrap_text(text: &[u8], width: i32, mut x: i32) -> Vec<WrapStep<'_>> {
+    fn write<'a>(steps: &mut Vec<WrapStep<'a>>, x: &mut i32, text: &'a [u8]) {
+        steps.push(WrapStep::Write(text));
+        *x += text.len() as i32;
+    }
+
+    let mut steps = Vec::new();
+    if width <= 0 {
+        return steps;
+    }
+
+    let mut rest = text;
+    while !rest.is_empty() {
+        let whitespace_len = rest
+            .iter()
+            .take_while(|byte| matches!(byte, b' ' | b'\t'))
+            .count();
+        if whitespace_len > 0 {
+            write(&mut steps, &mut x, &rest[..whitespace_len]);
+            rest = &rest[whitespace_len..];
+        }
+
+        if let Some(after_newline) = rest.strip_prefix(b"\n") {
+            steps.push(WrapStep::NewLine);
+            x = 0;
+            rest = after_newline;
+        }
+
+        let word_len = rest
+            .iter()
+            .take_while(|byte| !matches!(byte, b' ' | b'\t' | b'\n'))
+            .count();
+        if word_len > 0 {
+            let mut word = &rest[..word_len];
+            rest = &rest[word_len..];
+
+            if word_len as i32 > width {
+                // Words that don't fit on a single line get split across multiple lines.
+                while !word.is_empty() {
+                    if x >= width {
+                        steps.push(WrapStep::NewLine);
+                        x = 0;
+                    }
+
+                    let written = (width - x).max(0) as usize;
+                    write(&mut steps, &mut x, word);
+                    word = &word[written.min(word.len())..];
+                }
+            } else {
+                if x + word_len as i32 > width {
+                    steps.push(WrapStep::NewLine);
+                    x = 0;
+                }
+
+                write(&mut steps, &mut x, word);
+            }
+        }
+    }
+
+    steps
+}
+
+fn to_byte(channel: f64) -> u8 {
+    (channel.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self::new(TERMINAL_WIDTH, TERMINAL_HEIGHT, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_write_is_clipped_to_screen() {
+        let mut terminal = Terminal::new(5, 2, false);
+
+        terminal.set_cursor_pos(3, 0);
+        terminal.write(b"Hello");
+        assert_eq!(terminal.line(0).unwrap(), "   He");
+        assert_eq!(terminal.cursor_pos(), (8, 0));
+
+        terminal.set_cursor_pos(-2, 1);
+        terminal.write(b"Hello");
+        assert_eq!(terminal.line(1).unwrap(), "llo  ");
+    }
+
+    #[test]
+    fn test_colors() {
+        let mut terminal = Terminal::new(4, 2, true);
+
+        terminal.set_background_color(Color::Blue);
+        terminal.clear();
+        terminal.set_text_color(Color::Red);
+        terminal.write(b"Hi");
+        assert_eq!(
+            terminal.cell(0, 0),
+            Some(Cell {
+                char: b'H',
+                text_color: Color::Red,
+                background_color: Color::Blue,
+            })
+        );
+        assert_eq!(
+            terminal.blit_line(0),
+            Some((&b"Hi  "[..], &b"ee00"[..], &b"bbbb"[..]))
+        );
+
+        terminal.set_cursor_pos(1, 1);
+        terminal.blit(b"ab", b"01", b"fe");
+        assert_eq!(
+            terminal.blit_line(1),
+            Some((&b" ab "[..], &b"0010"[..], &b"bfeb"[..]))
+        );
+    }
+
+    #[test]
+    fn test_ansi_contents() {
+        let mut terminal = Terminal::new(2, 1, true);
+        terminal.set_text_color(Color::Red);
+        terminal.write(b"A");
+
+        assert_eq!(
+            terminal.ansi_contents(),
+            "\x1b[38;2;204;76;76m\x1b[48;2;17;17;17mA\x1b[38;2;240;240;240m\x1b[48;2;17;17;17m \x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_write_wrapped() {
+        let mut terminal = Terminal::new(10, 4, false);
+
+        let lines = terminal.write_wrapped(b"Hello there world\n");
+        assert_eq!(lines, 3);
+        assert_eq!(terminal.contents(), "Hello\nthere\nworld\n\n");
+        assert_eq!(terminal.cursor_pos(), (0, 3));
+
+        let lines = terminal.write_wrapped(b"abcdefghijklmno");
+        assert_eq!(lines, 1);
+        assert_eq!(terminal.contents(), "there\nworld\nabcdefghij\nklmno\n");
+    }
+
+    #[test]
+    fn test_write_wrapped_with_no_width() {
+        let mut terminal = Terminal::new(0, 2, false);
+
+        let lines = terminal.write_wrapped(b"Hello there\n");
+        assert_eq!(lines, 0);
+        assert_eq!(terminal.contents(), "\n\n");
+        assert_eq!(terminal.cursor_pos(), (0, 0));
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut terminal = Terminal::new(3, 3, false);
+        for (y, text) in ["a", "b", "c"].iter().enumerate() {
+            terminal.set_cursor_pos(0, y as i32);
+            terminal.write(text.as_bytes());
+        }
+
+        terminal.scroll(1);
+        assert_eq!(terminal.contents(), "b\nc\n\n");
+
+        terminal.scroll(-2);
+        assert_eq!(terminal.contents(), "\n\nb\n");
+    }
+}
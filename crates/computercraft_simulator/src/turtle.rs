@@ -4,6 +4,9 @@ use minecraft::{ItemId, blocks};
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::inventory::{insert_into_slots, take_from_slots};
+use crate::{page_lines, page_title};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InteractDirection {
     Forward,
@@ -46,7 +49,10 @@ pub struct InspectData {
 }
 
 #[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
-pub enum TurtleInspectError {}
+pub enum TurtleInspectError {
+    #[error("No block to inspect")]
+    NoBlock,
+}
 
 #[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TurtleDigError {
@@ -68,10 +74,61 @@ pub enum TurtlePlaceError {
     CannotPlaceBlock,
 }
 
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurtleDropError {
+    #[error("No items to drop")]
+    NoItems,
+    #[error("No space for items")]
+    NoSpace,
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurtleSuckError {
+    #[error("No items to take")]
+    NoItems,
+    #[error("No space for items")]
+    NoSpace,
+}
+
+/// Describes items like ComputerCraft's `getItemDetail` functions, for both
+/// turtles and inventory peripherals.
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ItemDetail {
     pub name: ItemId,
     pub count: u32,
+    /// How many of the items fit in a slot, only given in detailed
+    /// descriptions like the fields below.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_count: Option<u32>,
+    /// The title of a printed page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The lines of text on a printed page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<Vec<String>>,
+}
+
+impl ItemDetail {
+    pub fn new(stack: &ItemStack, detailed: bool) -> Self {
+        let mut detail = Self {
+            name: stack.name.clone(),
+            count: stack.count,
+            max_count: None,
+            title: None,
+            lines: None,
+        };
+
+        if detailed {
+            detail.max_count = Some(stack.max_stack_size());
+            if let Some(lines) = page_lines(stack) {
+                detail.title = page_title(stack).map(str::to_string);
+                detail.lines = Some(lines.into_iter().map(str::to_string).collect());
+            }
+        }
+
+        detail
+    }
 }
 
 #[derive(Debug)]
@@ -121,6 +178,16 @@ impl Turtle {
         self.position.forward(self.facing)
     }
 
+    /// Returns the position of the block the turtle interacts with in the
+    /// direction.
+    pub fn target(&self, direction: InteractDirection) -> Position {
+        match direction {
+            InteractDirection::Forward => self.looking_at(),
+            InteractDirection::Up => self.position.up(),
+            InteractDirection::Down => self.position.down(),
+        }
+    }
+
     pub fn move_to(&mut self, position: Position, world: &World) -> Result<(), TurtleMoveError> {
         if world.is_solid(position) {
             return Err(TurtleMoveError::Obstructed);
@@ -177,11 +244,16 @@ impl Turtle {
 
     pub fn inspect(
         &self,
-        _direction: InteractDirection,
-        _world: &World,
+        direction: InteractDirection,
+        world: &World,
     ) -> Result<InspectData, TurtleInspectError> {
+        let block = world.get_block(self.target(direction));
+        if block.id == BlockId::AIR {
+            return Err(TurtleInspectError::NoBlock);
+        }
+
         Ok(InspectData {
-            name: "minecraft:wheat".to_string(),
+            name: block.id.to_string(),
         })
     }
 
@@ -203,11 +275,7 @@ impl Turtle {
         side: TurtleSide,
         world: &mut World,
     ) -> Result<(), TurtleDigError> {
-        let target_position = match direction {
-            InteractDirection::Forward => self.position.forward(self.facing),
-            InteractDirection::Up => self.position.up(),
-            InteractDirection::Down => self.position.down(),
-        };
+        let target_position = self.target(direction);
 
         let block = world.get_block(target_position);
         if block.id == BlockId::AIR {
@@ -365,21 +433,35 @@ impl Turtle {
             .unwrap_or(64)
     }
 
-    pub fn get_item_detail(&self, slot: usize, _detailed: bool) -> Option<ItemDetail> {
+    /// Takes up to `limit` items out of the slot.
+    pub fn take_items(&mut self, slot: usize, limit: u32) -> Option<ItemStack> {
+        take_from_slots(&mut self.inventory, slot, limit)
+    }
+
+    /// Puts the items into the inventory, starting at the selected slot and
+    /// wrapping around like ComputerCraft does. Returns the items which didn't
+    /// fit.
+    pub fn store_items(&mut self, stack: ItemStack) -> Option<ItemStack> {
+        let selected_slot = self.selected_slot;
+        let order = (0..16).map(|offset| (selected_slot + offset) % 16);
+
+        insert_into_slots(&mut self.inventory, stack, order)
+    }
+
+    pub fn get_item_detail(&self, slot: usize, detailed: bool) -> Option<ItemDetail> {
         if slot >= 16 {
             return None;
         }
 
         let stack = self.inventory[slot].as_ref()?;
 
-        Some(ItemDetail {
-            name: stack.name.clone(),
-            count: stack.count,
-        })
+        Some(ItemDetail::new(stack, detailed))
     }
 }
 
+// The tests pass the world mutably, although moving only needs to read it.
 #[cfg(test)]
+#[allow(clippy::unnecessary_mut_passed)]
 mod tests {
     use minecraft::blocks;
     use pretty_assertions::assert_eq;
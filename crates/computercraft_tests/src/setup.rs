@@ -1,9 +1,21 @@
 use computercraft_simulator::Simulator;
 
-fn script_root() -> String {
+pub fn script_root() -> String {
     format!("{}/../..", env!("CARGO_MANIFEST_DIR"))
 }
 
+/// The paths in the script root which aren't scripts, and which computers
+/// shouldn't be able to see.
+const HIDDEN_PATHS: &[&str] = &["target", ".git"];
+
 pub fn set_script_root(simulator: &mut Simulator) {
     simulator.set_current_dir(script_root());
+
+    let mut filesystem = simulator.filesystem_mut();
+    let root = filesystem
+        .mount_at_mut("")
+        .expect("the root mount should always exist");
+    for path in HIDDEN_PATHS {
+        root.hide_host_path(path);
+    }
 }
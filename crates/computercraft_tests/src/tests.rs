@@ -1,3 +1,8 @@
+mod cylinder_builder_tests;
+mod debug_block_tests;
 mod lib_move_tests;
 mod shaft_miner_tests;
+mod std_tests;
+mod tree_farm_tests;
+mod tunnel_miner_tests;
 mod wheat_farmer_tests;
@@ -0,0 +1,35 @@
+use computercraft_simulator::Simulator;
+use indoc::indoc;
+use pretty_assertions::assert_eq;
+
+use crate::setup::set_script_root;
+
+#[test]
+fn test_cylinder_builder_usage() {
+    let mut simulator = Simulator::new().unwrap();
+    set_script_root(&mut simulator);
+
+    simulator
+        .run_program("programs/cylinder_builder.lua", &[])
+        .unwrap();
+    assert_eq!(
+        simulator.output(),
+        indoc! {"
+            Usage: cylinder_builder <height> <diameter> <filled>
+              height: Number of layers high
+              diameter: Diameter of the cylinder
+              filled: true for filled cylinder, false for hollow
+        "}
+    );
+}
+
+#[test]
+fn test_cylinder_builder_invalid_arguments() {
+    let mut simulator = Simulator::new().unwrap();
+    set_script_root(&mut simulator);
+
+    simulator
+        .run_program("programs/cylinder_builder.lua", &["tall", "3", "true"])
+        .unwrap();
+    assert_eq!(simulator.output(), "Invalid height: tall\n");
+}
@@ -0,0 +1,26 @@
+use std::path::Path;
+
+use computercraft_simulator::Simulator;
+use minecraft::blocks;
+use minecraft::world::Position;
+use pretty_assertions::assert_eq;
+
+use crate::setup::{script_root, set_script_root};
+
+#[test]
+fn test_debug_block_writes_to_simulated_filesystem() {
+    let mut simulator = Simulator::new().unwrap();
+    set_script_root(&mut simulator);
+    simulator.set_block_at(Position::new(0, 0, -1), blocks::STONE.clone());
+
+    simulator
+        .run_program("programs/debug_block.lua", &[])
+        .unwrap();
+
+    let expected: String = simulator
+        .eval_lua("textutils.serialize(select(2, turtle.inspect()))")
+        .unwrap();
+    let contents = simulator.filesystem().read_file("debug.txt").unwrap();
+    assert_eq!(String::from_utf8(contents).unwrap(), expected);
+    assert!(!Path::new(&script_root()).join("debug.txt").exists());
+}
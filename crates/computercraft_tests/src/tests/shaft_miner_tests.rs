@@ -35,3 +35,31 @@ fn test_shaft_miner_with_different_width_and_height_values() {
         .unwrap();
     assert_eq!(simulator.turtle().position, Position::new(2, -1, -4));
 }
+
+#[test]
+fn test_shaft_miner_as_program() {
+    let mut simulator = Simulator::new().unwrap();
+    set_script_root(&mut simulator);
+
+    simulator
+        .run_program("programs/shaft_miner.lua", &["1", "3"])
+        .unwrap();
+    assert_eq!(simulator.turtle().position, Position::new(2, -1, -2));
+
+    simulator.run_program("shaft_miner", &["1", "3"]).unwrap();
+    assert_eq!(simulator.turtle().position, Position::new(0, -2, -0));
+}
+
+#[test]
+fn test_shaft_miner_output() {
+    let mut simulator = Simulator::new().unwrap();
+    set_script_root(&mut simulator);
+
+    simulator
+        .run_program("programs/shaft_miner.lua", &["1", "3"])
+        .unwrap();
+    assert_eq!(
+        simulator.output(),
+        "Starting shaft miner\nDepth: 1\nSize: 3x3\n"
+    );
+}
@@ -0,0 +1,28 @@
+use computercraft_simulator::Simulator;
+use pretty_assertions::assert_eq;
+
+use crate::setup::set_script_root;
+
+#[test]
+fn test_is_main_only_for_the_running_program() {
+    let mut simulator = Simulator::new().unwrap();
+    set_script_root(&mut simulator);
+    simulator
+        .filesystem_mut()
+        .write_file(
+            "programs/check_main.lua",
+            r#"
+                local std = require("std")
+                local cylinder_builder = require("programs.cylinder_builder")
+                print(std.is_main(), type(cylinder_builder))
+            "#,
+        )
+        .unwrap();
+
+    simulator
+        .run_program("programs/check_main.lua", &[])
+        .unwrap();
+
+    // Requiring the cylinder builder must not run it, which would print its usage.
+    assert_eq!(simulator.output(), "true\ttable\n");
+}
@@ -0,0 +1,72 @@
+use computercraft_simulator::{Interruption, Simulator, SimulatorError, TurtleSide};
+use minecraft::world::Position;
+use minecraft::{Block, BlockId, ItemId, ItemStack};
+use pretty_assertions::assert_eq;
+
+use crate::setup::set_script_root;
+
+/// The turtle actions taken to chop down a tree two logs tall: digging in
+/// front, digging up and moving up for each log, moving back down to the
+/// track and planting a sapling.
+const CHOP_TREE_ACTIONS: u64 = 2 * 3 + 2 + 1;
+
+/// The turtle actions taken to move to the next tree, three blocks away:
+/// turning onto the track, moving and sucking up items on each block, and
+/// turning back to face the trees.
+const MOVE_TO_NEXT_TREE_ACTIONS: u64 = 1 + 3 * 2 + 1;
+
+#[test]
+fn test_tree_farm_kind_and_spacing_from_settings() {
+    let mut simulator = Simulator::new().unwrap();
+    set_script_root(&mut simulator);
+
+    let block = |id: &str| Block {
+        id: BlockId::new(id),
+    };
+    for x in 0..=3 {
+        simulator.set_block_at(
+            Position::new(x, -1, 0),
+            block("minecraft:cobbled_deepslate"),
+        );
+    }
+    for x in [0, 3] {
+        for y in 0..2 {
+            simulator.set_block_at(Position::new(x, y, -1), block("minecraft:spruce_log"));
+        }
+    }
+    simulator.turtle_mut().set_upgrade(
+        TurtleSide::Right,
+        Some(ItemId::new_static("minecraft:diamond_pickaxe")),
+    );
+    simulator.turtle_mut().inventory[0] = Some(ItemStack::new(
+        ItemId::new_static("minecraft:spruce_sapling"),
+        8,
+    ));
+
+    simulator
+        .exec_lua(
+            r#"
+            settings.set("tree_farm.tree_kind", "spruce")
+            settings.set("tree_farm.tree_spacing", 3)
+            "#,
+        )
+        .unwrap();
+
+    // The farm runs forever, so stop once the second tree has been chopped.
+    simulator.schedule_interruption(Interruption::AfterAction(
+        CHOP_TREE_ACTIONS + MOVE_TO_NEXT_TREE_ACTIONS + CHOP_TREE_ACTIONS,
+    ));
+    let result = simulator.run_program("tree_farm", &[]);
+
+    assert!(matches!(result, Err(SimulatorError::Interrupted)));
+    assert_eq!(
+        simulator.output(),
+        "Chopping tree...\nMoving to next tree...\nChopping tree...\n"
+    );
+    for x in [0, 3] {
+        for y in 0..2 {
+            assert_eq!(simulator.block_at(Position::new(x, y, -1)).id, BlockId::AIR);
+        }
+    }
+    assert_eq!(simulator.turtle().position, Position::new(3, 0, 0));
+}
@@ -0,0 +1,74 @@
+use computercraft_simulator::{Interruption, Simulator, SimulatorError, TurtleSide};
+use minecraft::world::Position;
+use minecraft::{BlockId, ItemId, ItemStack, blocks};
+use pretty_assertions::assert_eq;
+
+use crate::setup::set_script_root;
+
+/// The turtle actions taken to mine a column two blocks high: digging in
+/// front, digging up and moving up, digging in front again, and moving down
+/// once for each block of the column.
+const MINE_COLUMN_ACTIONS: u64 = 3 + 1 + 2;
+
+/// The turtle actions taken to mine the first row of a tunnel two blocks
+/// wide: mining a column, moving forward and turning into the row, mining
+/// the second column, moving up to place a torch and back down, then turning
+/// around, moving back across the row and turning to face along the tunnel.
+const FIRST_ROW_ACTIONS: u64 = MINE_COLUMN_ACTIONS + 2 + MINE_COLUMN_ACTIONS + 3 + 2 + 1 + 1;
+
+#[test]
+fn test_tunnel_miner_size_from_settings() {
+    let mut simulator = Simulator::new().unwrap();
+    set_script_root(&mut simulator);
+
+    for x in -2..=1 {
+        for z in -2..=0 {
+            simulator.set_block_at(Position::new(x, -1, z), blocks::STONE.clone());
+        }
+    }
+    for x in -2..=0 {
+        for y in 0..3 {
+            for z in -2..=-1 {
+                simulator.set_block_at(Position::new(x, y, z), blocks::STONE.clone());
+            }
+        }
+    }
+    simulator.turtle_mut().set_upgrade(
+        TurtleSide::Right,
+        Some(ItemId::new_static("minecraft:diamond_pickaxe")),
+    );
+    simulator.turtle_mut().inventory[0] =
+        Some(ItemStack::new(ItemId::new_static("minecraft:torch"), 16));
+
+    simulator
+        .exec_lua(
+            r#"
+            settings.set("tunnel_miner.width", 2)
+            settings.set("tunnel_miner.height", 2)
+            "#,
+        )
+        .unwrap();
+
+    // The miner runs forever, so stop once the first row has been mined.
+    simulator.schedule_interruption(Interruption::AfterAction(FIRST_ROW_ACTIONS));
+    let result = simulator.run_program("tunnel_miner", &[]);
+
+    assert!(matches!(result, Err(SimulatorError::Interrupted)));
+    for position in [
+        Position::new(0, 0, -1),
+        Position::new(0, 1, -1),
+        Position::new(-1, 0, -1),
+        Position::new(-1, 1, -1),
+    ] {
+        assert_eq!(simulator.block_at(position).id, BlockId::AIR);
+    }
+    assert_eq!(
+        simulator.block_at(Position::new(0, 2, -1)).id,
+        BlockId::STONE
+    );
+    assert_eq!(
+        simulator.block_at(Position::new(-2, 0, -1)).id,
+        BlockId::STONE
+    );
+    assert_eq!(simulator.turtle().position, Position::new(1, 0, -1));
+}
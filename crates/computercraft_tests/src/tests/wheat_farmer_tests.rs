@@ -26,6 +26,7 @@ fn test_wheat_farmer() {
     simulator.turtle_mut().inventory[15] = Some(ItemStack {
         name: ItemId::new_static("minecraft:wheat_seeds"),
         count: 20,
+        nbt: None,
     });
     simulator.move_turtle_to(Position::new(0, 1, 0));
 
@@ -1,28 +1,61 @@
 mod block;
 pub mod blocks;
 mod item;
+mod nbt;
 pub mod world;
 
 pub use block::*;
 pub use item::*;
+pub use nbt::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ItemStack {
     pub name: ItemId,
     pub count: u32,
+    /// The data the items hold, such as a floppy disk's ID.
+    pub nbt: Option<Nbt>,
 }
 
 impl ItemStack {
     pub fn new(name: ItemId, count: u32) -> Self {
-        Self { name, count }
+        Self {
+            name,
+            count,
+            nbt: None,
+        }
+    }
+
+    pub fn with_nbt(self, nbt: Nbt) -> Self {
+        Self {
+            nbt: Some(nbt),
+            ..self
+        }
+    }
+
+    /// Returns whether the items can go in the same stack as the other ones.
+    pub fn can_stack_with(&self, other: &ItemStack) -> bool {
+        self.name == other.name && self.nbt == other.nbt
+    }
+
+    /// Splits up to `count` items off into a new stack.
+    pub fn split(&mut self, count: u32) -> ItemStack {
+        let count = self.count.min(count);
+        self.count -= count;
+
+        Self {
+            count,
+            ..self.clone()
+        }
     }
 
     pub fn is_empty(&self) -> bool {
         self.count == 0
     }
 
+    /// Returns how many of the items fit in a slot. Items holding data, such
+    /// as floppy disks, don't stack.
     pub fn max_stack_size(&self) -> u32 {
-        64
+        if self.nbt.is_some() { 1 } else { 64 }
     }
 
     pub fn space_left(&self) -> u32 {
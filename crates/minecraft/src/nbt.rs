@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+
+/// Extra data attached to an item, like Minecraft's NBT tags.
+pub type Nbt = BTreeMap<String, NbtValue>;
+
+/// A value in an item's [`Nbt`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NbtValue {
+    Int(i64),
+    String(String),
+    List(Vec<NbtValue>),
+}
+
+impl NbtValue {
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[NbtValue]> {
+        match self {
+            Self::List(values) => Some(values),
+            _ => None,
+        }
+    }
+}
@@ -89,12 +89,16 @@ impl Position {
 #[derive(Debug)]
 pub struct World {
     blocks: HashMap<Position, Block>,
+    redstone_power: HashMap<Position, u8>,
+    bundled_power: HashMap<Position, u16>,
 }
 
 impl World {
     pub fn new() -> Self {
         Self {
             blocks: HashMap::new(),
+            redstone_power: HashMap::new(),
+            bundled_power: HashMap::new(),
         }
     }
 
@@ -120,6 +124,35 @@ impl World {
     pub fn can_dig(&self, position: Position) -> bool {
         self.get_block(position).is_diggable()
     }
+
+    /// Returns the redstone power given off by whatever is at the position,
+    /// such as a lever or a redstone torch, from 0 to 15.
+    pub fn redstone_power(&self, position: Position) -> u8 {
+        self.redstone_power.get(&position).copied().unwrap_or(0)
+    }
+
+    /// Sets the redstone power given off at the position, capped at 15.
+    pub fn set_redstone_power(&mut self, position: Position, power: u8) {
+        if power == 0 {
+            self.redstone_power.remove(&position);
+        } else {
+            self.redstone_power.insert(position, power.min(15));
+        }
+    }
+
+    /// Returns the colors of the bundled cable at the position which are on,
+    /// as a bit set.
+    pub fn bundled_power(&self, position: Position) -> u16 {
+        self.bundled_power.get(&position).copied().unwrap_or(0)
+    }
+
+    pub fn set_bundled_power(&mut self, position: Position, colors: u16) {
+        if colors == 0 {
+            self.bundled_power.remove(&position);
+        } else {
+            self.bundled_power.insert(position, colors);
+        }
+    }
 }
 
 impl Default for World {
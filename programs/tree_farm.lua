@@ -1,9 +1,26 @@
 local inventory = require("lib.inventory")
 
-local log_kind = "minecraft:birch_log"
-local sapling_kind = "minecraft:birch_sapling"
-local track_kind = "minecraft:cobbled_deepslate"
-local tree_spacing = 2
+settings.define("tree_farm.tree_kind", {
+    description = "The kind of tree to farm, such as birch or spruce.",
+    default = "birch",
+    type = "string",
+})
+settings.define("tree_farm.track_kind", {
+    description = "The block marking the track the turtle follows between trees.",
+    default = "minecraft:cobbled_deepslate",
+    type = "string",
+})
+settings.define("tree_farm.tree_spacing", {
+    description = "The number of blocks between each tree.",
+    default = 2,
+    type = "number",
+})
+
+local tree_kind = settings.get("tree_farm.tree_kind")
+local log_kind = "minecraft:" .. tree_kind .. "_log"
+local sapling_kind = "minecraft:" .. tree_kind .. "_sapling"
+local track_kind = settings.get("tree_farm.track_kind")
+local tree_spacing = settings.get("tree_farm.tree_spacing")
 
 local function plant_sapling(sapling)
     local has_block, data = turtle.inspect()
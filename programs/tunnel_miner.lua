@@ -1,9 +1,25 @@
 local inventory = require("lib.inventory")
 
+settings.define("tunnel_miner.width", {
+    description = "The width of the tunnel.",
+    default = 3,
+    type = "number",
+})
+settings.define("tunnel_miner.height", {
+    description = "The height of the tunnel.",
+    default = 3,
+    type = "number",
+})
+settings.define("tunnel_miner.torch_spacing", {
+    description = "The number of rows between each torch.",
+    default = 5,
+    type = "number",
+})
+
 local torch = "minecraft:torch"
-local tunnel_width = 3
-local tunnel_height = 3
-local torch_spacing = 5
+local tunnel_width = settings.get("tunnel_miner.width")
+local tunnel_height = settings.get("tunnel_miner.height")
+local torch_spacing = settings.get("tunnel_miner.torch_spacing")
 
 local function place_torch(height)
     if not inventory.select_item(torch) then
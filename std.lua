@@ -4,7 +4,15 @@ local std = {}
 ---
 --- @return boolean
 function std.is_main()
-    -- We're adding an additional frame to account for the `std` module being loaded.
+    -- When run from the shell, the script is the main script if it is the
+    -- program the shell is running, rather than a module it required.
+    local source = debug.getinfo(2, "S").source
+    if shell and source:sub(1, 1) == "@" then
+        return fs.combine(source:sub(2)) == fs.combine(shell.getRunningProgram())
+    end
+
+    -- Otherwise, the main script is the one at the bottom of the stack. We're
+    -- adding an additional frame to account for the `std` module being loaded.
     return not pcall(debug.getlocal, 4 + 1, 1)
 end
 